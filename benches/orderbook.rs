@@ -0,0 +1,220 @@
+//! Latency of the matching engine per operation.
+//!
+//! Besides criterion's own estimates, every scenario prints the p50, p99 and
+//! p999 of the individual calls of the last sample criterion measured, so the
+//! tail shows up as well.
+
+use std::hint::black_box;
+use std::time::{Duration, Instant};
+
+use criterion::{criterion_group, criterion_main, Criterion};
+use rand::rngs::StdRng;
+use rand::{Rng, SeedableRng};
+
+use orderbook::engine::{Engine, Event, Order, OrderId, Orderbook, Trade};
+use orderbook::generator::Generator;
+use orderbook::{Asset, Exchange, OrderSide};
+
+type Book = Orderbook<Order, Event<Order>, Trade>;
+
+const PAIR: &str = "BTC/USDC";
+const SEED: u64 = 0x0b00c;
+/// Price levels resting on each side, one unit (100) apart.
+const LEVELS: u64 = 50;
+const ORDERS_PER_LEVEL: u64 = 20;
+const BEST_BID: u64 = 100_000;
+const BEST_ASK: u64 = 100_100;
+
+/// Per-call latencies, in nanoseconds, of the last sample criterion took.
+///
+/// Samples are cleared as each one starts, so the warm-up is left out and
+/// memory stays bounded by the largest sample.
+#[derive(Default)]
+struct Latencies(Vec<u64>);
+
+impl Latencies {
+    #[inline]
+    fn start(&mut self) {
+        self.0.clear();
+    }
+
+    #[inline]
+    fn time<T>(&mut self, f: impl FnOnce() -> T) -> Duration {
+        let start = Instant::now();
+        black_box(f());
+        let elapsed = start.elapsed();
+        self.0.push(elapsed.as_nanos() as u64);
+        elapsed
+    }
+
+    fn report(mut self, name: &str) {
+        // Benchmarks filtered out never take a sample.
+        if self.0.is_empty() {
+            return;
+        }
+        self.0.sort_unstable();
+        let at = |quantile: f64| {
+            self.0[((self.0.len() - 1) as f64 * quantile) as usize]
+        };
+        println!(
+            "{name:<16} p50 {:>9} ns   p99 {:>9} ns   p999 {:>9} ns   ({} calls)",
+            at(0.5),
+            at(0.99),
+            at(0.999),
+            self.0.len(),
+        );
+    }
+}
+
+/// Hands out order ids never used before.
+struct Ids(u64);
+
+impl Ids {
+    #[inline]
+    fn next(&mut self) -> OrderId {
+        self.0 += 1;
+        OrderId::new(self.0)
+    }
+}
+
+/// Return a book with `LEVELS` levels of `ORDERS_PER_LEVEL` orders of 1 on
+/// each side, along with the ids of the orders resting.
+fn book(ids: &mut Ids) -> (Book, Vec<OrderId>) {
+    let mut book = Book::new(PAIR);
+    let mut resting = Vec::new();
+    for level in 0..LEVELS {
+        for _ in 0..ORDERS_PER_LEVEL {
+            for (side, price) in [
+                (OrderSide::Ask, BEST_ASK + level * 100),
+                (OrderSide::Bid, BEST_BID - level * 100),
+            ] {
+                let order_id = ids.next();
+                book.insert(Order::new(order_id, 1, side, price, 100));
+                resting.push(order_id);
+            }
+        }
+    }
+    (book, resting)
+}
+
+/// A random order of 1 resting within the book, without crossing it.
+fn passive(rng: &mut StdRng, ids: &mut Ids) -> Order {
+    let level = rng.gen_range(0..LEVELS) * 100;
+    let (side, price) = match rng.gen_bool(0.5) {
+        true => (OrderSide::Ask, BEST_ASK + level),
+        false => (OrderSide::Bid, BEST_BID - level),
+    };
+    Order::new(ids.next(), rng.gen_range(1..10), side, price, 100)
+}
+
+fn passive_insert(c: &mut Criterion) {
+    let mut rng = StdRng::seed_from_u64(SEED);
+    let mut ids = Ids(0);
+    let (mut book, _) = book(&mut ids);
+    let mut latencies = Latencies::default();
+
+    c.bench_function("passive_insert", |b| {
+        b.iter_custom(|iters| {
+            latencies.start();
+            let mut elapsed = Duration::ZERO;
+            for _ in 0..iters {
+                let order = passive(&mut rng, &mut ids);
+                let order_id = order.id();
+                elapsed += latencies.time(|| book.matching(order));
+                // Keep the book the same size from one call to the next.
+                book.remove(&order_id);
+            }
+            elapsed
+        })
+    });
+    latencies.report("passive_insert");
+}
+
+fn sweep(c: &mut Criterion) {
+    const SWEPT: u64 = 10;
+    let mut ids = Ids(0);
+    let (mut book, _) = book(&mut ids);
+    let mut latencies = Latencies::default();
+
+    c.bench_function("sweep_10_levels", |b| {
+        b.iter_custom(|iters| {
+            latencies.start();
+            let mut elapsed = Duration::ZERO;
+            for _ in 0..iters {
+                let taker = Order::new(
+                    ids.next(),
+                    2,
+                    OrderSide::Bid,
+                    BEST_ASK + (SWEPT - 1) * 100,
+                    SWEPT * ORDERS_PER_LEVEL * 100,
+                );
+                elapsed += latencies.time(|| book.matching(taker));
+                // Put back what was swept.
+                for level in 0..SWEPT {
+                    for _ in 0..ORDERS_PER_LEVEL {
+                        book.insert(Order::new(
+                            ids.next(),
+                            1,
+                            OrderSide::Ask,
+                            BEST_ASK + level * 100,
+                            100,
+                        ));
+                    }
+                }
+            }
+            elapsed
+        })
+    });
+    latencies.report("sweep_10_levels");
+}
+
+fn cancel(c: &mut Criterion) {
+    let mut rng = StdRng::seed_from_u64(SEED);
+    let mut ids = Ids(0);
+    let (mut book, mut resting) = book(&mut ids);
+    let mut latencies = Latencies::default();
+
+    c.bench_function("cancel", |b| {
+        b.iter_custom(|iters| {
+            latencies.start();
+            let mut elapsed = Duration::ZERO;
+            for _ in 0..iters {
+                let index = rng.gen_range(0..resting.len());
+                let order_id = resting.swap_remove(index);
+                elapsed += latencies.time(|| book.remove(&order_id));
+                // Keep the book the same size from one call to the next.
+                let order = passive(&mut rng, &mut ids);
+                resting.push(order.id());
+                book.insert(order);
+            }
+            elapsed
+        })
+    });
+    latencies.report("cancel");
+}
+
+/// The flow `orders_generator` writes by default.
+fn mixed(c: &mut Criterion) {
+    let mut requests = Generator::new(SEED);
+    let mut engine = Engine::new(PAIR);
+    let mut events = Vec::with_capacity(64);
+    let mut latencies = Latencies::default();
+
+    c.bench_function("mixed_flow", |b| {
+        b.iter_custom(|iters| {
+            latencies.start();
+            let mut elapsed = Duration::ZERO;
+            for _ in 0..iters {
+                let request = requests.next().expect("an endless flow");
+                events.clear();
+                elapsed += latencies
+                    .time(|| engine.try_process_into(request, &mut events));
+            }
+            elapsed
+        })
+    });
+    latencies.report("mixed_flow");
+}
+
+criterion_group!(benches, passive_insert, sweep, cancel, mixed);
+criterion_main!(benches);
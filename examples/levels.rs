@@ -0,0 +1,113 @@
+//! Time inserts, cancels and sweeps on a book of 200k orders over 50 levels
+//! per side, which is how the storage of resting orders is compared from one
+//! revision to the next:
+//!
+//!     cargo run --release --example levels
+//!
+//! It only uses the `Exchange` interface, so the same file runs unchanged on
+//! older revisions of the crate.
+
+use std::hint::black_box;
+use std::time::Instant;
+
+use rand::rngs::StdRng;
+use rand::seq::SliceRandom;
+use rand::SeedableRng;
+
+use orderbook::engine::{Event, Order, OrderId, Orderbook, Trade};
+use orderbook::{Exchange, OrderSide};
+
+type Book = Orderbook<Order, Event<Order>, Trade>;
+
+const ORDERS: u64 = 200_000;
+const LEVELS: u64 = 50;
+const BEST_BID: u64 = 100_000;
+const BEST_ASK: u64 = 100_100;
+/// Orders each sweep takes from the best ask.
+const FILLS: u64 = 10;
+const SWEEPS: u64 = 1_000;
+const RUNS: usize = 5;
+const SEED: u64 = 0x0b00c;
+
+/// Return the orders of the book, of one unit each, alternating sides and
+/// cycling through the levels.
+fn orders() -> Vec<Order> {
+    (1..=ORDERS)
+        .map(|id| {
+            let level = (id / 2) % LEVELS;
+            let (side, price) = match id % 2 {
+                0 => (OrderSide::Ask, BEST_ASK + level * 100),
+                _ => (OrderSide::Bid, BEST_BID - level * 100),
+            };
+            Order::new(OrderId::new(id), id, side, price, 1)
+        })
+        .collect()
+}
+
+fn book() -> Book {
+    let mut book = Book::new("BTC/USDC");
+    for order in orders() {
+        book.insert(order);
+    }
+    book
+}
+
+/// Return the nanoseconds per operation of a run of `operations`.
+fn per(operations: u64, run: impl FnOnce()) -> u64 {
+    let start = Instant::now();
+    run();
+    start.elapsed().as_nanos() as u64 / operations
+}
+
+/// Return the median of the runs of `run`.
+fn median(mut run: impl FnMut() -> u64) -> u64 {
+    let mut runs = (0..RUNS).map(|_| run()).collect::<Vec<_>>();
+    runs.sort_unstable();
+    runs[RUNS / 2]
+}
+
+fn main() {
+    let mut rng = StdRng::seed_from_u64(SEED);
+
+    let insert = median(|| {
+        let (mut book, orders) = (Book::new("BTC/USDC"), orders());
+        per(ORDERS, || {
+            for order in orders {
+                book.insert(order);
+            }
+        })
+    });
+
+    let cancel = median(|| {
+        let mut book = book();
+        let mut ids = (1..=ORDERS).map(OrderId::new).collect::<Vec<_>>();
+        ids.shuffle(&mut rng);
+        per(ORDERS, || {
+            for order_id in &ids {
+                black_box(book.remove(order_id));
+            }
+        })
+    });
+
+    let sweep = median(|| {
+        let mut book = book();
+        let sweeps = (0..SWEEPS)
+            .map(|i| {
+                let order_id = OrderId::new(ORDERS + 1 + i);
+                Order::new(order_id, 0, OrderSide::Bid, u64::MAX, FILLS)
+            })
+            .collect::<Vec<_>>();
+        per(SWEEPS, || {
+            for order in sweeps {
+                black_box(book.matching(order));
+            }
+        })
+    });
+
+    println!(
+        "{ORDERS} orders over {LEVELS} levels per side, median of {RUNS} runs:"
+    );
+    println!("  insert {insert:>9} ns   per order");
+    println!("  cancel {cancel:>9} ns   per order");
+    println!("  sweep  {sweep:>9} ns   per {FILLS}-fill sweep");
+}
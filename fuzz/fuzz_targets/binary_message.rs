@@ -0,0 +1,47 @@
+//! Binary messages from a peer must be decoded or refused, never panic, and
+//! every request decoded must be one the engine can process.
+
+#![no_main]
+
+use libfuzzer_sys::fuzz_target;
+
+use orderbook::binary::{
+    decode_request, decode_response, encode_request, encode_response,
+};
+use orderbook::engine::Engine;
+
+fuzz_target!(|data: &[u8]| {
+    let mut engine = Engine::new("BTC/USDC");
+    let mut buffer = data;
+    while let Ok(Some((request, length))) = decode_request(buffer) {
+        assert!(length > 0 && length <= buffer.len());
+
+        // What was decoded encodes back to a message decoding the same.
+        let mut encoded = Vec::new();
+        encode_request(&request, &mut encoded);
+        let (decoded, _) = decode_request(&encoded)
+            .expect("an encoded request")
+            .expect("a whole request");
+        assert_eq!(decoded, request);
+
+        let request = request.into_order_request("BTC/USDC");
+        assert!(engine.try_process(request).is_ok());
+        assert_eq!(engine.orderbook().validate(), Ok(()));
+
+        buffer = &buffer[length..];
+    }
+
+    let mut buffer = data;
+    while let Ok(Some((response, length))) = decode_response(buffer) {
+        assert!(length > 0 && length <= buffer.len());
+
+        let mut encoded = Vec::new();
+        encode_response(&response, &mut encoded);
+        let (decoded, _) = decode_response(&encoded)
+            .expect("an encoded response")
+            .expect("a whole response");
+        assert_eq!(decoded, response);
+
+        buffer = &buffer[length..];
+    }
+});
@@ -0,0 +1,358 @@
+//! Any sequence of operations leaves the book consistent, and uncrossed
+//! while trading continuously. Fees never exceed what they are taken from,
+//! the ledger reserves exactly what resting orders hold, and the execution
+//! reports agree with the orders left in the book.
+
+#![no_main]
+
+use std::collections::HashSet;
+
+use arbitrary::Arbitrary;
+use libfuzzer_sys::fuzz_target;
+use rust_decimal::Decimal;
+
+use orderbook::engine::{
+    CircuitBreaker, Engine, Event, ExecType, ExecutionReport, FeeSchedule,
+    FeeTier, Hybrid, Ledger, ManualClock, Order, OrderId, OrderRequest, Phase,
+    ProRata, ReportGenerator, RiskLimits, VolatilityBand,
+};
+use orderbook::{Asset, ExchangeExt, OrderSide};
+
+const PAIR: &str = "BTC/USDC";
+const BASE: &str = "BTC";
+const QUOTE: &str = "USDC";
+
+/// Units close to each other, so orders meet, or anywhere in range.
+#[derive(Arbitrary, Clone, Copy, Debug)]
+enum Units {
+    Near(u8),
+    Any(u64),
+}
+
+impl Units {
+    fn get(self) -> u64 {
+        match self {
+            Units::Near(units) => 10_000 + u64::from(units),
+            Units::Any(units) => units,
+        }
+    }
+}
+
+#[derive(Arbitrary, Debug)]
+enum Strategy {
+    Fifo,
+    ProRata {
+        lot: u8,
+    },
+    Hybrid {
+        top_order: bool,
+        lmm: u8,
+        basis_points: u16,
+    },
+}
+
+/// Rates in basis points.
+#[derive(Arbitrary, Debug)]
+struct Fees {
+    maker_rate: i16,
+    taker_rate: i16,
+    /// Minimum volume and rates of a second tier.
+    tier: Option<(u32, i16, i16)>,
+}
+
+#[derive(Arbitrary, Debug)]
+struct Setup {
+    strategy: Strategy,
+    /// Threshold, window and auction length of a volatility band.
+    band: Option<(u16, u16, u16)>,
+    /// Price band of the risk limits, in basis points.
+    price_band: Option<u16>,
+    fees: Option<Fees>,
+    /// Deposits of the base asset, or of the quote one, backing the orders.
+    ledger: Option<Vec<(u8, bool, u32)>>,
+}
+
+#[derive(Arbitrary, Debug)]
+enum Op {
+    Create {
+        order_id: u8,
+        account_id: u8,
+        ask: bool,
+        limit_price: Units,
+        amount: Units,
+    },
+    Delete {
+        order_id: u8,
+    },
+    Modify {
+        order_id: u8,
+        limit_price: Units,
+        amount: Units,
+    },
+    /// Any request, amounts and prices given as a mantissa and a scale.
+    Request {
+        order_id: u8,
+        account_id: u8,
+        ask: bool,
+        limit_price: (i64, u8),
+        amount: (i64, u8),
+    },
+    DeleteAll {
+        account_id: u8,
+        side: Option<bool>,
+    },
+    SetPhase(u8),
+    Advance(u16),
+}
+
+fn side(ask: bool) -> OrderSide {
+    match ask {
+        true => OrderSide::Ask,
+        false => OrderSide::Bid,
+    }
+}
+
+fn decimal((mantissa, scale): (i64, u8)) -> Decimal {
+    Decimal::try_new(mantissa, u32::from(scale)).unwrap_or_default()
+}
+
+/// Fee rate within 100%, either way.
+fn rate(basis_points: i16) -> i64 {
+    i64::from(basis_points) % 10_001
+}
+
+/// Check what holds after every operation.
+fn check(
+    engine: &Engine,
+    events: &[Event<Order>],
+    reports: &[ExecutionReport],
+) {
+    assert_eq!(engine.orderbook().validate(), Ok(()));
+    if engine.phase() == Phase::Continuous {
+        if let Some((ask, bid)) = engine.orderbook().spread() {
+            assert!(ask > bid, "crossed book: {ask} <= {bid}");
+        }
+    }
+
+    // Fees are taken out of what each side receives.
+    for event in events {
+        if let Event::Traded(trade) = event {
+            let cost =
+                u128::from(trade.amount()) * u128::from(trade.price()) / 100;
+            let (buyer_fee, seller_fee) = match trade.aggressor() {
+                OrderSide::Bid => (trade.taker_fee(), trade.maker_fee()),
+                OrderSide::Ask => (trade.maker_fee(), trade.taker_fee()),
+            };
+            assert!(buyer_fee.unsigned_abs() <= trade.amount(), "{trade:?}");
+            assert!(u128::from(seller_fee.unsigned_abs()) <= cost, "{trade:?}");
+        }
+    }
+
+    if let Some(ledger) = engine.ledger() {
+        for (asset, side) in [(BASE, OrderSide::Ask), (QUOTE, OrderSide::Bid)] {
+            let reserved = (0..=u64::from(u8::MAX))
+                .map(|account_id| {
+                    let balance = ledger.balance(account_id, asset);
+                    assert!(balance.reserved() <= balance.total());
+                    u128::from(balance.reserved())
+                })
+                .sum::<u128>();
+            let held = engine
+                .orderbook()
+                .iter()
+                .filter(|order| order.side() == side)
+                .map(|order| u128::from(ledger.reserved(&order.id())))
+                .sum::<u128>();
+            assert_eq!(reserved, held, "{asset} reserved");
+        }
+    }
+
+    // The last report of an order still resting tells how much it has left.
+    // Rejections are about the refused request instead, which may reuse the
+    // id of a resting order.
+    let mut reported = HashSet::new();
+    for report in reports.iter().rev() {
+        if report.exec_type() == ExecType::Rejected
+            || !reported.insert(report.order_id())
+        {
+            continue;
+        }
+        if let Some(order) = engine.order(&report.order_id()) {
+            assert_eq!(
+                (report.leaves_qty(), report.cum_qty()),
+                (order.remaining(), order.filled()),
+                "{report:?}"
+            );
+        }
+    }
+}
+
+fuzz_target!(|input: (Setup, Vec<Op>)| {
+    let (setup, ops) = input;
+    let clock = ManualClock::new(0);
+    let mut engine = Engine::new(PAIR).with_clock(clock.clone());
+    engine = match setup.strategy {
+        Strategy::Fifo => engine,
+        Strategy::ProRata { lot } => {
+            engine.with_matching(ProRata::new().with_lot(u64::from(lot).max(1)))
+        }
+        Strategy::Hybrid {
+            top_order,
+            lmm,
+            basis_points,
+        } => {
+            let mut hybrid = Hybrid::new()
+                .with_lmm(u64::from(lmm), u64::from(basis_points) % 10_001);
+            if top_order {
+                hybrid = hybrid.with_top_order();
+            }
+            engine.with_matching(hybrid)
+        }
+    };
+    if let Some((threshold, window, auction)) = setup.band {
+        engine = engine.with_circuit_breaker(CircuitBreaker::new(
+            VolatilityBand::new(
+                u64::from(threshold),
+                u64::from(window),
+                u64::from(auction),
+            ),
+        ));
+    }
+    if let Some(fees) = setup.fees {
+        let mut schedule =
+            FeeSchedule::new(rate(fees.maker_rate), rate(fees.taker_rate));
+        if let Some((min_volume, maker_rate, taker_rate)) = fees.tier {
+            schedule = schedule.with_tier(FeeTier::new(
+                min_volume.into(),
+                rate(maker_rate),
+                rate(taker_rate),
+            ));
+        }
+        engine = engine.with_fees(schedule);
+    }
+    if let Some(deposits) = setup.ledger {
+        let mut ledger = Ledger::new();
+        for (account_id, base, amount) in deposits {
+            let asset = match base {
+                true => BASE,
+                false => QUOTE,
+            };
+            ledger.deposit(account_id.into(), asset, amount.into());
+        }
+        engine = engine.with_ledger(ledger);
+    }
+    if let Some(price_band) = setup.price_band {
+        engine
+            .risk_mut()
+            .set_limits(RiskLimits::new().with_price_band(price_band.into()));
+    }
+
+    let mut generator = ReportGenerator::new();
+    for op in ops {
+        let (events, reports) = match op {
+            Op::Create {
+                order_id,
+                account_id,
+                ask,
+                limit_price,
+                amount,
+            } => {
+                let order = || {
+                    Order::new(
+                        OrderId::new(order_id.into()),
+                        account_id.into(),
+                        side(ask),
+                        limit_price.get(),
+                        amount.get(),
+                    )
+                };
+                let events = engine.create(order());
+                let reports = generator.created(&order(), &events);
+                (events, reports)
+            }
+            Op::Delete { order_id } => {
+                let events = engine.delete(OrderId::new(order_id.into()));
+                let reports = generator.reports(&events);
+                (events, reports)
+            }
+            Op::Modify {
+                order_id,
+                limit_price,
+                amount,
+            } => {
+                let order_id = OrderId::new(order_id.into());
+                let (limit_price, amount) = (limit_price.get(), amount.get());
+                let events = engine.modify(order_id, limit_price, amount);
+                let reports =
+                    generator.modified(order_id, limit_price, amount, &events);
+                (events, reports)
+            }
+            Op::Request {
+                order_id,
+                account_id,
+                ask,
+                limit_price,
+                amount,
+            } => process(
+                &mut engine,
+                &mut generator,
+                OrderRequest::Create {
+                    account_id: account_id.to_string().into(),
+                    amount: decimal(amount),
+                    order_id: order_id.to_string().into(),
+                    pair: PAIR.into(),
+                    limit_price: decimal(limit_price),
+                    side: side(ask),
+                },
+            ),
+            Op::DeleteAll { account_id, side } => process(
+                &mut engine,
+                &mut generator,
+                OrderRequest::DeleteAll {
+                    account_id: account_id.to_string().into(),
+                    side: side.map(self::side),
+                    pair: None,
+                },
+            ),
+            Op::SetPhase(phase) => {
+                let events = engine.set_phase(match phase % 6 {
+                    0 => Phase::Halted,
+                    1 => Phase::CancelOnly,
+                    2 => Phase::PreOpen,
+                    3 => Phase::Continuous,
+                    4 => Phase::Auction,
+                    _ => Phase::Closed,
+                });
+                let reports = generator.reports(&events);
+                (events, reports)
+            }
+            Op::Advance(millis) => {
+                clock.advance(millis.into());
+                let events = engine.tick();
+                let reports = generator.reports(&events);
+                (events, reports)
+            }
+        };
+
+        check(&engine, &events, &reports);
+    }
+});
+
+/// Process `request`, malformed or not, and report on what it did, like the
+/// gateways do.
+fn process(
+    engine: &mut Engine,
+    generator: &mut ReportGenerator,
+    request: OrderRequest,
+) -> (Vec<Event<Order>>, Vec<ExecutionReport>) {
+    let mut events = engine.tick();
+    let mut reports = generator.reports(&events);
+    let ticked = events.len();
+    if engine
+        .try_process_into(request.clone(), &mut events)
+        .is_ok()
+    {
+        reports.extend(generator.generate(&request, &events[ticked..]));
+    }
+    (events, reports)
+}
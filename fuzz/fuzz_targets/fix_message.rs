@@ -0,0 +1,24 @@
+//! FIX messages from a peer must be decoded or refused, never panic.
+
+#![no_main]
+
+use libfuzzer_sys::fuzz_target;
+
+use orderbook::fix::{decode_request, Message};
+
+fuzz_target!(|data: &[u8]| {
+    let mut buffer = data;
+    while let Ok(Some((message, length))) = Message::decode(buffer) {
+        assert!(length > 0 && length <= buffer.len());
+        let _ = decode_request(&message);
+
+        // What was decoded encodes back to a message decoding the same.
+        let encoded = message.encode();
+        let (decoded, _) = Message::decode(&encoded)
+            .expect("an encoded message")
+            .expect("a whole message");
+        assert_eq!(decoded, message);
+
+        buffer = &buffer[length..];
+    }
+});
@@ -0,0 +1,24 @@
+//! Malformed order requests must be reported, never panic.
+
+#![no_main]
+
+use libfuzzer_sys::fuzz_target;
+
+use orderbook::engine::{Engine, Order, OrderRequest};
+use orderbook::Asset;
+
+fuzz_target!(|data: &[u8]| {
+    let request: OrderRequest = match serde_json::from_slice(data) {
+        Ok(request) => request,
+        Err(_) => return,
+    };
+
+    if let Ok(order) = Order::try_from(request.clone()) {
+        assert!(order.filled() == 0 && order.remaining() == order.amount());
+    }
+
+    let mut engine = Engine::new("BTC/USDC");
+    if engine.try_process(request).is_ok() {
+        assert_eq!(engine.orderbook().validate(), Ok(()));
+    }
+});
@@ -1,43 +1,148 @@
-use std::io::Result;
-use std::path::Path;
+use std::fs::File;
+use std::io::{BufWriter, Error, ErrorKind, Result, Write};
+use std::path::PathBuf;
 
-use compact_str::{format_compact, CompactString};
-use rand::Rng;
+use clap::{ArgEnum, Parser};
 
-use orderbook::engine::OrderRequest;
-use orderbook::OrderSide;
+use orderbook::generator::{self, Generator};
 
-const N: usize = 7_500_000;
+#[derive(Parser)]
+#[clap(author, version, about = "Generate a flow of order requests")]
+struct Args {
+    #[clap(
+        short = 'n',
+        long,
+        default_value = "7500000",
+        help = "Number of requests"
+    )]
+    count: usize,
+    #[clap(
+        short,
+        long,
+        default_value = "orders.json",
+        help = "Requests destination"
+    )]
+    output: PathBuf,
+    #[clap(
+        short,
+        long,
+        arg_enum,
+        default_value = "json",
+        help = "Requests format"
+    )]
+    format: Format,
+    #[clap(short, long, help = "Random seed, printed when not given")]
+    seed: Option<u64>,
+    #[clap(long, default_value = "1", help = "Number of pairs traded")]
+    pairs: usize,
+    #[clap(long, default_value = "9", help = "Number of accounts trading")]
+    accounts: u64,
+    #[clap(
+        long,
+        default_value = "0.001",
+        help = "Share of requests deleting a live order"
+    )]
+    cancel_ratio: f64,
+    #[clap(
+        long,
+        default_value = "0",
+        help = "Share of requests modifying a live order"
+    )]
+    modify_ratio: f64,
+    #[clap(
+        long,
+        arg_enum,
+        default_value = "normal",
+        help = "Distribution of the prices around the mid"
+    )]
+    prices: Distribution,
+    #[clap(long, default_value = "1500", help = "Initial mid price")]
+    mid: f64,
+    #[clap(
+        long,
+        default_value = "50",
+        help = "Half-width, or standard deviation, of the prices around the mid"
+    )]
+    width: f64,
+    #[clap(
+        long,
+        default_value = "0.5",
+        help = "Standard deviation of the mid move on each request"
+    )]
+    volatility: f64,
+}
 
-fn main() -> Result<()> {
-    let mut rng = rand::thread_rng();
-    let mut orders = Vec::with_capacity(N);
-
-    for i in 1..=N {
-        let order = match rng.gen_range(0..1000) {
-            0 => OrderRequest::Delete {
-                order_id: format_compact!("{}", rng.gen_range(1..=i as u64)),
-            },
-            _ => OrderRequest::Create {
-                account_id: format_compact!("{}", rng.gen_range(1..10)),
-                amount: rng.gen_range(1000..2000).into(),
-                order_id: format_compact!("{}", i as u64),
-                pair: CompactString::new_inline("BTC/USDC"),
-                limit_price: rng.gen_range(1000..2000).into(),
-                side: match rng.gen_range(0..2) {
-                    0 => OrderSide::Ask,
-                    _ => OrderSide::Bid,
-                },
-            },
-        };
-
-        orders.push(order);
+#[derive(Clone, ArgEnum)]
+enum Format {
+    /// A single JSON array, as `orderbook --format json` reads.
+    Json,
+    /// One JSON request per line, as `orderbook --format jsonl` reads.
+    Jsonl,
+}
+
+#[derive(Clone, Copy, ArgEnum)]
+enum Distribution {
+    Uniform,
+    Normal,
+}
+
+impl From<Distribution> for generator::Distribution {
+    #[inline]
+    fn from(distribution: Distribution) -> Self {
+        match distribution {
+            Distribution::Uniform => generator::Distribution::Uniform,
+            Distribution::Normal => generator::Distribution::Normal,
+        }
     }
+}
 
-    let content = serde_json::to_string(&orders)?;
-    let path = Path::new("./orders.json");
+fn main() -> Result<()> {
+    let args = Args::parse();
+    if args.pairs == 0 || args.accounts == 0 {
+        return Err(Error::new(
+            ErrorKind::InvalidInput,
+            "at least one pair and one account are needed",
+        ));
+    }
+    let ratios = [args.cancel_ratio, args.modify_ratio];
+    if ratios.iter().any(|ratio| !(0.0..=1.0).contains(ratio))
+        || ratios.iter().sum::<f64>() > 1.0
+    {
+        return Err(Error::new(
+            ErrorKind::InvalidInput,
+            "the cancel and modify ratios must add up to at most 1",
+        ));
+    }
 
-    std::fs::write(&path, content)?;
+    let seed = args.seed.unwrap_or_else(|| {
+        let seed = rand::random();
+        eprintln!("Seed: {seed}");
+        seed
+    });
+    let generator = Generator::new(seed)
+        .with_pairs(args.pairs)
+        .with_accounts(args.accounts)
+        .with_ratios(args.cancel_ratio, args.modify_ratio)
+        .with_prices(args.prices.into(), args.mid, args.width)
+        .with_volatility(args.volatility);
 
-    Ok(())
+    let mut output = BufWriter::new(File::create(&args.output)?);
+    if let Format::Json = args.format {
+        output.write_all(b"[")?;
+    }
+    for (i, request) in generator.take(args.count).enumerate() {
+        match args.format {
+            Format::Json if i > 0 => output.write_all(b",")?,
+            Format::Json => {}
+            Format::Jsonl => {}
+        }
+        serde_json::to_writer(&mut output, &request)?;
+        if let Format::Jsonl = args.format {
+            output.write_all(b"\n")?;
+        }
+    }
+    if let Format::Json = args.format {
+        output.write_all(b"]")?;
+    }
+    output.flush()
 }
@@ -0,0 +1,178 @@
+use std::io::Result;
+
+use clap::Parser;
+use compact_str::{format_compact, CompactString};
+use rust_decimal::Decimal;
+
+use orderbook::simulation::{
+    MarketMaker, MomentumTaker, NoiseTrader, Report, Simulation,
+};
+
+#[derive(Parser)]
+#[clap(author, version, about = "Simulate agents trading on a single pair")]
+struct Args {
+    #[clap(short, long, default_value = "BTC/USDC")]
+    pair: CompactString,
+    #[clap(short, long, help = "Random seed, printed when not given")]
+    seed: Option<u64>,
+    #[clap(short, long, default_value = "3600", help = "Simulated seconds")]
+    duration: u64,
+    #[clap(
+        long,
+        default_value = "60",
+        help = "Seconds between samples of the spread"
+    )]
+    interval: u64,
+    #[clap(long, default_value = "1500", help = "Initial reference price")]
+    price: f64,
+    #[clap(long, default_value = "20", help = "Number of noise traders")]
+    noise: usize,
+    #[clap(
+        long,
+        default_value = "1",
+        help = "Arrivals per second of each noise trader"
+    )]
+    noise_rate: f64,
+    #[clap(long, default_value = "2", help = "Number of market makers")]
+    makers: usize,
+    #[clap(
+        long,
+        default_value = "2",
+        help = "Arrivals per second of each market maker"
+    )]
+    maker_rate: f64,
+    #[clap(long, default_value = "5", help = "Number of momentum takers")]
+    momentum: usize,
+    #[clap(
+        long,
+        default_value = "0.5",
+        help = "Arrivals per second of each momentum taker"
+    )]
+    momentum_rate: f64,
+    #[clap(
+        long,
+        default_value = "0.001",
+        help = "Move, relative to the price, momentum takers trade on"
+    )]
+    threshold: f64,
+    #[clap(long, help = "Print the report as JSON")]
+    json: bool,
+}
+
+fn main() -> Result<()> {
+    let args = Args::parse();
+    let seed = args.seed.unwrap_or_else(|| {
+        let seed = rand::random();
+        eprintln!("Seed: {seed}");
+        seed
+    });
+
+    let price = (args.price * 100.0).round().max(1.0) as u64;
+    let threshold = (args.price * args.threshold * 100.0).round() as u64;
+    let mut simulation =
+        Simulation::new(&args.pair, seed).with_interval(args.interval * 1_000);
+    for i in 1..=args.makers {
+        let name = format_compact!("maker-{i}");
+        simulation.add(MarketMaker::new(&name, args.maker_rate, price));
+    }
+    for i in 1..=args.noise {
+        let name = format_compact!("noise-{i}");
+        simulation.add(NoiseTrader::new(&name, args.noise_rate, price));
+    }
+    for i in 1..=args.momentum {
+        let name = format_compact!("momentum-{i}");
+        simulation.add(MomentumTaker::new(
+            &name,
+            args.momentum_rate,
+            threshold.max(1),
+        ));
+    }
+    simulation.run(args.duration * 1_000);
+
+    let report = simulation.report();
+    match args.json {
+        true => println!("{}", serde_json::to_string(&report)?),
+        false => summary(&args.pair, args.duration, seed, &report),
+    }
+    Ok(())
+}
+
+fn summary(pair: &str, duration: u64, seed: u64, report: &Report) {
+    println!("Simulated {duration}s of {pair} with seed {seed}");
+    println!("  Trades:     {}", report.trades());
+    println!("  Volume:     {}", units(report.volume()));
+    if let Some(price) = report.last_price() {
+        println!("  Last price: {}", units(price));
+    }
+    println!();
+
+    let spreads = report
+        .spreads()
+        .iter()
+        .filter_map(|sample| sample.spread())
+        .collect::<Vec<_>>();
+    println!("Spread over time:");
+    println!(
+        "  {:>8} {:>12} {:>12} {:>10}",
+        "time (s)", "bid", "ask", "spread"
+    );
+    for sample in report.spreads() {
+        let price = |price: Option<u64>| {
+            price.map_or_else(
+                || "-".to_owned(),
+                |price| units(price).to_string(),
+            )
+        };
+        println!(
+            "  {:>8} {:>12} {:>12} {:>10}",
+            sample.time() / 1_000,
+            price(sample.bid()),
+            price(sample.ask()),
+            price(sample.spread()),
+        );
+    }
+    if let (Some(min), Some(max)) = (spreads.iter().min(), spreads.iter().max())
+    {
+        let mean = spreads.iter().sum::<u64>() / spreads.len() as u64;
+        println!(
+            "  min {}, mean {}, max {} over {} of {} samples with both sides",
+            units(*min),
+            units(mean),
+            units(*max),
+            spreads.len(),
+            report.spreads().len(),
+        );
+    }
+    println!();
+
+    println!("Agents:");
+    println!(
+        "  {:<12} {:>8} {:>8} {:>8} {:>8} {:>12} {:>12} {:>14}",
+        "",
+        "orders",
+        "cancels",
+        "rejects",
+        "trades",
+        "volume",
+        "position",
+        "pnl"
+    );
+    for agent in report.agents() {
+        println!(
+            "  {:<12} {:>8} {:>8} {:>8} {:>8} {:>12} {:>12} {:>14}",
+            agent.name(),
+            agent.orders(),
+            agent.cancels(),
+            agent.rejects(),
+            agent.trades(),
+            units(agent.volume()),
+            units(agent.position()),
+            units(agent.pnl()),
+        );
+    }
+}
+
+/// Format engine units, which hold two decimals.
+fn units(units: impl Into<i128>) -> Decimal {
+    Decimal::from_i128_with_scale(units.into(), 2)
+}
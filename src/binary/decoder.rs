@@ -0,0 +1,147 @@
+use super::encoder::reason_code;
+use super::{msg_type, DecodeError, Request, Response, HEADER_LENGTH};
+use crate::engine::{Order, OrderId, RejectReason};
+use crate::OrderSide;
+
+/// Decode the first request in `buffer`, reading fields in place.
+///
+/// Return `Ok(None)` when `buffer` does not hold a whole message yet,
+/// otherwise the request and how many bytes it took.
+pub fn decode_request(
+    buffer: &[u8],
+) -> Result<Option<(Request, usize)>, DecodeError> {
+    let (msg_type, message) = match frame(buffer)? {
+        Some(frame) => frame,
+        None => return Ok(None),
+    };
+
+    let request = match msg_type {
+        msg_type::CREATE => {
+            expect_length(msg_type, message, 40)?;
+            let side = match message[HEADER_LENGTH] {
+                1 => OrderSide::Bid,
+                2 => OrderSide::Ask,
+                side => return Err(DecodeError::InvalidSide(side)),
+            };
+            Request::Create(Order::new(
+                OrderId::new(u64_at(message, 8)),
+                u64_at(message, 16),
+                side,
+                u64_at(message, 24),
+                u64_at(message, 32),
+            ))
+        }
+        msg_type::CANCEL => {
+            expect_length(msg_type, message, 16)?;
+            Request::Cancel(OrderId::new(u64_at(message, 8)))
+        }
+        msg_type::MODIFY => {
+            expect_length(msg_type, message, 32)?;
+            Request::Modify {
+                order_id: OrderId::new(u64_at(message, 8)),
+                limit_price: u64_at(message, 16),
+                amount: u64_at(message, 24),
+            }
+        }
+        other => return Err(DecodeError::UnknownMessageType(other)),
+    };
+
+    Ok(Some((request, message.len())))
+}
+
+/// Decode the first response in `buffer`.
+///
+/// Return `Ok(None)` when `buffer` does not hold a whole message yet,
+/// otherwise the response and how many bytes it took.
+pub fn decode_response(
+    buffer: &[u8],
+) -> Result<Option<(Response, usize)>, DecodeError> {
+    let (msg_type, message) = match frame(buffer)? {
+        Some(frame) => frame,
+        None => return Ok(None),
+    };
+
+    let response = match msg_type {
+        msg_type::ACK => {
+            expect_length(msg_type, message, 16)?;
+            Response::Ack {
+                msg_type: message[HEADER_LENGTH],
+                order_id: OrderId::new(u64_at(message, 8)),
+            }
+        }
+        msg_type::REJECT => {
+            expect_length(msg_type, message, 16)?;
+            let code = message[HEADER_LENGTH];
+            let reason = [
+                RejectReason::UnknownOrder,
+                RejectReason::DuplicateOrder,
+                RejectReason::InvalidAmount,
+                RejectReason::AmountLimit,
+                RejectReason::NotionalLimit,
+                RejectReason::PriceBand,
+                RejectReason::OpenOrdersLimit,
+                RejectReason::PositionLimit,
+                RejectReason::InsufficientBalance,
+                RejectReason::TradingPhase,
+            ]
+            .into_iter()
+            .find(|reason| reason_code(*reason) == code)
+            .ok_or(DecodeError::InvalidReason(code))?;
+            Response::Reject {
+                order_id: OrderId::new(u64_at(message, 8)),
+                reason,
+            }
+        }
+        msg_type::FILL => {
+            expect_length(msg_type, message, 40)?;
+            Response::Fill {
+                order_id: OrderId::new(u64_at(message, 8)),
+                amount: u64_at(message, 16),
+                price: u64_at(message, 24),
+                leaves: u64_at(message, 32),
+            }
+        }
+        other => return Err(DecodeError::UnknownMessageType(other)),
+    };
+
+    Ok(Some((response, message.len())))
+}
+
+/// Split the first whole message off `buffer`.
+#[inline]
+fn frame(buffer: &[u8]) -> Result<Option<(u8, &[u8])>, DecodeError> {
+    if buffer.len() < HEADER_LENGTH {
+        return Ok(None);
+    }
+
+    let length = usize::from(u16::from_le_bytes([buffer[0], buffer[1]]));
+    let msg_type = buffer[2];
+    if length < HEADER_LENGTH {
+        return Err(DecodeError::InvalidLength { msg_type, length });
+    }
+
+    Ok(buffer.get(..length).map(|message| (msg_type, message)))
+}
+
+#[inline]
+fn expect_length(
+    msg_type: u8,
+    message: &[u8],
+    length: usize,
+) -> Result<(), DecodeError> {
+    if message.len() == length {
+        Ok(())
+    } else {
+        Err(DecodeError::InvalidLength {
+            msg_type,
+            length: message.len(),
+        })
+    }
+}
+
+#[inline(always)]
+fn u64_at(message: &[u8], offset: usize) -> u64 {
+    let mut bytes = [0; 8];
+    bytes.copy_from_slice(&message[offset..offset + 8]);
+    u64::from_le_bytes(bytes)
+}
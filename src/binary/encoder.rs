@@ -0,0 +1,88 @@
+use super::{msg_type, Request, Response};
+use crate::engine::RejectReason;
+use crate::{Asset, OrderSide};
+
+/// Append the encoded `request` to `buffer`.
+pub fn encode_request(request: &Request, buffer: &mut Vec<u8>) {
+    match request {
+        Request::Create(order) => {
+            let side = match order.side() {
+                OrderSide::Bid => 1,
+                OrderSide::Ask => 2,
+            };
+            header(buffer, msg_type::CREATE, 40, side);
+            buffer.extend_from_slice(&u64::from(order.id()).to_le_bytes());
+            buffer.extend_from_slice(&order.account_id().to_le_bytes());
+            buffer.extend_from_slice(&order.limit_price().to_le_bytes());
+            buffer.extend_from_slice(&order.amount().to_le_bytes());
+        }
+        Request::Cancel(order_id) => {
+            header(buffer, msg_type::CANCEL, 16, 0);
+            buffer.extend_from_slice(&u64::from(*order_id).to_le_bytes());
+        }
+        Request::Modify {
+            order_id,
+            limit_price,
+            amount,
+        } => {
+            header(buffer, msg_type::MODIFY, 32, 0);
+            buffer.extend_from_slice(&u64::from(*order_id).to_le_bytes());
+            buffer.extend_from_slice(&limit_price.to_le_bytes());
+            buffer.extend_from_slice(&amount.to_le_bytes());
+        }
+    }
+}
+
+/// Append the encoded `response` to `buffer`.
+pub fn encode_response(response: &Response, buffer: &mut Vec<u8>) {
+    match response {
+        Response::Ack {
+            msg_type: acked,
+            order_id,
+        } => {
+            header(buffer, msg_type::ACK, 16, *acked);
+            buffer.extend_from_slice(&u64::from(*order_id).to_le_bytes());
+        }
+        Response::Reject { order_id, reason } => {
+            header(buffer, msg_type::REJECT, 16, reason_code(*reason));
+            buffer.extend_from_slice(&u64::from(*order_id).to_le_bytes());
+        }
+        Response::Fill {
+            order_id,
+            amount,
+            price,
+            leaves,
+        } => {
+            header(buffer, msg_type::FILL, 40, 0);
+            buffer.extend_from_slice(&u64::from(*order_id).to_le_bytes());
+            buffer.extend_from_slice(&amount.to_le_bytes());
+            buffer.extend_from_slice(&price.to_le_bytes());
+            buffer.extend_from_slice(&leaves.to_le_bytes());
+        }
+    }
+}
+
+/// Write the header and the first body byte, padded up to the first `u64`.
+#[inline]
+fn header(buffer: &mut Vec<u8>, msg_type: u8, length: u16, first: u8) {
+    buffer.reserve(usize::from(length));
+    buffer.extend_from_slice(&length.to_le_bytes());
+    buffer.extend_from_slice(&[msg_type, 0]);
+    buffer.extend_from_slice(&[first, 0, 0, 0]);
+}
+
+#[inline]
+pub(super) fn reason_code(reason: RejectReason) -> u8 {
+    match reason {
+        RejectReason::UnknownOrder => 1,
+        RejectReason::DuplicateOrder => 2,
+        RejectReason::InvalidAmount => 3,
+        RejectReason::AmountLimit => 4,
+        RejectReason::NotionalLimit => 5,
+        RejectReason::PriceBand => 6,
+        RejectReason::OpenOrdersLimit => 7,
+        RejectReason::PositionLimit => 8,
+        RejectReason::InsufficientBalance => 9,
+        RejectReason::TradingPhase => 10,
+    }
+}
@@ -0,0 +1,139 @@
+//! Compact fixed-layout binary order-entry protocol.
+//!
+//! Every message starts with a four-byte header: the total message length as
+//! a little-endian `u16`, the message type and a reserved byte. Fields follow
+//! at fixed offsets, little-endian, padded so that each `u64` is aligned to
+//! eight bytes within the message.
+//!
+//! | Message  | Type  | Length | Body                                        |
+//! |----------|-------|--------|---------------------------------------------|
+//! | Create   | `N`   | 40     | side, pad, order id, account, price, amount |
+//! | Cancel   | `X`   | 16     | pad, order id                               |
+//! | Modify   | `M`   | 32     | pad, order id, price, amount                |
+//! | Ack      | `A`   | 16     | acknowledged type, pad, order id            |
+//! | Reject   | `J`   | 16     | reason, pad, order id                       |
+//! | Fill     | `F`   | 40     | pad, order id, amount, price, leaves amount |
+//!
+//! Sides are `1` for bid and `2` for ask.
+
+use compact_str::{format_compact, CompactString};
+use thiserror::Error;
+
+use crate::engine::{
+    from_units, ExecType, ExecutionReport, Order, OrderId, OrderRequest,
+    RejectReason,
+};
+use crate::Asset;
+
+mod decoder;
+pub use decoder::{decode_request, decode_response};
+
+mod encoder;
+pub use encoder::{encode_request, encode_response};
+
+pub const HEADER_LENGTH: usize = 4;
+
+/// Message types.
+pub mod msg_type {
+    pub const CREATE: u8 = b'N';
+    pub const CANCEL: u8 = b'X';
+    pub const MODIFY: u8 = b'M';
+    pub const ACK: u8 = b'A';
+    pub const REJECT: u8 = b'J';
+    pub const FILL: u8 = b'F';
+}
+
+#[derive(Debug, Error, PartialEq, Eq)]
+pub enum DecodeError {
+    #[error("unknown message type {0:#04x}")]
+    UnknownMessageType(u8),
+    #[error("message type {msg_type:#04x} cannot be {length} bytes long")]
+    InvalidLength { msg_type: u8, length: usize },
+    #[error("invalid side {0}")]
+    InvalidSide(u8),
+    #[error("invalid reject reason {0}")]
+    InvalidReason(u8),
+}
+
+/// Inbound message, decoded straight into engine types.
+#[derive(Debug, PartialEq, Eq)]
+#[cfg_attr(test, derive(Clone, Copy))]
+pub enum Request {
+    Create(Order),
+    Cancel(OrderId),
+    Modify {
+        order_id: OrderId,
+        limit_price: u64,
+        amount: u64,
+    },
+}
+
+impl Request {
+    /// Convert into the order request the engine processes, creating orders
+    /// on `pair`.
+    pub fn into_order_request(self, pair: &str) -> OrderRequest {
+        match self {
+            Request::Create(order) => OrderRequest::Create {
+                account_id: format_compact!("{}", order.account_id()),
+                amount: from_units(order.amount()),
+                order_id: format_compact!("{}", order.id()),
+                pair: CompactString::new(pair),
+                limit_price: from_units(order.limit_price()),
+                side: order.side(),
+            },
+            Request::Cancel(order_id) => OrderRequest::Delete {
+                order_id: format_compact!("{order_id}"),
+            },
+            Request::Modify {
+                order_id,
+                limit_price,
+                amount,
+            } => OrderRequest::Modify {
+                order_id: format_compact!("{order_id}"),
+                amount: from_units(amount),
+                limit_price: from_units(limit_price),
+            },
+        }
+    }
+}
+
+/// Outbound message.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Response {
+    /// The request of type `msg_type` about `order_id` was accepted.
+    Ack { msg_type: u8, order_id: OrderId },
+    Reject {
+        order_id: OrderId,
+        reason: RejectReason,
+    },
+    Fill {
+        order_id: OrderId,
+        amount: u64,
+        price: u64,
+        leaves: u64,
+    },
+}
+
+impl From<&ExecutionReport> for Response {
+    #[inline]
+    fn from(report: &ExecutionReport) -> Self {
+        let order_id = report.order_id();
+        let ack = |msg_type| Response::Ack { msg_type, order_id };
+
+        match report.exec_type() {
+            ExecType::New => ack(msg_type::CREATE),
+            ExecType::Canceled => ack(msg_type::CANCEL),
+            ExecType::Replaced => ack(msg_type::MODIFY),
+            ExecType::PartialFill | ExecType::Fill => Response::Fill {
+                order_id,
+                amount: report.last_qty(),
+                price: report.last_px(),
+                leaves: report.leaves_qty(),
+            },
+            ExecType::Rejected => Response::Reject {
+                order_id,
+                reason: report.reason().unwrap_or(RejectReason::UnknownOrder),
+            },
+        }
+    }
+}
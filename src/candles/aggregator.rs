@@ -0,0 +1,99 @@
+use super::{Candle, Interval};
+use crate::engine::{Clock, Event, Order, SystemClock};
+
+/// Most flat candles opened in a row, so that a clock jump cannot open
+/// billions of them.
+pub const MAX_FLAT_CANDLES: u64 = 10_000;
+
+/// Builds the candles of a single interval out of [`Event::Traded`] events,
+/// bucketed by the time the engine stamped each trade with.
+///
+/// Candles start with the first trade. Intervals without trades from then
+/// on get a flat candle at the previous close, once a later trade or the
+/// clock of the aggregator reaches past them. Only the last
+/// [`MAX_FLAT_CANDLES`] intervals of a longer gap do, the earlier ones being
+/// left out. A trade stamped before the last candle is added to it, as closed
+/// candles are never reopened.
+pub struct CandleAggregator {
+    interval: Interval,
+    clock: Box<dyn Clock + Send>,
+    candles: Vec<Candle>,
+}
+
+impl CandleAggregator {
+    #[inline]
+    pub fn new(interval: Interval) -> Self {
+        Self {
+            interval,
+            clock: Box::new(SystemClock),
+            candles: Vec::new(),
+        }
+    }
+
+    /// Take the time from `clock` instead of the system one.
+    #[inline]
+    pub fn with_clock(mut self, clock: impl Clock + Send + 'static) -> Self {
+        self.clock = Box::new(clock);
+        self
+    }
+
+    #[inline]
+    pub fn interval(&self) -> Interval {
+        self.interval
+    }
+
+    /// Add the trades among `events` to the candles of the intervals they
+    /// were stamped in.
+    pub fn update(&mut self, events: &[Event<Order>]) {
+        for event in events {
+            if let Event::Traded(trade) = event {
+                let start = self.interval.start(trade.timestamp());
+                self.open(start);
+
+                let (price, amount) = (trade.price(), trade.amount());
+                match self.candles.last_mut() {
+                    Some(candle) => candle.push(price, amount),
+                    None => {
+                        self.candles.push(Candle::new(start, price, amount))
+                    }
+                }
+            }
+        }
+    }
+
+    /// Open the candles of every interval elapsed on the clock since the
+    /// last one, flat at its close.
+    #[inline]
+    pub fn tick(&mut self) {
+        self.open(self.interval.start(self.clock.now()));
+    }
+
+    /// Open flat candles until the last one starts at `start`.
+    fn open(&mut self, start: u64) {
+        let (last, millis) = match self.candles.last() {
+            Some(last) if last.start < start => (last, self.interval.millis()),
+            _ => return,
+        };
+        let first = (last.start + millis)
+            .max(start.saturating_sub((MAX_FLAT_CANDLES - 1) * millis));
+        let close = last.close;
+        self.candles.extend(
+            (first..=start)
+                .step_by(millis as usize)
+                .map(|start| Candle::empty(start, close)),
+        );
+    }
+
+    /// Return every candle so far. The last one is still open.
+    #[inline]
+    pub fn candles(&self) -> &[Candle] {
+        &self.candles
+    }
+
+    /// Remove and return the candles of the elapsed intervals.
+    pub fn drain_closed(&mut self) -> Vec<Candle> {
+        self.tick();
+        let closed = self.candles.len().saturating_sub(1);
+        self.candles.drain(..closed).collect()
+    }
+}
@@ -0,0 +1,169 @@
+//! OHLCV candles aggregated from the trade stream.
+
+use std::fmt;
+use std::str::FromStr;
+
+#[cfg(feature = "serde")]
+use serde::{Deserialize, Serialize};
+use thiserror::Error;
+
+mod aggregator;
+pub use aggregator::{CandleAggregator, MAX_FLAT_CANDLES};
+
+#[derive(Debug, Error)]
+#[error("unknown interval {0:?}, expected one of 1s, 1m, 5m, 1h or 1d")]
+pub struct IntervalError(String);
+
+/// Duration covered by each candle.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub enum Interval {
+    #[cfg_attr(feature = "serde", serde(rename = "1s"))]
+    Second,
+    #[cfg_attr(feature = "serde", serde(rename = "1m"))]
+    Minute,
+    #[cfg_attr(feature = "serde", serde(rename = "5m"))]
+    FiveMinutes,
+    #[cfg_attr(feature = "serde", serde(rename = "1h"))]
+    Hour,
+    #[cfg_attr(feature = "serde", serde(rename = "1d"))]
+    Day,
+}
+
+impl Interval {
+    /// Return the interval length in milliseconds.
+    #[inline]
+    pub fn millis(&self) -> u64 {
+        match self {
+            Interval::Second => 1_000,
+            Interval::Minute => 60_000,
+            Interval::FiveMinutes => 300_000,
+            Interval::Hour => 3_600_000,
+            Interval::Day => 86_400_000,
+        }
+    }
+
+    /// Return the start of the interval `timestamp` falls into.
+    #[inline]
+    pub fn start(&self, timestamp: u64) -> u64 {
+        timestamp - timestamp % self.millis()
+    }
+}
+
+impl fmt::Display for Interval {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(match self {
+            Interval::Second => "1s",
+            Interval::Minute => "1m",
+            Interval::FiveMinutes => "5m",
+            Interval::Hour => "1h",
+            Interval::Day => "1d",
+        })
+    }
+}
+
+impl FromStr for Interval {
+    type Err = IntervalError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "1s" => Ok(Interval::Second),
+            "1m" => Ok(Interval::Minute),
+            "5m" => Ok(Interval::FiveMinutes),
+            "1h" => Ok(Interval::Hour),
+            "1d" => Ok(Interval::Day),
+            _ => Err(IntervalError(s.to_owned())),
+        }
+    }
+}
+
+/// Open, high, low and close prices, volume and number of trades over an
+/// interval starting at `start`, in milliseconds since the Unix epoch.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub struct Candle {
+    start: u64,
+    open: u64,
+    high: u64,
+    low: u64,
+    close: u64,
+    volume: u64,
+    trades: u64,
+}
+
+impl Candle {
+    /// Create a candle opened by a trade of `amount` at `price`.
+    #[inline]
+    fn new(start: u64, price: u64, amount: u64) -> Self {
+        Self {
+            start,
+            open: price,
+            high: price,
+            low: price,
+            close: price,
+            volume: amount,
+            trades: 1,
+        }
+    }
+
+    /// Create a candle without trades, flat at `price`.
+    #[inline]
+    fn empty(start: u64, price: u64) -> Self {
+        Self {
+            volume: 0,
+            trades: 0,
+            ..Self::new(start, price, 0)
+        }
+    }
+
+    #[inline]
+    fn push(&mut self, price: u64, amount: u64) {
+        if self.trades == 0 {
+            self.open = price;
+            self.high = price;
+            self.low = price;
+        }
+        self.high = self.high.max(price);
+        self.low = self.low.min(price);
+        self.close = price;
+        self.volume = self.volume.saturating_add(amount);
+        self.trades += 1;
+    }
+
+    #[inline]
+    pub fn start(&self) -> u64 {
+        self.start
+    }
+
+    #[inline]
+    pub fn open(&self) -> u64 {
+        self.open
+    }
+
+    #[inline]
+    pub fn high(&self) -> u64 {
+        self.high
+    }
+
+    #[inline]
+    pub fn low(&self) -> u64 {
+        self.low
+    }
+
+    #[inline]
+    pub fn close(&self) -> u64 {
+        self.close
+    }
+
+    /// Total amount traded.
+    #[inline]
+    pub fn volume(&self) -> u64 {
+        self.volume
+    }
+
+    /// Number of trades.
+    #[inline]
+    pub fn trades(&self) -> u64 {
+        self.trades
+    }
+}
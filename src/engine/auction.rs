@@ -0,0 +1,140 @@
+use std::cmp::Ordering;
+
+use super::{Event, Order, Orderbook, Trade};
+use crate::OrderSide;
+
+#[cfg(feature = "serde")]
+use serde::{Deserialize, Serialize};
+
+/// Single price at which a call auction uncrosses, with the volume executed
+/// there and the amount left unmatched on the heavier side.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub struct Equilibrium {
+    price: u64,
+    volume: u64,
+    imbalance: u64,
+    imbalance_side: Option<OrderSide>,
+}
+
+impl Equilibrium {
+    /// Find the equilibrium price of `orderbook`.
+    ///
+    /// It is the limit price maximizing the executed volume. Ties go to the
+    /// smallest imbalance, then to the highest price when every tied price
+    /// leaves a bid surplus, or to the lowest one when every tied price
+    /// leaves an ask surplus. Remaining ties go to the price closest to
+    /// `reference`, then to the lowest price.
+    pub fn new(
+        orderbook: &Orderbook<Order, Event<Order>, Trade>,
+        reference: Option<u64>,
+    ) -> Option<Self> {
+        let asks = orderbook.quantities(OrderSide::Ask).collect::<Vec<_>>();
+        let mut bids = orderbook.quantities(OrderSide::Bid).collect::<Vec<_>>();
+        bids.reverse();
+
+        let mut prices = asks
+            .iter()
+            .chain(bids.iter())
+            .map(|(price, _)| *price)
+            .collect::<Vec<_>>();
+        prices.sort_unstable();
+        prices.dedup();
+
+        // Walk up the prices, accumulating the asks at or below each and
+        // dropping the bids below it. Whole sides may hold more than an
+        // order can, hence the wider sums.
+        let (mut ask_volume, mut bid_volume) =
+            (0, bids.iter().map(|(_, amount)| amount).sum::<u128>());
+        let (mut asks, mut bids) =
+            (asks.iter().peekable(), bids.iter().peekable());
+        let mut candidates = Vec::new();
+        for price in prices {
+            while let Some((_, amount)) = asks.next_if(|(ask, _)| *ask <= price)
+            {
+                ask_volume += amount;
+            }
+            while let Some((_, amount)) = bids.next_if(|(bid, _)| *bid < price)
+            {
+                bid_volume -= amount;
+            }
+
+            // Ties are broken on the exact sums, not the saturated ones.
+            let volume = ask_volume.min(bid_volume);
+            let imbalance = ask_volume.abs_diff(bid_volume);
+            if volume > 0 {
+                let candidate = Self {
+                    price,
+                    volume: saturate(volume),
+                    imbalance: saturate(imbalance),
+                    imbalance_side: match bid_volume.cmp(&ask_volume) {
+                        Ordering::Greater => Some(OrderSide::Bid),
+                        Ordering::Less => Some(OrderSide::Ask),
+                        Ordering::Equal => None,
+                    },
+                };
+                candidates.push((candidate, volume, imbalance));
+            }
+        }
+
+        let volume = candidates.iter().map(|(_, volume, _)| *volume).max()?;
+        candidates.retain(|(_, candidate, _)| *candidate == volume);
+        let imbalance = candidates
+            .iter()
+            .map(|(_, _, imbalance)| *imbalance)
+            .min()?;
+        let mut candidates = candidates
+            .into_iter()
+            .filter(|(_, _, candidate)| *candidate == imbalance)
+            .map(|(candidate, ..)| candidate)
+            .collect::<Vec<_>>();
+
+        let pressure = |side| {
+            candidates
+                .iter()
+                .all(|candidate| candidate.imbalance_side == Some(side))
+        };
+        if pressure(OrderSide::Bid) {
+            return candidates.pop();
+        }
+        if pressure(OrderSide::Ask) {
+            return candidates.first().copied();
+        }
+
+        match reference {
+            Some(reference) => candidates
+                .into_iter()
+                .min_by_key(|candidate| candidate.price.abs_diff(reference)),
+            None => candidates.first().copied(),
+        }
+    }
+
+    #[inline]
+    pub fn price(&self) -> u64 {
+        self.price
+    }
+
+    /// Amount executed at the equilibrium price, saturating at `u64::MAX`.
+    #[inline]
+    pub fn volume(&self) -> u64 {
+        self.volume
+    }
+
+    /// Amount left unmatched at the equilibrium price, saturating at
+    /// `u64::MAX`.
+    #[inline]
+    pub fn imbalance(&self) -> u64 {
+        self.imbalance
+    }
+
+    /// Side left with unmatched amount, if any.
+    #[inline]
+    pub fn imbalance_side(&self) -> Option<OrderSide> {
+        self.imbalance_side
+    }
+}
+
+#[inline]
+fn saturate(amount: u128) -> u64 {
+    u64::try_from(amount).unwrap_or(u64::MAX)
+}
@@ -0,0 +1,147 @@
+use std::collections::{HashMap, VecDeque};
+
+use compact_str::CompactString;
+
+use super::{Event, Order};
+
+#[cfg(feature = "serde")]
+use serde::{Deserialize, Serialize};
+
+/// How far, in basis points, trades may print from the reference price, the
+/// window in milliseconds the reference is taken over, and how long the
+/// volatility auction a breach triggers lasts.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub struct VolatilityBand {
+    threshold: u64,
+    window: u64,
+    auction: u64,
+}
+
+impl VolatilityBand {
+    #[inline]
+    pub fn new(threshold: u64, window: u64, auction: u64) -> Self {
+        Self {
+            threshold,
+            window,
+            auction,
+        }
+    }
+
+    #[inline]
+    pub fn threshold(&self) -> u64 {
+        self.threshold
+    }
+
+    #[inline]
+    pub fn window(&self) -> u64 {
+        self.window
+    }
+
+    #[inline]
+    pub fn auction(&self) -> u64 {
+        self.auction
+    }
+}
+
+/// Halts continuous matching when a trade would print too far away from the
+/// recent prices, following a [`VolatilityBand`] with optional overrides per
+/// pair.
+///
+/// The reference price of a pair is its oldest trade within the band window
+/// or, if there was none lately, its last trade. An auction resets it to the
+/// uncrossing price.
+#[derive(Debug, Default)]
+pub struct CircuitBreaker {
+    band: Option<VolatilityBand>,
+    pairs: HashMap<CompactString, VolatilityBand>,
+    history: HashMap<CompactString, History>,
+}
+
+/// Recent trades of a pair.
+#[derive(Debug, Default)]
+struct History {
+    prices: VecDeque<(u64, u64)>,
+    last_price: Option<u64>,
+}
+
+impl CircuitBreaker {
+    /// Create a breaker applying `band` to every pair.
+    #[inline]
+    pub fn new(band: VolatilityBand) -> Self {
+        Self {
+            band: Some(band),
+            ..Self::default()
+        }
+    }
+
+    /// Apply `band` to `pair` instead of the default one.
+    #[inline]
+    pub fn with_pair_band(mut self, pair: &str, band: VolatilityBand) -> Self {
+        self.pairs.insert(pair.into(), band);
+        self
+    }
+
+    /// Return the band applying to `pair`, if any.
+    #[inline]
+    pub fn band(&self, pair: &str) -> Option<VolatilityBand> {
+        self.pairs.get(pair).copied().or(self.band)
+    }
+
+    /// Return the price trades are compared against at `now`.
+    pub fn reference(&self, pair: &str, now: u64) -> Option<u64> {
+        let band = self.band(pair)?;
+        let history = self.history.get(pair)?;
+        history
+            .prices
+            .iter()
+            .find(|(timestamp, _)| now.saturating_sub(*timestamp) < band.window)
+            .map(|(_, price)| *price)
+            .or(history.last_price)
+    }
+
+    /// Return whether a trade on `pair` may print at `price` at `now`.
+    pub fn allows(&self, pair: &str, price: u64, now: u64) -> bool {
+        let (band, reference) =
+            match (self.band(pair), self.reference(pair, now)) {
+                (Some(band), Some(reference)) => (band, reference),
+                _ => return true,
+            };
+        u128::from(price.abs_diff(reference)) * 10_000
+            <= u128::from(band.threshold) * u128::from(reference)
+    }
+
+    /// Record the trades among `events`, printed on `pair` at `now`.
+    pub(super) fn update(
+        &mut self,
+        pair: &str,
+        events: &[Event<Order>],
+        now: u64,
+    ) {
+        let window = match self.band(pair) {
+            Some(band) => band.window,
+            None => return,
+        };
+        let history = self.history.entry(pair.into()).or_default();
+        for event in events {
+            if let Event::Traded(trade) = event {
+                history.prices.push_back((now, trade.price));
+                history.last_price = Some(trade.price);
+            }
+        }
+        while let Some((timestamp, _)) = history.prices.front() {
+            if now.saturating_sub(*timestamp) < window {
+                break;
+            }
+            history.prices.pop_front();
+        }
+    }
+
+    /// Take `price` as the only reference of `pair`, after an auction.
+    pub(super) fn reset(&mut self, pair: &str, price: u64, now: u64) {
+        let history = self.history.entry(pair.into()).or_default();
+        history.prices.clear();
+        history.prices.push_back((now, price));
+        history.last_price = Some(price);
+    }
+}
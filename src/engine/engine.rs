@@ -1,8 +1,32 @@
-use super::{Event, Order, OrderId, OrderRequest, Orderbook, Trade};
-use crate::{Asset, Exchange};
+use super::history::History;
+use super::ledger::cost;
+use super::{
+    parse_id, parse_units, CircuitBreaker, Clock, Equilibrium, Event,
+    FeeEngine, FeeSchedule, Impact, Ledger, MatchingStrategy, Order, OrderId,
+    OrderRequest, OrderRequestError, OrderStatus, Orderbook, Phase,
+    RejectReason, RiskManager, Schedule, SystemClock, Trade,
+};
+use crate::{Asset, Exchange, OrderSide};
+
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::Arc;
+
+use compact_str::CompactString;
 
 pub struct Engine {
     orderbook: Orderbook<Order, Event<Order>, Trade>,
+    risk: RiskManager,
+    ledger: Option<Ledger>,
+    fees: Option<FeeEngine>,
+    breaker: Option<CircuitBreaker>,
+    clock: Box<dyn Clock + Send>,
+    history: History,
+    sequence: u64,
+    trade_ids: Arc<AtomicU64>,
+    phase: Phase,
+    schedule: Schedule,
+    scheduled: Option<u64>,
+    resume_at: Option<u64>,
 }
 
 impl Engine {
@@ -10,34 +34,744 @@ impl Engine {
     pub fn new(pair: &str) -> Self {
         Self {
             orderbook: Orderbook::new(pair),
+            risk: RiskManager::new(),
+            ledger: None,
+            fees: None,
+            breaker: None,
+            clock: Box::new(SystemClock),
+            history: History::new(10_000),
+            sequence: 0,
+            trade_ids: Arc::default(),
+            phase: Phase::default(),
+            schedule: Schedule::new(),
+            scheduled: None,
+            resume_at: None,
         }
     }
 
+    /// Number trades out of `trade_ids`, the last id given so far, so that
+    /// engines sharing it never give the same id twice.
+    #[inline]
+    pub fn with_trade_ids(mut self, trade_ids: Arc<AtomicU64>) -> Self {
+        self.trade_ids = trade_ids;
+        self
+    }
+
+    /// Charge fees on every trade following `schedule`.
+    #[inline]
+    pub fn with_fees(mut self, schedule: FeeSchedule) -> Self {
+        self.fees = Some(FeeEngine::new(schedule));
+        self
+    }
+
+    /// Move into a volatility auction whenever a trade would print outside of
+    /// the `breaker` band.
+    #[inline]
+    pub fn with_circuit_breaker(mut self, breaker: CircuitBreaker) -> Self {
+        self.breaker = Some(breaker);
+        self
+    }
+
+    /// Split incoming orders among the orders resting at a level following
+    /// `strategy` instead of time priority.
+    #[inline]
+    pub fn with_matching(
+        mut self,
+        strategy: impl MatchingStrategy + Send + 'static,
+    ) -> Self {
+        self.orderbook.set_matching(strategy);
+        self
+    }
+
+    /// Take the time from `clock` instead of the system one.
+    #[inline]
+    pub fn with_clock(mut self, clock: impl Clock + Send + 'static) -> Self {
+        self.clock = Box::new(clock);
+        self
+    }
+
+    /// Back every order with funds from `ledger`. The base and quote assets
+    /// are named after the pair, e.g. `BTC` and `USDC` for `BTC/USDC`.
+    ///
+    /// # Panics
+    ///
+    /// Panics if orders already rest, as nothing was reserved for them.
+    #[inline]
+    pub fn with_ledger(mut self, ledger: Ledger) -> Self {
+        assert!(
+            self.orderbook.iter().next().is_none(),
+            "a ledger attached before any order rests"
+        );
+        self.ledger = Some(ledger);
+        self
+    }
+
+    /// Remember the final status of the last `capacity` orders which left
+    /// the book, instead of 10000.
+    #[inline]
+    pub fn with_history(mut self, capacity: usize) -> Self {
+        self.history = History::new(capacity);
+        self
+    }
+
+    /// Switch phases following `schedule`, on the engine clock. The phase due
+    /// now is entered on the next [`Engine::tick`].
+    #[inline]
+    pub fn with_schedule(mut self, schedule: Schedule) -> Self {
+        self.schedule = schedule;
+        self.scheduled = None;
+        self
+    }
+
+    /// Process `incoming_order`.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `incoming_order` holds malformed identifiers, amounts or
+    /// prices. Use [`Engine::try_process`] for untrusted input.
     #[inline]
     pub fn process(
         &mut self,
         incoming_order: OrderRequest,
     ) -> Vec<<Orderbook<Order, Event<Order>, Trade> as Exchange>::Event> {
+        self.try_process(incoming_order)
+            .expect("a well-formed order request")
+    }
+
+    /// Process `incoming_order`, failing if it is malformed. Scheduled phase
+    /// switches due by now are applied first.
+    pub fn try_process(
+        &mut self,
+        incoming_order: OrderRequest,
+    ) -> Result<Vec<Event<Order>>, OrderRequestError> {
+        let mut events = Vec::new();
+        self.try_process_into(incoming_order, &mut events)?;
+        Ok(events)
+    }
+
+    /// Process `incoming_order` like [`Engine::try_process`], appending the
+    /// events to `events` instead, so the same buffer can be reused from one
+    /// request to the next.
+    ///
+    /// The scheduled phase switches are applied, and their events appended,
+    /// even if the request then turns out to be malformed.
+    pub fn try_process_into(
+        &mut self,
+        incoming_order: OrderRequest,
+        events: &mut Vec<Event<Order>>,
+    ) -> Result<(), OrderRequestError> {
+        self.tick_into(events);
         match incoming_order {
             OrderRequest::Create { .. } => {
-                let order = Order::try_from(incoming_order).unwrap();
-                self.orderbook.matching(order)
+                self.create_into(Order::try_from(incoming_order)?, events)
             }
             OrderRequest::Delete { ref order_id } => {
-                if let Some(order) = self
-                    .orderbook
-                    .remove(&OrderId::new(order_id.parse::<u64>().unwrap()))
+                self.delete_into(OrderId::new(parse_id(order_id)?), events)
+            }
+            OrderRequest::Modify {
+                ref order_id,
+                amount,
+                limit_price,
+            } => self.modify_into(
+                OrderId::new(parse_id(order_id)?),
+                parse_units(limit_price)?,
+                parse_units(amount)?,
+                events,
+            ),
+            OrderRequest::DeleteAll {
+                ref account_id,
+                side,
+                ref pair,
+            } => {
+                let account_id = parse_id(account_id)?;
+                if pair
+                    .as_ref()
+                    .is_some_and(|pair| pair.as_str() != self.orderbook.pair())
+                {
+                    return Ok(());
+                }
+                self.sequence += 1;
+                if !self.phase.accepts_cancels() {
+                    events.extend(
+                        self.orderbook
+                            .orders_by_account(account_id)
+                            .filter(|order| {
+                                side.is_none_or(|side| order.side() == side)
+                            })
+                            .map(|order| {
+                                Event::Rejected(
+                                    order.id(),
+                                    RejectReason::TradingPhase,
+                                )
+                            }),
+                    );
+                    return Ok(());
+                }
+
+                let removed =
+                    self.orderbook.remove_by_account(account_id, side);
+                for mut order in removed {
+                    if let Some(ledger) = &mut self.ledger {
+                        ledger.release(&order.id());
+                    }
+                    order.cancel();
+                    self.history.push(order.id(), order.status());
+                    events.push(Event::Removed(order.id()));
+                }
+                self.indicate(events);
+            }
+            OrderRequest::SetPhase { phase, ref pair } => {
+                if pair
+                    .as_ref()
+                    .is_some_and(|pair| pair.as_str() != self.orderbook.pair())
                 {
-                    vec![Event::Removed(order.id())]
-                } else {
-                    vec![]
+                    return Ok(());
+                }
+                self.set_phase_into(phase, events)
+            }
+        }
+        Ok(())
+    }
+
+    /// Match a new order against the orderbook, resting what is left of it,
+    /// unless it breaches the risk limits.
+    #[inline]
+    pub fn create(&mut self, order: Order) -> Vec<Event<Order>> {
+        let mut events = Vec::new();
+        self.create_into(order, &mut events);
+        events
+    }
+
+    /// Like [`Engine::create`], appending the events to `events` instead.
+    pub fn create_into(
+        &mut self,
+        order: Order,
+        events: &mut Vec<Event<Order>>,
+    ) {
+        self.sequence += 1;
+        let checked = if !self.phase.accepts_orders() {
+            Err(RejectReason::TradingPhase)
+        } else if self.orderbook.get(&order.id()).is_some() {
+            // The resting order keeps its status.
+            events.push(Event::Rejected(
+                order.id(),
+                RejectReason::DuplicateOrder,
+            ));
+            return;
+        } else if order.remaining() == 0 {
+            Err(RejectReason::InvalidAmount)
+        } else {
+            self.risk
+                .check(&order, &self.orderbook)
+                .and_then(|_| self.reserve(&order))
+        };
+        if let Err(reason) = checked {
+            self.history.push(order.id(), OrderStatus::Rejected);
+            events.push(Event::Rejected(order.id(), reason));
+            return;
+        }
+
+        let start = events.len();
+        self.matching(order, events);
+        self.executed(&mut events[start..]);
+        self.indicate(events);
+    }
+
+    /// Remove a resting order from the orderbook.
+    #[inline]
+    pub fn delete(&mut self, order_id: OrderId) -> Vec<Event<Order>> {
+        let mut events = Vec::new();
+        self.delete_into(order_id, &mut events);
+        events
+    }
+
+    /// Like [`Engine::delete`], appending the events to `events` instead.
+    pub fn delete_into(
+        &mut self,
+        order_id: OrderId,
+        events: &mut Vec<Event<Order>>,
+    ) {
+        self.sequence += 1;
+        if !self.phase.accepts_cancels() {
+            events.push(Event::Rejected(order_id, RejectReason::TradingPhase));
+            return;
+        }
+        match self.orderbook.remove(&order_id) {
+            Some(mut order) => {
+                if let Some(ledger) = &mut self.ledger {
+                    ledger.release(&order_id);
+                }
+                order.cancel();
+                self.history.push(order.id(), order.status());
+                events.push(Event::Removed(order.id()));
+                self.indicate(events);
+            }
+            None => events
+                .push(Event::Rejected(order_id, RejectReason::UnknownOrder)),
+        }
+    }
+
+    /// Replace a resting order limit price and total amount. The order keeps
+    /// its time priority only when the price is unchanged and the amount is
+    /// not increased, otherwise it is matched again as a fresh order.
+    #[inline]
+    pub fn modify(
+        &mut self,
+        order_id: OrderId,
+        limit_price: u64,
+        amount: u64,
+    ) -> Vec<Event<Order>> {
+        let mut events = Vec::new();
+        self.modify_into(order_id, limit_price, amount, &mut events);
+        events
+    }
+
+    /// Like [`Engine::modify`], appending the events to `events` instead.
+    pub fn modify_into(
+        &mut self,
+        order_id: OrderId,
+        limit_price: u64,
+        amount: u64,
+        events: &mut Vec<Event<Order>>,
+    ) {
+        self.sequence += 1;
+        if !self.phase.accepts_orders() {
+            events.push(Event::Rejected(order_id, RejectReason::TradingPhase));
+            return;
+        }
+        let order = match self.orderbook.get_mut(&order_id) {
+            Some(order) if amount > order.filled() => order,
+            Some(_) => {
+                events.push(Event::Rejected(
+                    order_id,
+                    RejectReason::InvalidAmount,
+                ));
+                return;
+            }
+            None => {
+                events.push(Event::Rejected(
+                    order_id,
+                    RejectReason::UnknownOrder,
+                ));
+                return;
+            }
+        };
+
+        let replacement = Order::new(
+            order_id,
+            order.account_id(),
+            order.side(),
+            limit_price,
+            amount - order.filled(),
+        );
+        if limit_price == order.limit_price() && amount <= order.amount() {
+            order.replace(limit_price, amount);
+            self.reserve(&replacement)
+                .expect("a smaller order needs smaller funds");
+            events.push(Event::Modified(order_id));
+            self.indicate(events);
+            return;
+        }
+
+        if let Err(reason) = self
+            .risk
+            .check(&replacement, &self.orderbook)
+            .and_then(|_| self.reserve(&replacement))
+        {
+            events.push(Event::Rejected(order_id, reason));
+            return;
+        }
+
+        let mut order = self
+            .orderbook
+            .remove(&order_id)
+            .expect("order is resting in the orderbook");
+        order.replace(limit_price, amount);
+
+        let start = events.len();
+        events.push(Event::Modified(order_id));
+        self.matching(order, events);
+        self.executed(&mut events[start..]);
+        self.indicate(events);
+    }
+
+    #[inline]
+    pub fn phase(&self) -> Phase {
+        self.phase
+    }
+
+    /// Switch the orderbook to `phase`. Entering the continuous or closed
+    /// phase first uncrosses whatever a call phase left crossing, at a single
+    /// equilibrium price.
+    #[inline]
+    pub fn set_phase(&mut self, phase: Phase) -> Vec<Event<Order>> {
+        let mut events = Vec::new();
+        self.set_phase_into(phase, &mut events);
+        events
+    }
+
+    fn set_phase_into(&mut self, phase: Phase, events: &mut Vec<Event<Order>>) {
+        if phase == self.phase {
+            return;
+        }
+
+        self.sequence += 1;
+        self.resume_at = None;
+        if let Phase::Continuous | Phase::Closed = phase {
+            self.uncross(events);
+        }
+        self.phase = phase;
+        events.push(Event::Phase(phase));
+        self.indicate(events);
+    }
+
+    /// End a volatility auction which ran its course, then apply the
+    /// scheduled phase switches due by now, in order.
+    ///
+    /// [`Engine::try_process`] ticks before every request; call it directly
+    /// to switch phases while idle or when bypassing it.
+    #[inline]
+    pub fn tick(&mut self) -> Vec<Event<Order>> {
+        let mut events = Vec::new();
+        self.tick_into(&mut events);
+        events
+    }
+
+    /// Like [`Engine::tick`], appending the events to `events` instead.
+    pub fn tick_into(&mut self, events: &mut Vec<Event<Order>>) {
+        let now = self.clock.now();
+        if self.resume_at.is_some_and(|resume_at| resume_at <= now) {
+            self.set_phase_into(Phase::Continuous, events);
+        }
+        loop {
+            let due = match self.scheduled {
+                Some(last) => self.schedule.next(last),
+                None => self.schedule.current(now),
+            };
+            match due {
+                Some((at, phase)) if at <= now => {
+                    self.scheduled = Some(at);
+                    self.set_phase_into(phase, events);
                 }
+                _ => break,
             }
         }
     }
 
+    /// Return the price and volume the book would uncross at now, with the
+    /// last trade price as reference.
+    #[inline]
+    pub fn indicative(&self) -> Option<Equilibrium> {
+        Equilibrium::new(&self.orderbook, self.risk.last_price())
+    }
+
+    /// Simulate matching `amount` on `side` up to `limit_price` like
+    /// [`Orderbook::simulate`], as the engine would match it now: nothing
+    /// trades during a call phase or a phase refusing orders, and matching
+    /// stops before the first fill breaching the circuit breaker band. Risk
+    /// limits and funds are not checked.
+    pub fn simulate(
+        &self,
+        side: OrderSide,
+        limit_price: u64,
+        amount: u64,
+    ) -> Impact<OrderId> {
+        if self.phase.is_call() || !self.phase.accepts_orders() {
+            return Impact::new(amount);
+        }
+        let impact = self.orderbook.simulate(side, limit_price, amount);
+        let breaker = match &self.breaker {
+            Some(breaker) => breaker,
+            None => return impact,
+        };
+
+        let (pair, now) = (self.orderbook.pair(), self.clock.now());
+        let mut allowed = Impact::new(amount);
+        for fill in impact.fills() {
+            if !breaker.allows(pair, fill.price(), now) {
+                break;
+            }
+            if allowed.fills.last().map(|last| last.price())
+                != Some(fill.price())
+            {
+                allowed.levels += 1;
+            }
+            allowed.push(*fill);
+        }
+        allowed
+    }
+
+    /// Execute every crossing order at the single equilibrium price.
+    ///
+    /// Auction trades report the bid as the taker.
+    fn uncross(&mut self, events: &mut Vec<Event<Order>>) {
+        let equilibrium = match self.indicative() {
+            Some(equilibrium) => equilibrium,
+            None => return,
+        };
+
+        // Trade until either side has nothing left at the equilibrium price,
+        // which executes its volume even when it does not fit an amount.
+        let (start, price) = (events.len(), equilibrium.price());
+        let mut bid = None;
+        loop {
+            let mut taker = match bid.take() {
+                Some(taker) => taker,
+                None => match self.orderbook.peek(&OrderSide::Bid) {
+                    Some(top) if top.limit_price() >= price => self
+                        .orderbook
+                        .pop(&OrderSide::Bid)
+                        .expect("a bid rests at the top"),
+                    _ => break,
+                },
+            };
+            let maker = match self.orderbook.peek_mut(&OrderSide::Ask) {
+                Some(maker) if maker.limit_price() <= price => maker,
+                _ => {
+                    bid = Some(taker);
+                    break;
+                }
+            };
+
+            let amount = taker.remaining().min(maker.remaining());
+            events.push(Event::Traded(taker.cross(maker, amount, price)));
+
+            if maker.is_closed() {
+                self.orderbook.pop(&OrderSide::Ask);
+            }
+            if !taker.is_closed() {
+                bid = Some(taker);
+            }
+        }
+        if let Some(bid) = bid {
+            self.orderbook.restore(bid);
+        }
+
+        self.executed(&mut events[start..]);
+        if let Some(breaker) = &mut self.breaker {
+            breaker.reset(self.orderbook.pair(), price, self.clock.now());
+        }
+    }
+
+    /// Match `order`, or only rest it during a call phase. A trade breaching
+    /// the circuit breaker band stops matching and starts a volatility
+    /// auction instead.
+    fn matching(&mut self, order: Order, events: &mut Vec<Event<Order>>) {
+        if self.phase.is_call() {
+            events.push(Event::Added(order.id()));
+            self.orderbook.insert(order);
+            return;
+        }
+
+        let pair = CompactString::new(self.orderbook.pair());
+        let (breaker, band) = match self
+            .breaker
+            .as_ref()
+            .and_then(|breaker| Some((breaker, breaker.band(&pair)?)))
+        {
+            Some(breaker) => breaker,
+            None => {
+                return self.orderbook.matching_into(order, |_, _| true, events)
+            }
+        };
+
+        let now = self.clock.now();
+        let mut breached = false;
+        self.orderbook.matching_into(
+            order,
+            |taker, maker| {
+                // Trades print at the maker price.
+                let crosses = match taker.side() {
+                    OrderSide::Bid => {
+                        taker.limit_price() >= maker.limit_price()
+                    }
+                    OrderSide::Ask => {
+                        taker.limit_price() <= maker.limit_price()
+                    }
+                };
+                breached =
+                    crosses && !breaker.allows(&pair, maker.limit_price(), now);
+                !breached
+            },
+            events,
+        );
+        if breached {
+            self.phase = Phase::Auction;
+            self.resume_at = Some(now.saturating_add(band.auction()));
+            events.push(Event::Phase(Phase::Auction));
+        }
+    }
+
+    /// Publish the indicative price and volume during a call auction.
+    #[inline]
+    fn indicate(&self, events: &mut Vec<Event<Order>>) {
+        if self.phase == Phase::Auction {
+            events.push(Event::Indicative(self.indicative()));
+        }
+    }
+
+    /// Reserve the funds `order` needs to rest, if backed by a ledger.
+    fn reserve(&mut self, order: &Order) -> Result<(), RejectReason> {
+        let ledger = match &mut self.ledger {
+            Some(ledger) => ledger,
+            None => return Ok(()),
+        };
+        let (base, quote) = assets(self.orderbook.pair());
+        let (asset, amount) = match order.side() {
+            OrderSide::Bid => {
+                (quote, cost(order.remaining(), order.limit_price(), true))
+            }
+            OrderSide::Ask => (base, order.remaining()),
+        };
+
+        ledger
+            .reserve(order.id(), order.account_id(), asset, amount)
+            .map_err(|_| RejectReason::InsufficientBalance)
+    }
+
+    /// Stamp and account for the trades among `events`.
+    fn executed(&mut self, events: &mut [Event<Order>]) {
+        let now = self.clock.now();
+        for event in events.iter_mut() {
+            if let Event::Traded(trade) = event {
+                trade.id = self.trade_ids.fetch_add(1, Ordering::Relaxed) + 1;
+                trade.sequence = self.sequence;
+                trade.timestamp = now;
+                trade.pair = self.orderbook.pair().into();
+
+                if let Some(fees) = &mut self.fees {
+                    fees.charge(trade, now);
+                }
+            }
+        }
+        self.risk.update(events);
+        if let Some(breaker) = &mut self.breaker {
+            breaker.update(self.orderbook.pair(), events, now);
+        }
+        for event in events.iter() {
+            if let Event::Traded(trade) = event {
+                for order_id in [trade.taker, trade.maker] {
+                    if self.orderbook.get(&order_id).is_none() {
+                        self.history.push(order_id, OrderStatus::Completed);
+                    }
+                }
+            }
+        }
+
+        let ledger = match &mut self.ledger {
+            Some(ledger) => ledger,
+            None => return,
+        };
+        let (base, quote) = assets(self.orderbook.pair());
+        for event in events.iter() {
+            if let Event::Traded(trade) = event {
+                ledger.settle(trade, base, quote);
+            }
+        }
+        // Funds left over by better prices are no longer needed once an order
+        // is filled.
+        for event in events.iter() {
+            if let Event::Traded(trade) = event {
+                for order_id in [trade.taker, trade.maker] {
+                    if self.orderbook.get(&order_id).is_none() {
+                        ledger.release(&order_id);
+                    }
+                }
+            }
+        }
+    }
+
+    /// Return the resting order identified by `order_id`, if any.
+    #[inline]
+    pub fn order(&self, order_id: &OrderId) -> Option<&Order> {
+        self.orderbook.get(order_id)
+    }
+
+    /// Return the status of `order_id`, whether it rests or is among the last
+    /// orders which left the book, filled, cancelled or rejected. `None`
+    /// means the order never existed, or left the book too long ago.
+    #[inline]
+    pub fn status(&self, order_id: &OrderId) -> Option<OrderStatus> {
+        match self.orderbook.get(order_id) {
+            Some(order) => Some(order.status()),
+            None => self.history.get(order_id),
+        }
+    }
+
+    /// Return how many orders are ahead of `order_id` within its price level.
+    #[inline]
+    pub fn queue_position(&self, order_id: &OrderId) -> Option<usize> {
+        self.orderbook.queue_position(order_id)
+    }
+
+    /// Iterate over every open order owned by `account_id`, in no particular
+    /// order.
+    #[inline]
+    pub fn open_orders(
+        &self,
+        account_id: u64,
+    ) -> impl Iterator<Item = &Order> + '_ {
+        self.orderbook.orders_by_account(account_id)
+    }
+
+    #[inline]
+    pub fn risk(&self) -> &RiskManager {
+        &self.risk
+    }
+
+    #[inline]
+    pub fn ledger(&self) -> Option<&Ledger> {
+        self.ledger.as_ref()
+    }
+
+    /// Return the ledger, to move funds in and out of accounts.
+    #[inline]
+    pub fn ledger_mut(&mut self) -> Option<&mut Ledger> {
+        self.ledger.as_mut()
+    }
+
+    #[inline]
+    pub fn circuit_breaker(&self) -> Option<&CircuitBreaker> {
+        self.breaker.as_ref()
+    }
+
+    #[inline]
+    pub fn fees(&self) -> Option<&FeeEngine> {
+        self.fees.as_ref()
+    }
+
+    /// Return the fee engine, to change the schedule at runtime.
+    #[inline]
+    pub fn fees_mut(&mut self) -> Option<&mut FeeEngine> {
+        self.fees.as_mut()
+    }
+
+    /// Return the sequence number of the last request processed.
+    #[inline]
+    pub fn sequence(&self) -> u64 {
+        self.sequence
+    }
+
+    /// Return the current time, in milliseconds since the Unix epoch.
+    #[inline]
+    pub fn now(&self) -> u64 {
+        self.clock.now()
+    }
+
+    /// Return the risk manager, to set limits at runtime.
+    #[inline]
+    pub fn risk_mut(&mut self) -> &mut RiskManager {
+        &mut self.risk
+    }
+
     #[inline]
     pub fn orderbook(&self) -> &Orderbook<Order, Event<Order>, Trade> {
         &self.orderbook
     }
 }
+
+/// Split `pair` into its base and quote asset names.
+#[inline]
+fn assets(pair: &str) -> (&str, &str) {
+    pair.split_once('/').unwrap_or((pair, ""))
+}
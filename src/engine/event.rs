@@ -1,9 +1,18 @@
+use std::fmt;
+
+use super::{Equilibrium, Phase, RejectReason};
 use crate::{Asset, ExchangeEvent};
 
 pub enum Event<Order: Asset> {
     Added(<Order as Asset>::OrderId),
     Removed(<Order as Asset>::OrderId),
+    Modified(<Order as Asset>::OrderId),
     Traded(<Order as Asset>::Trade),
+    Rejected(<Order as Asset>::OrderId, RejectReason),
+    /// Price and volume the call auction would uncross at now.
+    Indicative(Option<Equilibrium>),
+    /// The book entered a new trading phase.
+    Phase(Phase),
 }
 
 impl<Order: Asset> ExchangeEvent for Event<Order> {
@@ -24,3 +33,35 @@ impl<Order: Asset> ExchangeEvent for Event<Order> {
         Self::Traded(trade)
     }
 }
+
+impl<Order: Asset> fmt::Debug for Event<Order>
+where
+    <Order as Asset>::OrderId: fmt::Debug,
+    <Order as Asset>::Trade: fmt::Debug,
+{
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Added(order_id) => {
+                f.debug_tuple("Added").field(order_id).finish()
+            }
+            Self::Removed(order_id) => {
+                f.debug_tuple("Removed").field(order_id).finish()
+            }
+            Self::Modified(order_id) => {
+                f.debug_tuple("Modified").field(order_id).finish()
+            }
+            Self::Traded(trade) => {
+                f.debug_tuple("Traded").field(trade).finish()
+            }
+            Self::Rejected(order_id, reason) => f
+                .debug_tuple("Rejected")
+                .field(order_id)
+                .field(reason)
+                .finish(),
+            Self::Indicative(equilibrium) => {
+                f.debug_tuple("Indicative").field(equilibrium).finish()
+            }
+            Self::Phase(phase) => f.debug_tuple("Phase").field(phase).finish(),
+        }
+    }
+}
@@ -0,0 +1,420 @@
+use std::collections::HashMap;
+
+use rust_decimal::Decimal;
+
+use super::{
+    to_units, Event, Order, OrderId, OrderRequest, OrderStatus, RejectReason,
+};
+use crate::{Asset, OrderSide};
+
+#[cfg(feature = "serde")]
+use serde::{Deserialize, Serialize};
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+#[cfg_attr(feature = "serde", serde(rename_all = "SCREAMING_SNAKE_CASE"))]
+pub enum ExecType {
+    New,
+    PartialFill,
+    Fill,
+    Canceled,
+    Rejected,
+    Replaced,
+}
+
+/// Per-order execution report, in the spirit of FIX `ExecutionReport`.
+///
+/// Prices and amounts are all in engine units, like those of [`Order`] and
+/// [`Trade`](super::Trade): the average price only keeps the fraction of a
+/// unit it may fall on.
+#[derive(Clone, Debug, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub struct ExecutionReport {
+    order_id: OrderId,
+    account_id: u64,
+    side: Option<OrderSide>,
+    exec_type: ExecType,
+    order_status: OrderStatus,
+    limit_price: u64,
+    leaves_qty: u64,
+    cum_qty: u64,
+    avg_px: Decimal,
+    last_qty: u64,
+    last_px: u64,
+    #[cfg_attr(feature = "serde", serde(default))]
+    reason: Option<RejectReason>,
+}
+
+impl ExecutionReport {
+    /// Report the rejection of a request about `order_id`, which is not live
+    /// or not known to the requester.
+    pub fn rejected(order_id: OrderId, reason: RejectReason) -> Self {
+        Self {
+            order_id,
+            account_id: 0,
+            side: None,
+            exec_type: ExecType::Rejected,
+            order_status: OrderStatus::Rejected,
+            limit_price: 0,
+            leaves_qty: 0,
+            cum_qty: 0,
+            avg_px: Decimal::ZERO,
+            last_qty: 0,
+            last_px: 0,
+            reason: Some(reason),
+        }
+    }
+
+    #[inline]
+    pub fn order_id(&self) -> OrderId {
+        self.order_id
+    }
+
+    #[inline]
+    pub fn account_id(&self) -> u64 {
+        self.account_id
+    }
+
+    /// Side of the order, unknown when rejecting a request about an order
+    /// which is not live.
+    #[inline]
+    pub fn side(&self) -> Option<OrderSide> {
+        self.side
+    }
+
+    #[inline]
+    pub fn exec_type(&self) -> ExecType {
+        self.exec_type
+    }
+
+    #[inline]
+    pub fn order_status(&self) -> OrderStatus {
+        self.order_status
+    }
+
+    #[inline]
+    pub fn limit_price(&self) -> u64 {
+        self.limit_price
+    }
+
+    /// Amount still open for further execution.
+    #[inline]
+    pub fn leaves_qty(&self) -> u64 {
+        self.leaves_qty
+    }
+
+    /// Total amount executed so far.
+    #[inline]
+    pub fn cum_qty(&self) -> u64 {
+        self.cum_qty
+    }
+
+    /// Volume-weighted average price of every execution so far, in engine
+    /// units.
+    #[inline]
+    pub fn avg_px(&self) -> Decimal {
+        self.avg_px
+    }
+
+    /// Amount executed by the execution this report refers to.
+    #[inline]
+    pub fn last_qty(&self) -> u64 {
+        self.last_qty
+    }
+
+    /// Price of the execution this report refers to.
+    #[inline]
+    pub fn last_px(&self) -> u64 {
+        self.last_px
+    }
+
+    #[inline]
+    pub fn reason(&self) -> Option<RejectReason> {
+        self.reason
+    }
+}
+
+/// What the generator remembers about a live order between reports.
+#[derive(Clone, Copy, Debug)]
+struct OrderState {
+    account_id: u64,
+    side: OrderSide,
+    limit_price: u64,
+    amount: u64,
+    cum_qty: u64,
+    notional: u128,
+}
+
+impl OrderState {
+    #[inline]
+    fn new(order: &Order) -> Self {
+        Self {
+            account_id: order.account_id(),
+            side: order.side(),
+            limit_price: order.limit_price(),
+            amount: order.amount(),
+            cum_qty: order.filled(),
+            notional: u128::from(order.filled())
+                * u128::from(order.limit_price()),
+        }
+    }
+
+    #[inline]
+    fn report(
+        &self,
+        order_id: OrderId,
+        exec_type: ExecType,
+        order_status: OrderStatus,
+    ) -> ExecutionReport {
+        let leaves_qty = match order_status {
+            OrderStatus::Open | OrderStatus::Partial => {
+                self.amount - self.cum_qty
+            }
+            _ => 0,
+        };
+        // The notional may not fit a decimal, while the quotient, which is
+        // a price, and the remainder do.
+        let avg_px = match u128::from(self.cum_qty) {
+            0 => Decimal::ZERO,
+            cum_qty => {
+                Decimal::from(self.notional / cum_qty)
+                    + Decimal::from(self.notional % cum_qty)
+                        / Decimal::from(cum_qty)
+            }
+        };
+
+        ExecutionReport {
+            order_id,
+            account_id: self.account_id,
+            side: Some(self.side),
+            exec_type,
+            order_status,
+            limit_price: self.limit_price,
+            leaves_qty,
+            cum_qty: self.cum_qty,
+            avg_px,
+            last_qty: 0,
+            last_px: 0,
+            reason: None,
+        }
+    }
+}
+
+/// Turns the events produced by [`Engine`](super::Engine) into execution
+/// reports for every order involved, on both sides of each trade.
+///
+/// The generator keeps track of each live order it has seen created, so it
+/// must observe every request processed by the engine.
+#[derive(Debug, Default)]
+pub struct ReportGenerator {
+    orders: HashMap<OrderId, OrderState>,
+}
+
+impl ReportGenerator {
+    #[inline]
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Generate the reports for `request` given the `events` the engine
+    /// returned while processing it.
+    ///
+    /// The events of the phase switches [`Engine::try_process`] applies first
+    /// must be left out: tick the engine beforehand and report on its events
+    /// with [`ReportGenerator::reports`].
+    ///
+    /// [`Engine::try_process`]: super::Engine::try_process
+    pub fn generate(
+        &mut self,
+        request: &OrderRequest,
+        events: &[Event<Order>],
+    ) -> Vec<ExecutionReport> {
+        match request {
+            OrderRequest::Create { .. } => {
+                match Order::try_from(request.clone()) {
+                    Ok(order) => self.created(&order, events),
+                    Err(_) => Vec::new(),
+                }
+            }
+            OrderRequest::Modify {
+                order_id,
+                amount,
+                limit_price,
+            } => match (
+                order_id.parse::<u64>(),
+                to_units(*limit_price),
+                to_units(*amount),
+            ) {
+                (Ok(order_id), Some(limit_price), Some(amount)) => self
+                    .modified(
+                        OrderId::new(order_id),
+                        limit_price,
+                        amount,
+                        events,
+                    ),
+                _ => Vec::new(),
+            },
+            _ => self.reports(events),
+        }
+    }
+
+    /// Generate the reports for a new `order` given the `events` the engine
+    /// returned while matching it.
+    pub fn created(
+        &mut self,
+        order: &Order,
+        events: &[Event<Order>],
+    ) -> Vec<ExecutionReport> {
+        let state = OrderState::new(order);
+
+        if let Some(reason) = events.iter().find_map(|event| match event {
+            Event::Rejected(order_id, reason) if *order_id == order.id() => {
+                Some(*reason)
+            }
+            _ => None,
+        }) {
+            let mut report = state.report(
+                order.id(),
+                ExecType::Rejected,
+                OrderStatus::Rejected,
+            );
+            report.reason = Some(reason);
+            return vec![report];
+        }
+
+        self.orders.insert(order.id(), state);
+        let mut reports = Vec::with_capacity(events.len() + 1);
+        reports.push(state.report(
+            order.id(),
+            ExecType::New,
+            OrderStatus::Open,
+        ));
+        reports.extend(self.reports(events));
+        reports
+    }
+
+    /// Generate the reports for a modification of `order_id` given the
+    /// `events` the engine returned while processing it.
+    pub fn modified(
+        &mut self,
+        order_id: OrderId,
+        limit_price: u64,
+        amount: u64,
+        events: &[Event<Order>],
+    ) -> Vec<ExecutionReport> {
+        if events.iter().any(
+            |event| matches!(event, Event::Modified(id) if *id == order_id),
+        ) {
+            if let Some(state) = self.orders.get_mut(&order_id) {
+                state.limit_price = limit_price;
+                state.amount = amount;
+            }
+        }
+
+        let mut reports = self.reports(events);
+        for report in &mut reports {
+            if report.order_status == OrderStatus::Rejected {
+                report.limit_price = limit_price;
+            }
+        }
+        reports
+    }
+
+    /// Generate the reports for `events` about orders already known. A
+    /// rejection about any other order is reported as a rejected order.
+    pub fn reports(&mut self, events: &[Event<Order>]) -> Vec<ExecutionReport> {
+        let mut reports = Vec::with_capacity(events.len() * 2);
+
+        for event in events {
+            match event {
+                Event::Added(_) | Event::Indicative(_) | Event::Phase(_) => (),
+                Event::Modified(order_id) => {
+                    if let Some(state) = self.orders.get(order_id) {
+                        let order_status = match state.cum_qty {
+                            0 => OrderStatus::Open,
+                            _ => OrderStatus::Partial,
+                        };
+                        reports.push(state.report(
+                            *order_id,
+                            ExecType::Replaced,
+                            order_status,
+                        ));
+                    }
+                }
+                Event::Traded(trade) => {
+                    reports.extend(
+                        [trade.taker, trade.maker].into_iter().filter_map(
+                            |order_id| {
+                                self.executed(
+                                    order_id,
+                                    trade.amount,
+                                    trade.price,
+                                )
+                            },
+                        ),
+                    );
+                }
+                Event::Removed(order_id) => {
+                    if let Some(state) = self.orders.remove(order_id) {
+                        let order_status = match state.cum_qty {
+                            0 => OrderStatus::Cancelled,
+                            _ => OrderStatus::Closed,
+                        };
+                        reports.push(state.report(
+                            *order_id,
+                            ExecType::Canceled,
+                            order_status,
+                        ));
+                    }
+                }
+                Event::Rejected(order_id, reason) => {
+                    // A rejected modification leaves the resting order as it
+                    // was, so its current status is reported.
+                    reports.push(match self.orders.get(order_id) {
+                        Some(state) => {
+                            let mut report = state.report(
+                                *order_id,
+                                ExecType::Rejected,
+                                match state.cum_qty {
+                                    0 => OrderStatus::Open,
+                                    _ => OrderStatus::Partial,
+                                },
+                            );
+                            report.reason = Some(*reason);
+                            report
+                        }
+                        None => ExecutionReport::rejected(*order_id, *reason),
+                    });
+                }
+            }
+        }
+
+        reports
+    }
+
+    fn executed(
+        &mut self,
+        order_id: OrderId,
+        amount: u64,
+        price: u64,
+    ) -> Option<ExecutionReport> {
+        let state = self.orders.get_mut(&order_id)?;
+        state.cum_qty += amount;
+        state.notional += u128::from(amount) * u128::from(price);
+
+        let (exec_type, order_status) = if state.cum_qty == state.amount {
+            (ExecType::Fill, OrderStatus::Completed)
+        } else {
+            (ExecType::PartialFill, OrderStatus::Partial)
+        };
+        let mut report = state.report(order_id, exec_type, order_status);
+        report.last_qty = amount;
+        report.last_px = price;
+
+        if order_status == OrderStatus::Completed {
+            self.orders.remove(&order_id);
+        }
+
+        Some(report)
+    }
+}
@@ -0,0 +1,219 @@
+use std::collections::{HashMap, VecDeque};
+
+use compact_str::CompactString;
+
+use super::ledger::cost;
+use super::Trade;
+use crate::OrderSide;
+
+#[cfg(feature = "serde")]
+use serde::{Deserialize, Serialize};
+
+/// Window over which the volume deciding an account tier is summed.
+pub const VOLUME_WINDOW: u64 = 30 * 24 * 60 * 60 * 1000;
+
+/// Maker and taker rates, in basis points, applying from a minimum 30-day
+/// volume on. A negative maker rate is a rebate.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub struct FeeTier {
+    min_volume: u64,
+    maker_rate: i64,
+    taker_rate: i64,
+}
+
+impl FeeTier {
+    /// # Panics
+    ///
+    /// Panics if a rate exceeds 100%, either way.
+    #[inline]
+    pub fn new(min_volume: u64, maker_rate: i64, taker_rate: i64) -> Self {
+        assert!(
+            maker_rate.abs() <= 10_000 && taker_rate.abs() <= 10_000,
+            "fee rates should be within 10000 basis points"
+        );
+
+        Self {
+            min_volume,
+            maker_rate,
+            taker_rate,
+        }
+    }
+
+    /// Minimum 30-day volume, in units of the quote asset.
+    #[inline]
+    pub fn min_volume(&self) -> u64 {
+        self.min_volume
+    }
+
+    #[inline]
+    pub fn maker_rate(&self) -> i64 {
+        self.maker_rate
+    }
+
+    #[inline]
+    pub fn taker_rate(&self) -> i64 {
+        self.taker_rate
+    }
+}
+
+/// Fee tiers, with optional overrides per pair.
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub struct FeeSchedule {
+    tiers: Vec<FeeTier>,
+    #[cfg_attr(feature = "serde", serde(default))]
+    pairs: HashMap<CompactString, Vec<FeeTier>>,
+}
+
+impl FeeSchedule {
+    /// Create a schedule charging every account the same rates.
+    #[inline]
+    pub fn new(maker_rate: i64, taker_rate: i64) -> Self {
+        Self::default().with_tier(FeeTier::new(0, maker_rate, taker_rate))
+    }
+
+    #[inline]
+    pub fn with_tier(mut self, tier: FeeTier) -> Self {
+        insert_tier(&mut self.tiers, tier);
+        self
+    }
+
+    /// Add a tier to `pair`, whose tiers then replace the default ones.
+    #[inline]
+    pub fn with_pair_tier(mut self, pair: &str, tier: FeeTier) -> Self {
+        insert_tier(self.pairs.entry(pair.into()).or_default(), tier);
+        self
+    }
+
+    /// Return the tier applying on `pair` to an account with `volume`.
+    pub fn tier(&self, pair: &str, volume: u64) -> Option<FeeTier> {
+        self.pairs
+            .get(pair)
+            .unwrap_or(&self.tiers)
+            .iter()
+            .rev()
+            .find(|tier| tier.min_volume <= volume)
+            .copied()
+    }
+}
+
+#[inline]
+fn insert_tier(tiers: &mut Vec<FeeTier>, tier: FeeTier) {
+    tiers.retain(|other| other.min_volume != tier.min_volume);
+    let index =
+        tiers.partition_point(|other| other.min_volume < tier.min_volume);
+    tiers.insert(index, tier);
+}
+
+/// Charges fees on trades following a [`FeeSchedule`], keeping track of each
+/// account rolling 30-day volume.
+///
+/// Fees are taken out of what each side receives: the base asset for the
+/// buyer and the quote asset for the seller.
+#[derive(Debug, Default)]
+pub struct FeeEngine {
+    schedule: FeeSchedule,
+    volumes: HashMap<u64, Volume>,
+}
+
+#[derive(Debug, Default)]
+struct Volume {
+    trades: VecDeque<(u64, u64)>,
+    /// Exact sum of the notionals in `trades`, which may exceed `u64::MAX`.
+    total: u128,
+}
+
+impl Volume {
+    #[inline]
+    fn expire(&mut self, now: u64) {
+        while let Some((timestamp, notional)) = self.trades.front() {
+            if now.saturating_sub(*timestamp) < VOLUME_WINDOW {
+                break;
+            }
+            self.total -= u128::from(*notional);
+            self.trades.pop_front();
+        }
+    }
+}
+
+impl FeeEngine {
+    #[inline]
+    pub fn new(schedule: FeeSchedule) -> Self {
+        Self {
+            schedule,
+            volumes: HashMap::new(),
+        }
+    }
+
+    #[inline]
+    pub fn schedule(&self) -> &FeeSchedule {
+        &self.schedule
+    }
+
+    /// Replace the schedule, keeping the volumes traded so far.
+    #[inline]
+    pub fn set_schedule(&mut self, schedule: FeeSchedule) {
+        self.schedule = schedule;
+    }
+
+    /// Return the volume `account_id` traded over the window ending at `now`,
+    /// saturating at `u64::MAX`.
+    pub fn volume(&self, account_id: u64, now: u64) -> u64 {
+        let volume = self.volumes.get(&account_id).map_or(0, |volume| {
+            volume
+                .trades
+                .iter()
+                .filter(|(timestamp, _)| {
+                    now.saturating_sub(*timestamp) < VOLUME_WINDOW
+                })
+                .map(|(_, notional)| u128::from(*notional))
+                .sum::<u128>()
+        });
+        u64::try_from(volume).unwrap_or(u64::MAX)
+    }
+
+    /// Record the fees of `trade`, then count it towards both accounts
+    /// volume.
+    pub(super) fn charge(&mut self, trade: &mut Trade, now: u64) {
+        let notional = cost(trade.amount, trade.price, false);
+        let (taker_receives, maker_receives) = match trade.aggressor {
+            OrderSide::Bid => (trade.amount, notional),
+            OrderSide::Ask => (notional, trade.amount),
+        };
+
+        let mut rate = |account_id, maker| {
+            let volume = self.volumes.entry(account_id).or_default();
+            volume.expire(now);
+            self.schedule
+                .tier(
+                    &trade.pair,
+                    u64::try_from(volume.total).unwrap_or(u64::MAX),
+                )
+                .map_or(0, |tier| match maker {
+                    true => tier.maker_rate,
+                    false => tier.taker_rate,
+                })
+        };
+        trade.taker_fee = fee(taker_receives, rate(trade.taker_account, false));
+        trade.maker_fee = fee(maker_receives, rate(trade.maker_account, true));
+
+        for account_id in [trade.taker_account, trade.maker_account] {
+            let volume = self.volumes.entry(account_id).or_default();
+            volume.trades.push_back((now, notional));
+            volume.total += u128::from(notional);
+        }
+    }
+}
+
+/// Return the fee on `amount` at `rate` basis points, rounded in favour of
+/// the exchange.
+#[inline]
+fn fee(amount: u64, rate: i64) -> i64 {
+    let fee = i128::from(amount) * i128::from(rate);
+    let fee = match fee > 0 {
+        true => (fee + 9_999) / 10_000,
+        false => fee / 10_000,
+    };
+    fee as i64
+}
@@ -0,0 +1,46 @@
+use std::collections::{HashMap, VecDeque};
+
+use super::{OrderId, OrderStatus};
+
+/// Bounded record of the final status of the orders which left the book,
+/// forgetting the oldest ones first.
+#[derive(Debug)]
+pub(super) struct History {
+    capacity: usize,
+    statuses: HashMap<OrderId, OrderStatus>,
+    order_ids: VecDeque<OrderId>,
+}
+
+impl History {
+    #[inline]
+    pub(super) fn new(capacity: usize) -> Self {
+        Self {
+            capacity,
+            statuses: HashMap::new(),
+            order_ids: VecDeque::new(),
+        }
+    }
+
+    /// Record the final `status` of `order_id`, forgetting the oldest order
+    /// when full.
+    pub(super) fn push(&mut self, order_id: OrderId, status: OrderStatus) {
+        if self.capacity == 0 {
+            return;
+        }
+        // An identifier used again keeps its place, with its latest status.
+        if self.statuses.insert(order_id, status).is_some() {
+            return;
+        }
+        if self.order_ids.len() == self.capacity {
+            if let Some(oldest) = self.order_ids.pop_front() {
+                self.statuses.remove(&oldest);
+            }
+        }
+        self.order_ids.push_back(order_id);
+    }
+
+    #[inline]
+    pub(super) fn get(&self, order_id: &OrderId) -> Option<OrderStatus> {
+        self.statuses.get(order_id).copied()
+    }
+}
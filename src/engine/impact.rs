@@ -0,0 +1,110 @@
+#[cfg(feature = "serde")]
+use serde::{Deserialize, Serialize};
+
+/// Would-be trade against a resting order.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub struct Fill<OrderId> {
+    maker: OrderId,
+    maker_account: u64,
+    amount: u64,
+    price: u64,
+}
+
+impl<OrderId: Copy> Fill<OrderId> {
+    #[inline]
+    pub(super) fn new(
+        maker: OrderId,
+        maker_account: u64,
+        amount: u64,
+        price: u64,
+    ) -> Self {
+        Self {
+            maker,
+            maker_account,
+            amount,
+            price,
+        }
+    }
+
+    #[inline]
+    pub fn maker(&self) -> OrderId {
+        self.maker
+    }
+
+    #[inline]
+    pub fn maker_account(&self) -> u64 {
+        self.maker_account
+    }
+
+    #[inline]
+    pub fn amount(&self) -> u64 {
+        self.amount
+    }
+
+    #[inline]
+    pub fn price(&self) -> u64 {
+        self.price
+    }
+}
+
+/// What an order would do to the book if it were matched now.
+#[derive(Clone, Debug, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub struct Impact<OrderId> {
+    pub(super) fills: Vec<Fill<OrderId>>,
+    pub(super) levels: usize,
+    pub(super) leftover: u64,
+    notional: u128,
+}
+
+impl<OrderId: Copy> Impact<OrderId> {
+    #[inline]
+    pub(super) fn new(amount: u64) -> Self {
+        Self {
+            fills: Vec::new(),
+            levels: 0,
+            leftover: amount,
+            notional: 0,
+        }
+    }
+
+    #[inline]
+    pub(super) fn push(&mut self, fill: Fill<OrderId>) {
+        self.leftover -= fill.amount;
+        self.notional += u128::from(fill.amount) * u128::from(fill.price);
+        self.fills.push(fill);
+    }
+
+    /// Return the would-be trades, in the order they would happen.
+    #[inline]
+    pub fn fills(&self) -> &[Fill<OrderId>] {
+        &self.fills
+    }
+
+    /// Return the amount which would trade.
+    #[inline]
+    pub fn filled(&self) -> u64 {
+        self.fills.iter().map(Fill::amount).sum()
+    }
+
+    /// Return the amount which would be left to rest.
+    #[inline]
+    pub fn leftover(&self) -> u64 {
+        self.leftover
+    }
+
+    /// Return how many price levels would be traded against.
+    #[inline]
+    pub fn levels(&self) -> usize {
+        self.levels
+    }
+
+    /// Return the average price weighted by amount, rounded down, if
+    /// anything would trade.
+    #[inline]
+    pub fn average_price(&self) -> Option<u64> {
+        let filled = u128::from(self.filled());
+        (filled > 0).then(|| (self.notional / filled) as u64)
+    }
+}
@@ -0,0 +1,26 @@
+use thiserror::Error;
+
+/// Broken invariant found by [`Orderbook::validate`].
+///
+/// [`Orderbook::validate`]: super::Orderbook::validate
+#[derive(Clone, Copy, Debug, Error, PartialEq, Eq)]
+pub enum InvariantError<OrderId: std::fmt::Debug> {
+    #[error("level at {0} holds no order")]
+    EmptyLevel(u64),
+    #[error("level at {0} is not linked consistently")]
+    BrokenLevel(u64),
+    #[error("order {0:?} rests at another price or side than its level")]
+    MisplacedOrder(OrderId),
+    #[error("order {0:?} rests with nothing left to trade")]
+    ClosedOrder(OrderId),
+    #[error("order {0:?} filled more than its amount")]
+    Overfilled(OrderId),
+    #[error("level at {0} does not total the remaining amount of its orders")]
+    LevelQuantity(u64),
+    #[error("book is crossed: best ask {ask} is not above best bid {bid}")]
+    Crossed { ask: u64, bid: u64 },
+    #[error("order {0:?} rests without being indexed")]
+    UnindexedOrder(OrderId),
+    #[error("order {0:?} is indexed without resting")]
+    DanglingOrder(OrderId),
+}
@@ -0,0 +1,230 @@
+use std::collections::HashMap;
+
+use compact_str::CompactString;
+
+use super::{OrderId, Trade, DECIMALS};
+use crate::OrderSide;
+
+#[cfg(feature = "serde")]
+use serde::{Deserialize, Serialize};
+use thiserror::Error;
+
+#[derive(Debug, Error, PartialEq, Eq)]
+pub enum LedgerError {
+    #[error("insufficient available balance")]
+    InsufficientBalance,
+}
+
+/// Holdings of an account in a single asset.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub struct Balance {
+    total: u64,
+    reserved: u64,
+}
+
+impl Balance {
+    #[inline]
+    pub fn total(&self) -> u64 {
+        self.total
+    }
+
+    /// Amount held for resting orders.
+    #[inline]
+    pub fn reserved(&self) -> u64 {
+        self.reserved
+    }
+
+    /// Amount free to back new orders or to be withdrawn.
+    #[inline]
+    pub fn available(&self) -> u64 {
+        self.total - self.reserved
+    }
+}
+
+#[derive(Debug)]
+struct Reservation {
+    account_id: u64,
+    asset: CompactString,
+    amount: u64,
+}
+
+/// Per-asset account balances, and the funds reserved by each open order.
+///
+/// Amounts carry the same implied decimals as orders, so buying `amount` at
+/// `price` costs `amount * price`, scaled back by those decimals, of the
+/// quote asset.
+#[derive(Debug, Default)]
+pub struct Ledger {
+    balances: HashMap<u64, HashMap<CompactString, Balance>>,
+    reservations: HashMap<OrderId, Reservation>,
+    fees: HashMap<CompactString, i128>,
+}
+
+impl Ledger {
+    #[inline]
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Return the balance of `account_id` in `asset`.
+    #[inline]
+    pub fn balance(&self, account_id: u64, asset: &str) -> Balance {
+        self.balances
+            .get(&account_id)
+            .and_then(|balances| balances.get(asset))
+            .copied()
+            .unwrap_or_default()
+    }
+
+    /// Credit `amount` of `asset` to `account_id`.
+    #[inline]
+    pub fn deposit(&mut self, account_id: u64, asset: &str, amount: u64) {
+        self.balance_mut(account_id, asset).total += amount;
+    }
+
+    /// Debit `amount` of `asset` from `account_id`, which cannot touch the
+    /// reserved funds.
+    pub fn withdraw(
+        &mut self,
+        account_id: u64,
+        asset: &str,
+        amount: u64,
+    ) -> Result<(), LedgerError> {
+        let balance = self.balance_mut(account_id, asset);
+        if balance.available() < amount {
+            return Err(LedgerError::InsufficientBalance);
+        }
+
+        balance.total -= amount;
+        Ok(())
+    }
+
+    /// Return the net fees collected in `asset`, rebates deducted.
+    #[inline]
+    pub fn fees(&self, asset: &str) -> i128 {
+        self.fees.get(asset).copied().unwrap_or_default()
+    }
+
+    /// Return the amount `order_id` still holds in reserve.
+    #[inline]
+    pub fn reserved(&self, order_id: &OrderId) -> u64 {
+        self.reservations
+            .get(order_id)
+            .map_or(0, |reservation| reservation.amount)
+    }
+
+    /// Reserve `amount` of `asset` for `order_id`, in place of whatever the
+    /// order held before.
+    pub(super) fn reserve(
+        &mut self,
+        order_id: OrderId,
+        account_id: u64,
+        asset: &str,
+        amount: u64,
+    ) -> Result<(), LedgerError> {
+        let held = match self.reservations.get(&order_id) {
+            Some(reservation) if reservation.asset == asset => {
+                reservation.amount
+            }
+            _ => 0,
+        };
+        if self.balance(account_id, asset).available() + held < amount {
+            return Err(LedgerError::InsufficientBalance);
+        }
+
+        self.release(&order_id);
+        self.balance_mut(account_id, asset).reserved += amount;
+        self.reservations.insert(
+            order_id,
+            Reservation {
+                account_id,
+                asset: asset.into(),
+                amount,
+            },
+        );
+        Ok(())
+    }
+
+    /// Release whatever `order_id` still holds in reserve.
+    pub(super) fn release(&mut self, order_id: &OrderId) {
+        if let Some(reservation) = self.reservations.remove(order_id) {
+            self.balance_mut(reservation.account_id, &reservation.asset)
+                .reserved -= reservation.amount;
+        }
+    }
+
+    /// Exchange the assets of `trade` between both accounts, out of the
+    /// funds their orders reserved, and charge their fees on what each one
+    /// receives.
+    pub(super) fn settle(&mut self, trade: &Trade, base: &str, quote: &str) {
+        let (buyer, seller) = match trade.aggressor {
+            OrderSide::Bid => (
+                (trade.taker, trade.taker_account, trade.taker_fee),
+                (trade.maker, trade.maker_account, trade.maker_fee),
+            ),
+            OrderSide::Ask => (
+                (trade.maker, trade.maker_account, trade.maker_fee),
+                (trade.taker, trade.taker_account, trade.taker_fee),
+            ),
+        };
+        let cost = cost(trade.amount, trade.price, false);
+
+        self.spend((buyer.0, buyer.1), quote, cost);
+        self.credit(buyer.1, base, trade.amount, buyer.2);
+        self.spend((seller.0, seller.1), base, trade.amount);
+        self.credit(seller.1, quote, cost, seller.2);
+    }
+
+    /// Credit `amount` of `asset` to `account_id`, less `fee`.
+    #[inline]
+    fn credit(&mut self, account_id: u64, asset: &str, amount: u64, fee: i64) {
+        let balance = self.balance_mut(account_id, asset);
+        balance.total = (i128::from(balance.total) + i128::from(amount)
+            - i128::from(fee)) as u64;
+        *self.fees.entry(asset.into()).or_default() += i128::from(fee);
+    }
+
+    /// Debit `amount` of `asset` from the funds reserved by an order.
+    #[inline]
+    fn spend(
+        &mut self,
+        (order_id, account_id): (OrderId, u64),
+        asset: &str,
+        amount: u64,
+    ) {
+        if let Some(reservation) = self.reservations.get_mut(&order_id) {
+            debug_assert!(
+                reservation.amount >= amount,
+                "spent amount should be covered by the reservation"
+            );
+            reservation.amount -= amount;
+        }
+
+        let balance = self.balance_mut(account_id, asset);
+        balance.reserved -= amount;
+        balance.total -= amount;
+    }
+
+    #[inline]
+    fn balance_mut(&mut self, account_id: u64, asset: &str) -> &mut Balance {
+        self.balances
+            .entry(account_id)
+            .or_default()
+            .entry(asset.into())
+            .or_default()
+    }
+}
+
+/// Return the quote amount of `amount` at `price`. Reservations round up so
+/// they always cover executions, which round down.
+#[inline]
+pub(super) fn cost(amount: u64, price: u64, round_up: bool) -> u64 {
+    let cost = u128::from(amount) * u128::from(price);
+    let unit = 10u128.pow(DECIMALS);
+    let cost = match round_up {
+        true => cost.div_ceil(unit),
+        false => cost / unit,
+    };
+    u64::try_from(cost).unwrap_or(u64::MAX)
+}
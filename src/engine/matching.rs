@@ -0,0 +1,301 @@
+use std::cmp::Reverse;
+
+#[cfg(feature = "serde")]
+use serde::{Deserialize, Serialize};
+
+/// Decides how an incoming order is split among the orders resting at the
+/// best opposite price level.
+pub trait MatchingStrategy {
+    /// Split `amount` among `orders`, given in time priority as
+    /// `(account_id, remaining)` pairs, writing what each of them trades into
+    /// `fills`.
+    ///
+    /// Fills never exceed an order remaining amount and add up to `amount`,
+    /// or to the whole level when it holds less.
+    fn allocate(&self, amount: u64, orders: &[(u64, u64)], fills: &mut [u64]);
+
+    /// Allocate like [`MatchingStrategy::allocate`], ranking orders in
+    /// `scratch` when needed, so that a buffer kept from one call to the next
+    /// spares the allocation.
+    #[inline]
+    fn allocate_with(
+        &self,
+        amount: u64,
+        orders: &[(u64, u64)],
+        fills: &mut [u64],
+        _scratch: &mut Vec<usize>,
+    ) {
+        self.allocate(amount, orders, fills);
+    }
+
+    /// Whether `allocate` needs every order at the level, or only the oldest
+    /// ones holding at least `amount`.
+    #[inline]
+    fn whole_level(&self) -> bool {
+        true
+    }
+}
+
+/// Price-time priority: the oldest order at a level fills first.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct Fifo;
+
+impl MatchingStrategy for Fifo {
+    fn allocate(&self, amount: u64, orders: &[(u64, u64)], fills: &mut [u64]) {
+        let mut left = amount;
+        for (fill, (_, remaining)) in fills.iter_mut().zip(orders) {
+            *fill = left.min(*remaining);
+            left -= *fill;
+        }
+    }
+
+    #[inline]
+    fn whole_level(&self) -> bool {
+        false
+    }
+}
+
+/// Where the amount left over by rounding pro-rata shares down goes.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+#[cfg_attr(feature = "serde", serde(rename_all = "SCREAMING_SNAKE_CASE"))]
+pub enum Residual {
+    /// To the oldest orders first.
+    #[default]
+    Fifo,
+    /// To the orders with the most room left first, the oldest one among
+    /// equals. Room is what an order has left to fill once the priority
+    /// allocations of a [`Hybrid`] strategy are taken.
+    Largest,
+}
+
+/// Pro-rata: every order at a level fills in proportion to its remaining
+/// amount.
+///
+/// Shares are rounded down to a multiple of the lot size, and whatever the
+/// rounding leaves over is handed out following the [`Residual`] rule.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub struct ProRata {
+    lot: u64,
+    residual: Residual,
+}
+
+impl Default for ProRata {
+    #[inline]
+    fn default() -> Self {
+        Self {
+            lot: 1,
+            residual: Residual::default(),
+        }
+    }
+}
+
+impl ProRata {
+    #[inline]
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Round shares down to multiples of `lot`.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `lot` is zero.
+    #[inline]
+    pub fn with_lot(mut self, lot: u64) -> Self {
+        assert!(lot > 0, "lot size should be positive");
+        self.lot = lot;
+        self
+    }
+
+    #[inline]
+    pub fn with_residual(mut self, residual: Residual) -> Self {
+        self.residual = residual;
+        self
+    }
+
+    #[inline]
+    pub fn lot(&self) -> u64 {
+        self.lot
+    }
+
+    #[inline]
+    pub fn residual(&self) -> Residual {
+        self.residual
+    }
+}
+
+impl ProRata {
+    /// Split `amount` pro-rata on top of `fills`, over the room each order
+    /// has left.
+    fn split(
+        &self,
+        amount: u64,
+        orders: &[(u64, u64)],
+        fills: &mut [u64],
+        scratch: &mut Vec<usize>,
+    ) {
+        let room = |(index, (_, remaining)): (usize, &(u64, u64))| {
+            remaining - fills[index]
+        };
+        let total = orders
+            .iter()
+            .enumerate()
+            .map(|order| u128::from(room(order)))
+            .sum::<u128>();
+        if u128::from(amount) >= total {
+            for (fill, (_, remaining)) in fills.iter_mut().zip(orders) {
+                *fill = *remaining;
+            }
+            return;
+        }
+
+        if self.residual == Residual::Largest {
+            // Rank by the room left before the split, the oldest order first
+            // among equals.
+            scratch.clear();
+            scratch.extend(0..orders.len());
+            scratch.sort_unstable_by_key(|index| {
+                (Reverse(orders[*index].1 - fills[*index]), *index)
+            });
+        }
+
+        let mut left = amount;
+        for (index, (_, remaining)) in orders.iter().enumerate() {
+            let share = (u128::from(amount)
+                * u128::from(remaining - fills[index])
+                / total) as u64;
+            let share = share - share % self.lot;
+            fills[index] += share;
+            left -= share;
+        }
+
+        match self.residual {
+            Residual::Fifo => {
+                for (fill, (_, remaining)) in fills.iter_mut().zip(orders) {
+                    let extra = left.min(remaining - *fill);
+                    *fill += extra;
+                    left -= extra;
+                }
+            }
+            Residual::Largest => {
+                for index in scratch.iter() {
+                    let extra = left.min(orders[*index].1 - fills[*index]);
+                    fills[*index] += extra;
+                    left -= extra;
+                }
+            }
+        }
+    }
+}
+
+impl MatchingStrategy for ProRata {
+    #[inline]
+    fn allocate(&self, amount: u64, orders: &[(u64, u64)], fills: &mut [u64]) {
+        self.allocate_with(amount, orders, fills, &mut Vec::new());
+    }
+
+    #[inline]
+    fn allocate_with(
+        &self,
+        amount: u64,
+        orders: &[(u64, u64)],
+        fills: &mut [u64],
+        scratch: &mut Vec<usize>,
+    ) {
+        fills.fill(0);
+        self.split(amount, orders, fills, scratch);
+    }
+}
+
+/// Pro-rata with priority allocations, applied in turn:
+///
+/// 1. the top order, i.e. the oldest order at the level, fills first;
+/// 2. every lead market maker (LMM) account gets its share, in basis points, of
+///    what is left, across its orders in time priority;
+/// 3. whatever is still left is split pro-rata.
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub struct Hybrid {
+    top_order: bool,
+    lmm: Vec<(u64, u64)>,
+    pro_rata: ProRata,
+}
+
+impl Hybrid {
+    #[inline]
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Fill the oldest order at the level first.
+    #[inline]
+    pub fn with_top_order(mut self) -> Self {
+        self.top_order = true;
+        self
+    }
+
+    /// Give `account_id` a `basis_points` share of what is left after the
+    /// top order.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the shares of every LMM add up to more than 100%.
+    #[inline]
+    pub fn with_lmm(mut self, account_id: u64, basis_points: u64) -> Self {
+        self.lmm.retain(|(other, _)| *other != account_id);
+        self.lmm.push((account_id, basis_points));
+        assert!(
+            self.lmm.iter().map(|(_, share)| share).sum::<u64>() <= 10_000,
+            "LMM shares should add up to 10000 basis points at most"
+        );
+        self
+    }
+
+    /// Split what is left after the priority allocations following
+    /// `pro_rata`.
+    #[inline]
+    pub fn with_pro_rata(mut self, pro_rata: ProRata) -> Self {
+        self.pro_rata = pro_rata;
+        self
+    }
+}
+
+impl MatchingStrategy for Hybrid {
+    #[inline]
+    fn allocate(&self, amount: u64, orders: &[(u64, u64)], fills: &mut [u64]) {
+        self.allocate_with(amount, orders, fills, &mut Vec::new());
+    }
+
+    fn allocate_with(
+        &self,
+        amount: u64,
+        orders: &[(u64, u64)],
+        fills: &mut [u64],
+        scratch: &mut Vec<usize>,
+    ) {
+        fills.fill(0);
+        let mut left = amount;
+        if let (true, Some((_, remaining))) = (self.top_order, orders.first()) {
+            fills[0] = left.min(*remaining);
+            left -= fills[0];
+        }
+
+        let base = left;
+        for (account_id, share) in &self.lmm {
+            let mut target =
+                (u128::from(base) * u128::from(*share) / 10_000) as u64;
+            for (index, (owner, remaining)) in orders.iter().enumerate() {
+                if owner == account_id {
+                    let extra = target.min(remaining - fills[index]);
+                    fills[index] += extra;
+                    target -= extra;
+                    left -= extra;
+                }
+            }
+        }
+
+        self.pro_rata.split(left, orders, fills, scratch);
+    }
+}
@@ -1,9 +1,39 @@
+mod auction;
+pub use auction::Equilibrium;
+
+mod circuit_breaker;
+pub use circuit_breaker::{CircuitBreaker, VolatilityBand};
+
+mod clock;
+pub use clock::{Clock, ManualClock, SystemClock};
+
+#[allow(clippy::module_inception)]
 mod engine;
 pub use engine::Engine;
 
 mod event;
 pub use event::Event;
 
+mod execution_report;
+pub use execution_report::{ExecType, ExecutionReport, ReportGenerator};
+
+mod fees;
+pub use fees::{FeeEngine, FeeSchedule, FeeTier, VOLUME_WINDOW};
+
+mod history;
+
+mod impact;
+pub use impact::{Fill, Impact};
+
+mod invariant;
+pub use invariant::InvariantError;
+
+mod ledger;
+pub use ledger::{Balance, Ledger, LedgerError};
+
+mod matching;
+pub use matching::{Fifo, Hybrid, MatchingStrategy, ProRata, Residual};
+
 mod order;
 pub use order::{AskOrder, BidOrder, Order};
 
@@ -14,10 +44,28 @@ mod order_id;
 pub use order_id::OrderId;
 
 mod order_request;
-pub use order_request::OrderRequest;
+pub(crate) use order_request::{
+    from_units, parse_id, parse_units, to_units, DECIMALS,
+};
+pub use order_request::{OrderRequest, OrderRequestError};
 
 mod order_status;
 pub use order_status::OrderStatus;
 
+mod phase;
+pub use phase::Phase;
+
+mod reject_reason;
+pub use reject_reason::RejectReason;
+
+mod risk;
+pub use risk::{RiskLimits, RiskManager};
+
+mod schedule;
+pub use schedule::Schedule;
+
 mod trade;
-pub use trade::Trade;
+pub use trade::{PublicTrade, Trade};
+
+mod trade_tape;
+pub use trade_tape::TradeTape;
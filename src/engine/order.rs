@@ -2,6 +2,8 @@ use std::borrow::Borrow;
 use std::cmp::{Ordering, Reverse};
 use std::ops::{Deref, DerefMut};
 
+use compact_str::CompactString;
+
 use super::{OrderId, OrderStatus, Trade};
 use crate::{Asset, OrderSide};
 
@@ -48,6 +50,63 @@ impl Order {
             status: OrderStatus::Open,
         }
     }
+
+    /// Exchange `amount` with `maker` at `price`, regardless of their limit
+    /// prices.
+    pub(super) fn cross(
+        &mut self,
+        maker: &mut Order,
+        amount: u64,
+        price: u64,
+    ) -> Trade {
+        self.fill(amount);
+        maker.fill(amount);
+
+        Trade {
+            id: 0,
+            sequence: 0,
+            timestamp: 0,
+            pair: CompactString::default(),
+            aggressor: self.side,
+            taker: self.id,
+            maker: maker.id,
+            taker_account: self.account_id,
+            maker_account: maker.account_id,
+            amount,
+            price,
+            taker_fee: 0,
+            maker_fee: 0,
+        }
+    }
+
+    #[inline(always)]
+    fn fill(&mut self, amount: u64) {
+        debug_assert!(
+            self.remaining() >= amount,
+            "exchanged amount should be less or equal to remaining"
+        );
+
+        self.filled += amount;
+
+        self.status = if self.filled == self.amount {
+            OrderStatus::Completed
+        } else {
+            OrderStatus::Partial
+        };
+    }
+
+    /// Replace order limit price and total amount, keeping what was already
+    /// filled.
+    #[inline]
+    pub(super) fn replace(&mut self, limit_price: u64, amount: u64) {
+        debug_assert!(
+            amount > self.filled,
+            "replaced amount should be greater than filled"
+        );
+
+        self.limit_price = limit_price;
+        self.amount = amount;
+    }
 }
 
 impl Borrow<Order> for Reverse<Order> {
@@ -94,6 +153,11 @@ impl Asset for Order {
         self.id
     }
 
+    #[inline]
+    fn account_id(&self) -> u64 {
+        self.account_id
+    }
+
     #[inline]
     fn status(&self) -> OrderStatus {
         self.status
@@ -109,6 +173,16 @@ impl Asset for Order {
         self.limit_price
     }
 
+    #[inline]
+    fn amount(&self) -> u64 {
+        self.amount
+    }
+
+    #[inline]
+    fn filled(&self) -> u64 {
+        self.filled
+    }
+
     #[inline]
     fn remaining(&self) -> u64 {
         self.amount - self.filled
@@ -121,11 +195,16 @@ impl Asset for Order {
             OrderStatus::Cancelled
                 | OrderStatus::Closed
                 | OrderStatus::Completed
+                | OrderStatus::Rejected
         )
     }
 
     #[inline]
-    fn trade(&mut self, other: &mut Self) -> Option<Self::Trade> {
+    fn trade_up_to(
+        &mut self,
+        other: &mut Self,
+        amount: u64,
+    ) -> Option<Self::Trade> {
         let (taker, maker) = (self, other);
 
         #[inline(always)]
@@ -141,35 +220,14 @@ impl Asset for Order {
             }
         }
 
-        #[inline(always)]
-        fn subtract_amount(order: &mut Order, exchanged: u64) {
-            debug_assert!(
-                order.remaining() >= exchanged,
-                "exchanged amount should be less or equal to remaining"
-            );
-
-            order.filled += exchanged;
-
-            if order.filled == order.amount {
-                order.status = OrderStatus::Completed;
-            }
-        }
-
         matches_with(taker, maker).then(|| {
-            let exchanged = taker.remaining().min(maker.remaining());
+            let exchanged =
+                amount.min(taker.remaining()).min(maker.remaining());
             let price = match taker.side() {
                 OrderSide::Ask => taker.limit_price().max(maker.limit_price()),
                 OrderSide::Bid => taker.limit_price().min(maker.limit_price()),
             };
-            subtract_amount(taker, exchanged);
-            subtract_amount(maker, exchanged);
-
-            Trade {
-                taker: taker.id,
-                maker: maker.id,
-                amount: exchanged,
-                price,
-            }
+            taker.cross(maker, exchanged, price)
         })
     }
 
@@ -199,7 +257,7 @@ impl TryFrom<Order> for AskOrder {
         order
             .side()
             .eq(&OrderSide::Ask)
-            .then(|| Self(order))
+            .then_some(Self(order))
             .ok_or(OrderError::MismatchSide)
     }
 }
@@ -212,7 +270,7 @@ impl TryFrom<Order> for BidOrder {
         order
             .side()
             .eq(&OrderSide::Bid)
-            .then(|| Self(order))
+            .then_some(Self(order))
             .ok_or(OrderError::MismatchSide)
     }
 }
@@ -274,6 +332,11 @@ impl Asset<BidOrder> for AskOrder {
         self.deref().id()
     }
 
+    #[inline]
+    fn account_id(&self) -> u64 {
+        self.deref().account_id()
+    }
+
     #[inline]
     fn side(&self) -> Self::OrderSide {
         self.deref().side()
@@ -284,6 +347,16 @@ impl Asset<BidOrder> for AskOrder {
         self.deref().limit_price()
     }
 
+    #[inline]
+    fn amount(&self) -> u64 {
+        self.deref().amount()
+    }
+
+    #[inline]
+    fn filled(&self) -> u64 {
+        self.deref().filled()
+    }
+
     #[inline]
     fn remaining(&self) -> u64 {
         self.deref().remaining()
@@ -300,8 +373,12 @@ impl Asset<BidOrder> for AskOrder {
     }
 
     #[inline]
-    fn trade(&mut self, order: &mut BidOrder) -> Option<Self::Trade> {
-        self.deref_mut().trade(order)
+    fn trade_up_to(
+        &mut self,
+        order: &mut BidOrder,
+        amount: u64,
+    ) -> Option<Self::Trade> {
+        self.deref_mut().trade_up_to(order, amount)
     }
 
     #[inline]
@@ -321,6 +398,11 @@ impl Asset<AskOrder> for BidOrder {
         self.deref().id()
     }
 
+    #[inline]
+    fn account_id(&self) -> u64 {
+        self.deref().account_id()
+    }
+
     #[inline]
     fn side(&self) -> Self::OrderSide {
         self.deref().side()
@@ -331,6 +413,16 @@ impl Asset<AskOrder> for BidOrder {
         self.deref().limit_price()
     }
 
+    #[inline]
+    fn amount(&self) -> u64 {
+        self.deref().amount()
+    }
+
+    #[inline]
+    fn filled(&self) -> u64 {
+        self.deref().filled()
+    }
+
     #[inline]
     fn remaining(&self) -> u64 {
         self.deref().remaining()
@@ -347,8 +439,12 @@ impl Asset<AskOrder> for BidOrder {
     }
 
     #[inline]
-    fn trade(&mut self, order: &mut AskOrder) -> Option<Self::Trade> {
-        self.deref_mut().trade(order)
+    fn trade_up_to(
+        &mut self,
+        order: &mut AskOrder,
+        amount: u64,
+    ) -> Option<Self::Trade> {
+        self.deref_mut().trade_up_to(order, amount)
     }
 
     #[inline]
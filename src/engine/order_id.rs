@@ -1,3 +1,5 @@
+use std::fmt;
+
 #[cfg(feature = "serde")]
 use serde::{Deserialize, Serialize};
 
@@ -12,3 +14,17 @@ impl OrderId {
         Self(order_id)
     }
 }
+
+impl fmt::Display for OrderId {
+    #[inline]
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        self.0.fmt(f)
+    }
+}
+
+impl From<OrderId> for u64 {
+    #[inline]
+    fn from(order_id: OrderId) -> Self {
+        order_id.0
+    }
+}
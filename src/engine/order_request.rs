@@ -1,4 +1,4 @@
-use super::{Order, OrderId};
+use super::{Order, OrderId, Phase};
 use crate::OrderSide;
 
 use compact_str::CompactString;
@@ -11,8 +11,13 @@ use thiserror::Error;
 pub enum OrderRequestError {
     #[error("order type mismatch")]
     MismatchType,
+    #[error("invalid identifier {0:?}")]
+    InvalidId(CompactString),
+    #[error("invalid amount or price {0}")]
+    InvalidValue(Decimal),
 }
 
+#[derive(Clone, Debug)]
 #[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
 #[cfg_attr(feature = "serde", serde(tag = "type_op", rename_all = "UPPERCASE"))]
 pub enum OrderRequest {
@@ -27,6 +32,32 @@ pub enum OrderRequest {
     Delete {
         order_id: CompactString,
     },
+    /// Replace the price and/or total amount of a resting order. The order
+    /// keeps its time priority only when the price is unchanged and the amount
+    /// is not increased.
+    Modify {
+        order_id: CompactString,
+        amount: Decimal,
+        limit_price: Decimal,
+    },
+    /// Remove every resting order of an account, optionally restricted to a
+    /// side and/or a pair.
+    #[cfg_attr(feature = "serde", serde(rename = "DELETE_ALL"))]
+    DeleteAll {
+        account_id: CompactString,
+        #[cfg_attr(feature = "serde", serde(default))]
+        side: Option<OrderSide>,
+        #[cfg_attr(feature = "serde", serde(default))]
+        pair: Option<CompactString>,
+    },
+    /// Switch the trading phase of the book, optionally only if it trades
+    /// `pair`. Meant for operators: the gateways refuse it from clients.
+    #[cfg_attr(feature = "serde", serde(rename = "SET_PHASE"))]
+    SetPhase {
+        phase: Phase,
+        #[cfg_attr(feature = "serde", serde(default))]
+        pair: Option<CompactString>,
+    },
 }
 
 impl TryFrom<OrderRequest> for Order {
@@ -38,19 +69,53 @@ impl TryFrom<OrderRequest> for Order {
             OrderRequest::Create {
                 account_id,
                 amount,
+                order_id,
                 limit_price,
                 side,
                 ..
             } => Ok(Order::new(
-                OrderId::new(account_id.parse::<u64>().unwrap()),
-                account_id.parse::<u64>().unwrap(),
+                OrderId::new(parse_id(&order_id)?),
+                parse_id(&account_id)?,
                 side,
-                limit_price.trunc().to_u64().unwrap() * 100
-                    + limit_price.fract().to_u64().unwrap(),
-                amount.trunc().to_u64().unwrap() * 100
-                    + amount.fract().to_u64().unwrap(),
+                parse_units(limit_price)?,
+                parse_units(amount)?,
             )),
-            OrderRequest::Delete { .. } => Err(OrderRequestError::MismatchType),
+            OrderRequest::Delete { .. }
+            | OrderRequest::Modify { .. }
+            | OrderRequest::DeleteAll { .. }
+            | OrderRequest::SetPhase { .. } => {
+                Err(OrderRequestError::MismatchType)
+            }
         }
     }
 }
+
+/// Decimal places kept by the engine, which stores amounts and prices as
+/// integers.
+pub(crate) const DECIMALS: u32 = 2;
+
+/// Convert a request amount or price into engine units.
+#[inline]
+pub(crate) fn to_units(value: Decimal) -> Option<u64> {
+    value
+        .checked_mul(Decimal::from(10u64.pow(DECIMALS)))?
+        .trunc()
+        .to_u64()
+}
+
+#[inline]
+pub(crate) fn parse_id(id: &CompactString) -> Result<u64, OrderRequestError> {
+    id.parse::<u64>()
+        .map_err(|_| OrderRequestError::InvalidId(id.clone()))
+}
+
+#[inline]
+pub(crate) fn parse_units(value: Decimal) -> Result<u64, OrderRequestError> {
+    to_units(value).ok_or(OrderRequestError::InvalidValue(value))
+}
+
+/// Convert engine units back into a request amount or price.
+#[inline]
+pub(crate) fn from_units(units: impl Into<Decimal>) -> Decimal {
+    (units.into() / Decimal::from(10u64.pow(DECIMALS))).normalize()
+}
@@ -11,4 +11,5 @@ pub enum OrderStatus {
     Cancelled,
     Closed,
     Completed,
+    Rejected,
 }
@@ -1,24 +1,59 @@
 use std::cmp::Reverse;
-use std::collections::{BTreeMap, VecDeque};
+use std::collections::{BTreeMap, HashMap};
 
+use std::fmt::Debug;
 use std::hash::Hash;
 use std::marker::PhantomData;
 
 use compact_str::CompactString;
-use indexmap::IndexMap;
+use indexmap::IndexSet;
+use slab::Slab;
 
-use crate::{Asset, Exchange, ExchangeEvent, ExchangeExt, OrderSide};
+use super::{Fifo, Fill, Impact, InvariantError, MatchingStrategy};
+use crate::{Asset, Exchange, ExchangeEvent, ExchangeExt, Opposite, OrderSide};
 
+/// Orders live in a slab, each price level being a doubly-linked list
+/// threaded through it, so an order leaves its level in constant time
+/// wherever it sits.
 pub struct Orderbook<Order: Asset, Event, Trade> {
-    #[allow(dead_code)]
     pair: CompactString,
-    orders: IndexMap<<Order as Asset>::OrderId, Order>,
-    ask: BTreeMap<u64, VecDeque<<Order as Asset>::OrderId>>,
-    bid: BTreeMap<Reverse<u64>, VecDeque<<Order as Asset>::OrderId>>,
+    nodes: Slab<Node<Order>>,
+    index: HashMap<<Order as Asset>::OrderId, usize>,
+    accounts: HashMap<u64, IndexSet<<Order as Asset>::OrderId>>,
+    levels: Slab<Level>,
+    ask: BTreeMap<u64, usize>,
+    bid: BTreeMap<Reverse<u64>, usize>,
+    strategy: Box<dyn MatchingStrategy + Send>,
+    scratch: Scratch,
     _event: PhantomData<Event>,
     _trade: PhantomData<Trade>,
 }
 
+/// Resting order, linked to its neighbours within its level.
+struct Node<Order> {
+    order: Order,
+    level: usize,
+    prev: Option<usize>,
+    next: Option<usize>,
+}
+
+/// Oldest and newest orders resting at a price.
+struct Level {
+    head: usize,
+    tail: usize,
+    len: usize,
+}
+
+/// Buffers kept from one matching to the next, so matching allocates
+/// nothing once they have grown to the largest level seen.
+#[derive(Default)]
+struct Scratch {
+    slots: Vec<usize>,
+    resting: Vec<(u64, u64)>,
+    fills: Vec<u64>,
+    ranks: Vec<usize>,
+}
+
 impl<Order, Event, Trade> Orderbook<Order, Event, Trade>
 where
     Order: Asset,
@@ -27,104 +62,603 @@ where
     pub fn new(pair: &str) -> Self {
         Self {
             pair: CompactString::new_inline(pair),
-            orders: IndexMap::new(),
+            nodes: Slab::new(),
+            index: HashMap::new(),
+            accounts: HashMap::new(),
+            levels: Slab::new(),
             ask: BTreeMap::new(),
             bid: BTreeMap::new(),
+            strategy: Box::new(Fifo),
+            scratch: Scratch::default(),
             _event: PhantomData,
             _trade: PhantomData,
         }
     }
+
+    /// Split incoming orders among the orders resting at a level following
+    /// `strategy` instead of time priority.
+    #[inline]
+    pub fn with_matching(
+        mut self,
+        strategy: impl MatchingStrategy + Send + 'static,
+    ) -> Self {
+        self.set_matching(strategy);
+        self
+    }
+
+    #[inline]
+    pub fn set_matching(
+        &mut self,
+        strategy: impl MatchingStrategy + Send + 'static,
+    ) {
+        self.strategy = Box::new(strategy);
+    }
+
+    #[inline]
+    pub fn pair(&self) -> &str {
+        &self.pair
+    }
 }
 
-impl<Order, Event, Trade> Exchange for Orderbook<Order, Event, Trade>
+impl<Order, Event, Trade> Orderbook<Order, Event, Trade>
 where
     Order: Asset<OrderSide = OrderSide>,
-    Order: Asset<Trade = Trade>,
     <Order as Asset>::OrderId: Hash,
-    Event: ExchangeEvent<Order = Order>,
 {
-    type Order = Order;
-    type Event = Event;
+    /// Return the resting order identified by `order_id`, if any.
+    #[inline]
+    pub fn get(&self, order_id: &<Order as Asset>::OrderId) -> Option<&Order> {
+        let slot = self.index.get(order_id)?;
+        Some(&self.nodes[*slot].order)
+    }
 
     #[inline]
-    fn insert(&mut self, order: Self::Order) {
-        let level = match order.side() {
-            OrderSide::Ask => self
-                .ask
-                .entry(order.limit_price())
-                .or_insert_with(|| VecDeque::with_capacity(8)),
-            OrderSide::Bid => self
-                .bid
-                .entry(Reverse(order.limit_price()))
-                .or_insert_with(|| VecDeque::with_capacity(8)),
+    pub(super) fn get_mut(
+        &mut self,
+        order_id: &<Order as Asset>::OrderId,
+    ) -> Option<&mut Order> {
+        let slot = self.index.get(order_id)?;
+        Some(&mut self.nodes[*slot].order)
+    }
+
+    /// Return how many orders are ahead of `order_id` within its price level.
+    pub fn queue_position(
+        &self,
+        order_id: &<Order as Asset>::OrderId,
+    ) -> Option<usize> {
+        let mut slot = *self.index.get(order_id)?;
+        let mut ahead = 0;
+        while let Some(prev) = self.nodes[slot].prev {
+            slot = prev;
+            ahead += 1;
+        }
+        Some(ahead)
+    }
+
+    /// Return up to `levels` price levels of `side`, from the best one, along
+    /// with the total amount resting at each, saturating at `u64::MAX`.
+    pub fn depth(&self, side: OrderSide, levels: usize) -> Vec<(u64, u64)> {
+        self.quantities(side)
+            .take(levels)
+            .map(|(price, quantity)| {
+                (price, u64::try_from(quantity).unwrap_or(u64::MAX))
+            })
+            .collect()
+    }
+
+    /// Return the total amount resting at `price` on `side`, saturating at
+    /// `u64::MAX`.
+    pub fn depth_at(&self, side: OrderSide, price: u64) -> u64 {
+        let level = match side {
+            OrderSide::Ask => self.ask.get(&price),
+            OrderSide::Bid => self.bid.get(&Reverse(price)),
         };
+        let quantity = level.map_or(0, |level| self.quantity(*level));
+        u64::try_from(quantity).unwrap_or(u64::MAX)
+    }
 
-        level.push_back(order.id());
+    /// Iterate over every resting order, in no particular order.
+    #[inline]
+    pub fn iter(&self) -> impl Iterator<Item = &Order> + '_ {
+        self.nodes.iter().map(|(_, node)| &node.order)
+    }
 
-        self.orders.insert(order.id(), order);
+    /// Iterate over the levels of `side` from the best one, along with the
+    /// exact total amount resting at each.
+    pub(super) fn quantities(
+        &self,
+        side: OrderSide,
+    ) -> impl Iterator<Item = (u64, u128)> + '_ {
+        self.levels(side)
+            .map(|(price, level)| (price, self.quantity(level)))
     }
 
+    /// Total amount resting at `level`.
     #[inline]
-    fn remove(
+    fn quantity(&self, level: usize) -> u128 {
+        self.orders(level)
+            .map(|(_, order)| u128::from(order.remaining()))
+            .sum()
+    }
+
+    /// Put `order` back at the front of its price level, as if it had never
+    /// left it.
+    pub(super) fn restore(&mut self, order: Order) {
+        let level = self.level(order.side(), order.limit_price());
+        let head = self.levels.get(level).map(|level| level.head);
+        let slot = self.nodes.insert(Node {
+            order,
+            level,
+            prev: None,
+            next: head,
+        });
+        match head {
+            Some(head) => {
+                self.nodes[head].prev = Some(slot);
+                self.levels[level].head = slot;
+                self.levels[level].len += 1;
+            }
+            None => {
+                self.levels.insert(Level {
+                    head: slot,
+                    tail: slot,
+                    len: 1,
+                });
+            }
+        }
+        self.index_slot(slot);
+    }
+
+    /// Iterate over every resting order owned by `account_id`, in no
+    /// particular order: removing an order may move the last one in its place.
+    pub fn orders_by_account(
+        &self,
+        account_id: u64,
+    ) -> impl Iterator<Item = &Order> + '_ {
+        self.accounts
+            .get(&account_id)
+            .into_iter()
+            .flatten()
+            .filter_map(|order_id| self.get(order_id))
+    }
+
+    /// Remove every resting order owned by `account_id`. When `side` is
+    /// given, only orders on that side are removed.
+    pub fn remove_by_account(
         &mut self,
-        _order_id: &<Self::Order as Asset>::OrderId,
-    ) -> Option<Self::Order> {
-        // TODO: implement a way to remove orders. It should not let dangling
-        // levels (level with no orders).
-        todo!()
+        account_id: u64,
+        side: Option<OrderSide>,
+    ) -> Vec<Order> {
+        let order_ids = match self.accounts.get(&account_id) {
+            Some(order_ids) => order_ids
+                .iter()
+                .filter(|order_id| {
+                    side.is_none_or(|side| {
+                        self.get(order_id)
+                            .is_some_and(|order| order.side() == side)
+                    })
+                })
+                .copied()
+                .collect::<Vec<_>>(),
+            None => return Vec::new(),
+        };
+
+        order_ids
+            .iter()
+            .filter_map(|order_id| self.remove_order(order_id))
+            .collect()
     }
 
     #[inline]
-    fn peek(&self, side: &OrderSide) -> Option<&Self::Order> {
-        match side {
-            OrderSide::Ask => {
-                self.ask.first_key_value().map(|(_, level)| level)?
+    fn remove_order(
+        &mut self,
+        order_id: &<Order as Asset>::OrderId,
+    ) -> Option<Order> {
+        let slot = *self.index.get(order_id)?;
+        Some(self.take(slot))
+    }
+
+    /// Simulate matching `amount` on `side` up to `limit_price` without
+    /// touching the book, following the matching strategy. Use `u64::MAX`
+    /// or zero as limit price to simulate a market bid or ask.
+    ///
+    /// The book knows nothing of trading phases or circuit breakers, which
+    /// [`Engine::simulate`](super::Engine::simulate) accounts for.
+    pub fn simulate(
+        &self,
+        side: OrderSide,
+        limit_price: u64,
+        amount: u64,
+    ) -> Impact<<Order as Asset>::OrderId> {
+        let mut impact = Impact::new(amount);
+        let mut scratch = Scratch::default();
+        for (price, level) in self.levels(side.opposite()) {
+            if impact.leftover == 0 || !crosses(side, limit_price, price) {
+                break;
+            }
+
+            self.allocate(level, impact.leftover, &mut scratch);
+            let filled = impact.fills.len();
+            for (slot, fill) in scratch.slots.iter().zip(&scratch.fills) {
+                if *fill > 0 {
+                    let order = &self.nodes[*slot].order;
+                    impact.push(Fill::new(
+                        order.id(),
+                        order.account_id(),
+                        *fill,
+                        price,
+                    ));
+                }
             }
-            OrderSide::Bid => {
-                self.bid.first_key_value().map(|(_, level)| level)?
+            if impact.fills.len() == filled {
+                break;
             }
+            impact.levels += 1;
         }
-        .front()
-        .and_then(|order_id| self.orders.get(order_id))
+
+        impact
     }
 
+    /// Check that the book is not crossed, that every level holds orders at
+    /// its own price and side, linked from the oldest to the newest and
+    /// adding up to its depth, that no order filled more than its amount,
+    /// and that the indexes hold exactly the resting orders. It walks the whole
+    /// book, so it is meant for tests and debugging.
+    ///
+    /// Orders resting for a call auction may cross, which is reported as
+    /// [`InvariantError::Crossed`] all the same.
+    pub fn validate(
+        &self,
+    ) -> Result<(), InvariantError<<Order as Asset>::OrderId>>
+    where
+        <Order as Asset>::OrderId: Debug,
+    {
+        let best = |side| self.levels(side).next().map(|(price, _)| price);
+        if let (Some(ask), Some(bid)) =
+            (best(OrderSide::Ask), best(OrderSide::Bid))
+        {
+            if ask <= bid {
+                return Err(InvariantError::Crossed { ask, bid });
+            }
+        }
+
+        let mut reached = vec![false; self.nodes.capacity()];
+        for side in [OrderSide::Ask, OrderSide::Bid] {
+            for (price, slot) in self.levels(side) {
+                let level = match self.levels.get(slot) {
+                    Some(level) if level.len > 0 => level,
+                    _ => return Err(InvariantError::EmptyLevel(price)),
+                };
+
+                let (mut prev, mut len, mut total) = (None, 0, 0);
+                for (slot, order) in self.orders(slot).take(self.nodes.len()) {
+                    let node = &self.nodes[slot];
+                    if node.prev != prev || reached[slot] {
+                        return Err(InvariantError::BrokenLevel(price));
+                    }
+                    if order.side() != side || order.limit_price() != price {
+                        return Err(InvariantError::MisplacedOrder(order.id()));
+                    }
+                    if order.filled() > order.amount() {
+                        return Err(InvariantError::Overfilled(order.id()));
+                    }
+                    if order.is_closed() || order.remaining() == 0 {
+                        return Err(InvariantError::ClosedOrder(order.id()));
+                    }
+                    let indexed = self.index.get(&order.id()) == Some(&slot)
+                        && self.accounts.get(&order.account_id()).is_some_and(
+                            |order_ids| order_ids.contains(&order.id()),
+                        );
+                    if !indexed {
+                        return Err(InvariantError::UnindexedOrder(order.id()));
+                    }
+                    reached[slot] = true;
+                    (prev, len) = (Some(slot), len + 1);
+                    total += u128::from(order.amount() - order.filled());
+                }
+                if prev != Some(level.tail) || len != level.len {
+                    return Err(InvariantError::BrokenLevel(price));
+                }
+                // Depth is only read once no order is known to overfill.
+                if total != self.quantity(slot) {
+                    return Err(InvariantError::LevelQuantity(price));
+                }
+            }
+        }
+
+        // Whatever was not reached from a level does not rest.
+        if let Some((_, node)) =
+            self.nodes.iter().find(|(slot, _)| !reached[*slot])
+        {
+            return Err(InvariantError::DanglingOrder(node.order.id()));
+        }
+        let reachable = |order_id| {
+            self.index
+                .get(order_id)
+                .is_some_and(|slot| self.nodes[*slot].order.id() == *order_id)
+        };
+        let dangling = self
+            .index
+            .keys()
+            .chain(self.accounts.values().flatten())
+            .find(|order_id| !reachable(order_id));
+        match dangling {
+            Some(order_id) => Err(InvariantError::DanglingOrder(*order_id)),
+            None => Ok(()),
+        }
+    }
+
+    /// Iterate over the levels of `side` from the best one, as their price
+    /// and slot.
     #[inline]
-    fn peek_mut(&mut self, side: &OrderSide) -> Option<&mut Self::Order> {
+    fn levels(
+        &self,
+        side: OrderSide,
+    ) -> Box<dyn Iterator<Item = (u64, usize)> + '_> {
         match side {
             OrderSide::Ask => {
-                self.ask.first_key_value().map(|(_, level)| level)?
-            }
-            OrderSide::Bid => {
-                self.bid.first_key_value().map(|(_, level)| level)?
+                Box::new(self.ask.iter().map(|(price, level)| (*price, *level)))
             }
+            OrderSide::Bid => Box::new(
+                self.bid
+                    .iter()
+                    .map(|(Reverse(price), level)| (*price, *level)),
+            ),
         }
-        .front()
-        .and_then(|order_id| self.orders.get_mut(order_id))
     }
 
+    /// Return the slot of the best level of `side`, if any.
     #[inline]
-    fn pop(&mut self, side: &OrderSide) -> Option<Self::Order> {
+    fn best(&self, side: OrderSide) -> Option<usize> {
         match side {
-            OrderSide::Ask => {
-                let mut level = self.ask.first_entry()?;
-                // It prevents dagling levels (level with no orders).
-                if level.get().len() == 1 {
-                    level.remove().pop_front()
-                } else {
-                    level.get_mut().pop_front()
+            OrderSide::Ask => self.ask.values().next(),
+            OrderSide::Bid => self.bid.values().next(),
+        }
+        .copied()
+    }
+
+    /// Return the slot of the level of `side` at `price`, or the one a new
+    /// level there is about to take.
+    #[inline]
+    fn level(&mut self, side: OrderSide, price: u64) -> usize {
+        let vacant = self.levels.vacant_key();
+        *match side {
+            OrderSide::Ask => self.ask.entry(price).or_insert(vacant),
+            OrderSide::Bid => self.bid.entry(Reverse(price)).or_insert(vacant),
+        }
+    }
+
+    /// Iterate over the orders at `level`, oldest first, along with their
+    /// slots.
+    #[inline]
+    fn orders(&self, level: usize) -> impl Iterator<Item = (usize, &Order)> {
+        let mut next = Some(self.levels[level].head);
+        std::iter::from_fn(move || {
+            let slot = next?;
+            let node = &self.nodes[slot];
+            next = node.next;
+            Some((slot, &node.order))
+        })
+    }
+
+    /// Split `amount` among the orders at `level` following the matching
+    /// strategy, into `scratch`. Only the oldest orders holding `amount` are
+    /// looked at when the strategy does not need the whole level.
+    fn allocate(&self, level: usize, amount: u64, scratch: &mut Scratch) {
+        let whole_level = self.strategy.whole_level();
+        scratch.slots.clear();
+        scratch.resting.clear();
+        let mut resting = 0;
+        for (slot, order) in self.orders(level) {
+            if !whole_level && resting >= amount {
+                break;
+            }
+            resting = resting.saturating_add(order.remaining());
+            scratch.slots.push(slot);
+            scratch
+                .resting
+                .push((order.account_id(), order.remaining()));
+        }
+        scratch.fills.clear();
+        scratch.fills.resize(scratch.resting.len(), 0);
+        self.strategy.allocate_with(
+            amount,
+            &scratch.resting,
+            &mut scratch.fills,
+            &mut scratch.ranks,
+        );
+    }
+
+    /// Index the order at `slot` by its id and by its account.
+    #[inline]
+    fn index_slot(&mut self, slot: usize) {
+        let order = &self.nodes[slot].order;
+        self.accounts
+            .entry(order.account_id())
+            .or_default()
+            .insert(order.id());
+        self.index.insert(order.id(), slot);
+    }
+
+    /// Unlink the order at `slot` from its level and from the indexes.
+    fn take(&mut self, slot: usize) -> Order {
+        let Node {
+            order,
+            level,
+            prev,
+            next,
+        } = self.nodes.remove(slot);
+        match prev {
+            Some(prev) => self.nodes[prev].next = next,
+            None => self.levels[level].head = next.unwrap_or(slot),
+        }
+        match next {
+            Some(next) => self.nodes[next].prev = prev,
+            None => self.levels[level].tail = prev.unwrap_or(slot),
+        }
+
+        self.levels[level].len -= 1;
+        // It prevents dangling levels (level with no orders).
+        if self.levels[level].len == 0 {
+            self.levels.remove(level);
+            match order.side() {
+                OrderSide::Ask => self.ask.remove(&order.limit_price()),
+                OrderSide::Bid => {
+                    self.bid.remove(&Reverse(order.limit_price()))
                 }
+            };
+        }
+
+        self.index.remove(&order.id());
+        if let Some(order_ids) = self.accounts.get_mut(&order.account_id()) {
+            order_ids.swap_remove(&order.id());
+            if order_ids.is_empty() {
+                self.accounts.remove(&order.account_id());
             }
-            OrderSide::Bid => {
-                let mut level = self.bid.first_entry()?;
-                // It prevents dagling levels (level with no orders).
-                if level.get().len() == 1 {
-                    level.remove().pop_front()
-                } else {
-                    level.get_mut().pop_front()
+        }
+        order
+    }
+}
+
+/// Return whether an order on `side` at `limit_price` trades with a level at
+/// `price`.
+#[inline]
+fn crosses(side: OrderSide, limit_price: u64, price: u64) -> bool {
+    match side {
+        OrderSide::Ask => limit_price <= price,
+        OrderSide::Bid => limit_price >= price,
+    }
+}
+
+impl<Order, Event, Trade> Exchange for Orderbook<Order, Event, Trade>
+where
+    Order: Asset<OrderSide = OrderSide>,
+    Order: Asset<Trade = Trade>,
+    <Order as Asset>::OrderId: Hash,
+    Event: ExchangeEvent<Order = Order>,
+{
+    type Order = Order;
+    type Event = Event;
+
+    #[inline]
+    fn insert(&mut self, order: Self::Order) {
+        let level = self.level(order.side(), order.limit_price());
+        let tail = self.levels.get(level).map(|level| level.tail);
+        let slot = self.nodes.insert(Node {
+            order,
+            level,
+            prev: tail,
+            next: None,
+        });
+        match tail {
+            Some(tail) => {
+                self.nodes[tail].next = Some(slot);
+                self.levels[level].tail = slot;
+                self.levels[level].len += 1;
+            }
+            None => {
+                self.levels.insert(Level {
+                    head: slot,
+                    tail: slot,
+                    len: 1,
+                });
+            }
+        }
+        self.index_slot(slot);
+    }
+
+    #[inline]
+    fn remove(
+        &mut self,
+        order_id: &<Self::Order as Asset>::OrderId,
+    ) -> Option<Self::Order> {
+        self.remove_order(order_id)
+    }
+
+    /// Match `order` level by level, splitting it among the orders resting
+    /// at each one following the matching strategy. `guard` is asked once
+    /// per level, with its oldest order.
+    fn matching_into<F>(
+        &mut self,
+        order: Self::Order,
+        mut guard: F,
+        events: &mut Vec<Self::Event>,
+    ) where
+        F: FnMut(&Self::Order, &Self::Order) -> bool,
+    {
+        let mut incoming_order = order;
+        let side = incoming_order.side().opposite();
+        let mut scratch = std::mem::take(&mut self.scratch);
+        while !incoming_order.is_closed() {
+            let level = match self.best(side) {
+                Some(level) => level,
+                None => break,
+            };
+            let top_order = &self.nodes[self.levels[level].head].order;
+            if !guard(&incoming_order, top_order) {
+                break;
+            }
+            if !crosses(
+                incoming_order.side(),
+                incoming_order.limit_price(),
+                top_order.limit_price(),
+            ) {
+                break;
+            }
+
+            self.allocate(level, incoming_order.remaining(), &mut scratch);
+            let traded = events.len();
+            for (slot, fill) in scratch.slots.iter().zip(&scratch.fills) {
+                if *fill == 0 {
+                    continue;
+                }
+                let top_order = &mut self.nodes[*slot].order;
+                let remaining =
+                    (incoming_order.remaining(), top_order.remaining());
+                if let Some(trade) =
+                    incoming_order.trade_up_to(top_order, *fill)
+                {
+                    events.push(Self::Event::traded(trade));
+                }
+                // Both sides of a trade exchange the same amount.
+                debug_assert_eq!(
+                    remaining.0 - incoming_order.remaining(),
+                    remaining.1 - top_order.remaining(),
+                );
+                if top_order.is_closed() {
+                    self.take(*slot);
                 }
             }
+            // A strategy allocating nothing would otherwise loop forever.
+            if events.len() == traded {
+                break;
+            }
+        }
+        self.scratch = scratch;
+        // We need to check if incoming order is fullfilled. If not, we'll
+        // insert it into orderbook.
+        if !incoming_order.is_closed() {
+            events.push(Self::Event::added(incoming_order.id()));
+            self.insert(incoming_order);
         }
-        .and_then(|order_id| self.orders.remove(&order_id))
+    }
+
+    #[inline]
+    fn peek(&self, side: &OrderSide) -> Option<&Self::Order> {
+        let level = self.best(*side)?;
+        Some(&self.nodes[self.levels[level].head].order)
+    }
+
+    #[inline]
+    fn peek_mut(&mut self, side: &OrderSide) -> Option<&mut Self::Order> {
+        let level = self.best(*side)?;
+        Some(&mut self.nodes[self.levels[level].head].order)
+    }
+
+    #[inline]
+    fn pop(&mut self, side: &OrderSide) -> Option<Self::Order> {
+        let level = self.best(*side)?;
+        Some(self.take(self.levels[level].head))
     }
 }
 
@@ -143,9 +677,11 @@ where
     }
 
     fn len(&self) -> (usize, usize) {
-        (
-            self.ask.iter().fold(0, |acc, (_, level)| acc + level.len()),
-            self.bid.iter().fold(0, |acc, (_, level)| acc + level.len()),
-        )
+        let len = |side| {
+            self.levels(side)
+                .map(|(_, level)| self.levels[level].len)
+                .sum()
+        };
+        (len(OrderSide::Ask), len(OrderSide::Bid))
     }
 }
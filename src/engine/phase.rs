@@ -0,0 +1,43 @@
+#[cfg(feature = "serde")]
+use serde::{Deserialize, Serialize};
+
+/// Trading phase of an orderbook, which decides the requests it accepts.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+#[cfg_attr(feature = "serde", serde(rename_all = "SCREAMING_SNAKE_CASE"))]
+pub enum Phase {
+    /// Nothing is accepted, not even cancels.
+    Halted,
+    /// Only cancels are accepted.
+    CancelOnly,
+    /// Orders are accepted and rest without matching.
+    PreOpen,
+    /// Orders are matched as they arrive.
+    #[default]
+    Continuous,
+    /// Orders rest without matching while the indicative price and volume
+    /// are published, until the book is uncrossed.
+    Auction,
+    /// Only cancels are accepted until the next session.
+    Closed,
+}
+
+impl Phase {
+    /// Return whether new orders and modifications are accepted.
+    #[inline]
+    pub fn accepts_orders(&self) -> bool {
+        matches!(self, Self::PreOpen | Self::Continuous | Self::Auction)
+    }
+
+    /// Return whether resting orders may be cancelled.
+    #[inline]
+    pub fn accepts_cancels(&self) -> bool {
+        !matches!(self, Self::Halted)
+    }
+
+    /// Return whether orders rest without matching, so the book may cross.
+    #[inline]
+    pub fn is_call(&self) -> bool {
+        matches!(self, Self::PreOpen | Self::Auction)
+    }
+}
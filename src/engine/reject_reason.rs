@@ -0,0 +1,29 @@
+#[cfg(feature = "serde")]
+use serde::{Deserialize, Serialize};
+use thiserror::Error;
+
+#[derive(Clone, Copy, Debug, Error, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+#[cfg_attr(feature = "serde", serde(rename_all = "SCREAMING_SNAKE_CASE"))]
+pub enum RejectReason {
+    #[error("unknown order")]
+    UnknownOrder,
+    #[error("duplicate order id")]
+    DuplicateOrder,
+    #[error("invalid amount")]
+    InvalidAmount,
+    #[error("order amount exceeds the limit")]
+    AmountLimit,
+    #[error("order notional exceeds the limit")]
+    NotionalLimit,
+    #[error("limit price is outside of the price band")]
+    PriceBand,
+    #[error("too many open orders")]
+    OpenOrdersLimit,
+    #[error("position would exceed the limit")]
+    PositionLimit,
+    #[error("insufficient available balance")]
+    InsufficientBalance,
+    #[error("not accepted in the current trading phase")]
+    TradingPhase,
+}
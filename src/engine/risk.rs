@@ -0,0 +1,253 @@
+use std::collections::HashMap;
+
+use super::{Event, Order, Orderbook, RejectReason, Trade, DECIMALS};
+use crate::{Asset, ExchangeExt, OrderSide};
+
+#[cfg(feature = "serde")]
+use serde::{Deserialize, Serialize};
+
+/// Pre-trade limits. Unset limits are not checked.
+///
+/// Amounts and prices are expressed in the same units as [`Order`], and the
+/// notional in units of the quote asset.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+#[cfg_attr(feature = "serde", serde(default))]
+pub struct RiskLimits {
+    max_amount: Option<u64>,
+    max_notional: Option<u64>,
+    price_band: Option<u64>,
+    max_open_orders: Option<usize>,
+    max_position: Option<u64>,
+}
+
+impl RiskLimits {
+    #[inline]
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Limit the amount of a single order.
+    #[inline]
+    pub fn with_max_amount(mut self, max_amount: u64) -> Self {
+        self.max_amount = Some(max_amount);
+        self
+    }
+
+    /// Limit the amount times the limit price of a single order.
+    #[inline]
+    pub fn with_max_notional(mut self, max_notional: u64) -> Self {
+        self.max_notional = Some(max_notional);
+        self
+    }
+
+    /// Reject limit prices further than `basis_points` away from the last
+    /// trade price or, before any trade, from the mid price.
+    #[inline]
+    pub fn with_price_band(mut self, basis_points: u64) -> Self {
+        self.price_band = Some(basis_points);
+        self
+    }
+
+    /// Limit how many orders an account may have resting at once.
+    #[inline]
+    pub fn with_max_open_orders(mut self, max_open_orders: usize) -> Self {
+        self.max_open_orders = Some(max_open_orders);
+        self
+    }
+
+    /// Limit the net position an account could reach, long or short, if all
+    /// of its orders on one side were filled.
+    #[inline]
+    pub fn with_max_position(mut self, max_position: u64) -> Self {
+        self.max_position = Some(max_position);
+        self
+    }
+
+    #[inline]
+    pub fn max_amount(&self) -> Option<u64> {
+        self.max_amount
+    }
+
+    #[inline]
+    pub fn max_notional(&self) -> Option<u64> {
+        self.max_notional
+    }
+
+    #[inline]
+    pub fn price_band(&self) -> Option<u64> {
+        self.price_band
+    }
+
+    #[inline]
+    pub fn max_open_orders(&self) -> Option<usize> {
+        self.max_open_orders
+    }
+
+    #[inline]
+    pub fn max_position(&self) -> Option<u64> {
+        self.max_position
+    }
+
+    /// Fill the limits unset in `self` from `other`.
+    #[inline]
+    fn or(self, other: Self) -> Self {
+        Self {
+            max_amount: self.max_amount.or(other.max_amount),
+            max_notional: self.max_notional.or(other.max_notional),
+            price_band: self.price_band.or(other.price_band),
+            max_open_orders: self.max_open_orders.or(other.max_open_orders),
+            max_position: self.max_position.or(other.max_position),
+        }
+    }
+}
+
+/// Checks orders against [`RiskLimits`] before they reach the orderbook.
+///
+/// Limits are set for the whole pair and may be overridden per account. The
+/// manager also tracks the last trade price and the net position of every
+/// account from the trades it is fed.
+#[derive(Debug, Default)]
+pub struct RiskManager {
+    limits: RiskLimits,
+    accounts: HashMap<u64, RiskLimits>,
+    positions: HashMap<u64, i128>,
+    last_price: Option<u64>,
+}
+
+impl RiskManager {
+    #[inline]
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Set the limits of the whole pair.
+    #[inline]
+    pub fn set_limits(&mut self, limits: RiskLimits) {
+        self.limits = limits;
+    }
+
+    /// Override the pair limits for `account_id`. Limits unset in `limits`
+    /// keep falling back to the pair ones.
+    #[inline]
+    pub fn set_account_limits(&mut self, account_id: u64, limits: RiskLimits) {
+        self.accounts.insert(account_id, limits);
+    }
+
+    /// Remove the limits overridden for `account_id`.
+    #[inline]
+    pub fn clear_account_limits(&mut self, account_id: u64) {
+        self.accounts.remove(&account_id);
+    }
+
+    /// Return the limits in effect for `account_id`.
+    #[inline]
+    pub fn limits(&self, account_id: u64) -> RiskLimits {
+        match self.accounts.get(&account_id) {
+            Some(limits) => limits.or(self.limits),
+            None => self.limits,
+        }
+    }
+
+    /// Return the net position of `account_id`, positive when long.
+    #[inline]
+    pub fn position(&self, account_id: u64) -> i128 {
+        self.positions.get(&account_id).copied().unwrap_or_default()
+    }
+
+    #[inline]
+    pub fn last_price(&self) -> Option<u64> {
+        self.last_price
+    }
+
+    /// Check `order` against the limits of its account. An order already
+    /// resting in `orderbook` under the same id is considered replaced.
+    pub fn check(
+        &self,
+        order: &Order,
+        orderbook: &Orderbook<Order, Event<Order>, Trade>,
+    ) -> Result<(), RejectReason> {
+        let limits = self.limits(order.account_id());
+        let replaced = orderbook.get(&order.id());
+
+        if limits
+            .max_amount
+            .is_some_and(|max_amount| order.remaining() > max_amount)
+        {
+            return Err(RejectReason::AmountLimit);
+        }
+
+        // Both amount and price carry the implied decimals.
+        let notional = u128::from(order.remaining())
+            * u128::from(order.limit_price())
+            / 10u128.pow(DECIMALS);
+        if limits
+            .max_notional
+            .is_some_and(|max_notional| notional > u128::from(max_notional))
+        {
+            return Err(RejectReason::NotionalLimit);
+        }
+
+        if let Some(price_band) = limits.price_band {
+            let reference = self.last_price.or_else(|| {
+                orderbook.spread().map(|(ask, bid)| ask.midpoint(bid))
+            });
+            if let Some(reference) = reference {
+                let distance = order.limit_price().abs_diff(reference);
+                if u128::from(distance) * 10_000
+                    > u128::from(price_band) * u128::from(reference)
+                {
+                    return Err(RejectReason::PriceBand);
+                }
+            }
+        }
+
+        let open_orders = || {
+            orderbook
+                .orders_by_account(order.account_id())
+                .filter(|open| open.id() != order.id())
+        };
+
+        if limits.max_open_orders.is_some_and(|max_open_orders| {
+            replaced.is_none() && open_orders().count() >= max_open_orders
+        }) {
+            return Err(RejectReason::OpenOrdersLimit);
+        }
+
+        if let Some(max_position) = limits.max_position {
+            let pending = open_orders()
+                .filter(|open| open.side() == order.side())
+                .map(|open| u128::from(open.remaining()))
+                .sum::<u128>()
+                + u128::from(order.remaining());
+            let position = self.position(order.account_id());
+            let exposure = match order.side() {
+                OrderSide::Bid => position + pending as i128,
+                OrderSide::Ask => pending as i128 - position,
+            };
+            if exposure > i128::from(max_position) {
+                return Err(RejectReason::PositionLimit);
+            }
+        }
+
+        Ok(())
+    }
+
+    /// Update the last price and positions from the trades among `events`.
+    pub(super) fn update(&mut self, events: &[Event<Order>]) {
+        for event in events {
+            if let Event::Traded(trade) = event {
+                let amount = i128::from(trade.amount);
+                let (taker, maker) = match trade.aggressor {
+                    OrderSide::Bid => (amount, -amount),
+                    OrderSide::Ask => (-amount, amount),
+                };
+                *self.positions.entry(trade.taker_account).or_default() +=
+                    taker;
+                *self.positions.entry(trade.maker_account).or_default() +=
+                    maker;
+                self.last_price = Some(trade.price);
+            }
+        }
+    }
+}
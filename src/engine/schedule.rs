@@ -0,0 +1,73 @@
+use super::Phase;
+
+/// Milliseconds in a day.
+const DAY: u64 = 24 * 60 * 60 * 1000;
+
+/// Daily timetable of trading phases.
+///
+/// Each entry switches the book into a phase at a time of day, in
+/// milliseconds since midnight UTC, and the timetable repeats every day.
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct Schedule {
+    entries: Vec<(u64, Phase)>,
+}
+
+impl Schedule {
+    #[inline]
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Switch to `phase` every day at `time_of_day`, replacing any entry at
+    /// the same time.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `time_of_day` is not within a day.
+    pub fn with_phase(mut self, time_of_day: u64, phase: Phase) -> Self {
+        assert!(time_of_day < DAY, "time of day should be within a day");
+
+        match self
+            .entries
+            .binary_search_by_key(&time_of_day, |(at, _)| *at)
+        {
+            Ok(index) => self.entries[index].1 = phase,
+            Err(index) => self.entries.insert(index, (time_of_day, phase)),
+        }
+        self
+    }
+
+    /// Return the entries, sorted by time of day.
+    #[inline]
+    pub fn entries(&self) -> &[(u64, Phase)] {
+        &self.entries
+    }
+
+    /// Return the last switch due at or before `now`, as its timestamp and
+    /// phase.
+    pub fn current(&self, now: u64) -> Option<(u64, Phase)> {
+        let midnight = now - now % DAY;
+        let time_of_day = now % DAY;
+        match self.entries.iter().rev().find(|(at, _)| *at <= time_of_day) {
+            Some(&(at, phase)) => Some((midnight + at, phase)),
+            // Still under the last entry of the day before.
+            None => self.entries.last().and_then(|&(at, phase)| {
+                Some((midnight.checked_sub(DAY)? + at, phase))
+            }),
+        }
+    }
+
+    /// Return the first switch due strictly after `after`, as its timestamp
+    /// and phase.
+    pub fn next(&self, after: u64) -> Option<(u64, Phase)> {
+        let midnight = after - after % DAY;
+        let time_of_day = after % DAY;
+        match self.entries.iter().find(|(at, _)| *at > time_of_day) {
+            Some(&(at, phase)) => Some((midnight + at, phase)),
+            None => self
+                .entries
+                .first()
+                .map(|&(at, phase)| (midnight + DAY + at, phase)),
+        }
+    }
+}
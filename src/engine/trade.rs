@@ -1,6 +1,7 @@
-use crate::Asset;
+use compact_str::CompactString;
 
 use super::OrderId;
+use crate::{Asset, OrderSide};
 
 #[cfg(feature = "serde")]
 use serde::{Deserialize, Serialize};
@@ -12,14 +13,133 @@ pub enum TradeError {
     MismatchSides,
 }
 
-#[derive(Debug)]
-#[cfg_attr(test, derive(Copy, Clone))]
+#[derive(Clone, Debug)]
 #[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
 pub struct Trade {
+    #[cfg_attr(feature = "serde", serde(default))]
+    pub(super) id: u64,
+    #[cfg_attr(feature = "serde", serde(default))]
+    pub(super) sequence: u64,
+    #[cfg_attr(feature = "serde", serde(default))]
+    pub(super) timestamp: u64,
+    #[cfg_attr(feature = "serde", serde(default))]
+    pub(super) pair: CompactString,
+    pub(super) aggressor: OrderSide,
     pub(super) taker: OrderId,
     pub(super) maker: OrderId,
+    pub(super) taker_account: u64,
+    pub(super) maker_account: u64,
     pub(super) amount: u64,
     pub(super) price: u64,
+    #[cfg_attr(feature = "serde", serde(default))]
+    pub(super) taker_fee: i64,
+    #[cfg_attr(feature = "serde", serde(default))]
+    pub(super) maker_fee: i64,
+}
+
+impl Trade {
+    /// Trade identifier, increasing within a pair, and unique across the
+    /// engines sharing their trade ids.
+    #[inline]
+    pub fn id(&self) -> u64 {
+        self.id
+    }
+
+    /// Sequence number of the request which caused the trade.
+    #[inline]
+    pub fn sequence(&self) -> u64 {
+        self.sequence
+    }
+
+    /// Milliseconds since the Unix epoch when the trade happened.
+    #[inline]
+    pub fn timestamp(&self) -> u64 {
+        self.timestamp
+    }
+
+    #[inline]
+    pub fn pair(&self) -> &str {
+        &self.pair
+    }
+
+    /// Side of the incoming order which took liquidity.
+    #[inline]
+    pub fn aggressor(&self) -> OrderSide {
+        self.aggressor
+    }
+
+    #[inline]
+    pub fn taker(&self) -> OrderId {
+        self.taker
+    }
+
+    #[inline]
+    pub fn maker(&self) -> OrderId {
+        self.maker
+    }
+
+    #[inline]
+    pub fn taker_account(&self) -> u64 {
+        self.taker_account
+    }
+
+    #[inline]
+    pub fn maker_account(&self) -> u64 {
+        self.maker_account
+    }
+
+    /// Amount exchanged.
+    #[inline]
+    pub fn amount(&self) -> u64 {
+        self.amount
+    }
+
+    /// Price the amount was exchanged at.
+    #[inline]
+    pub fn price(&self) -> u64 {
+        self.price
+    }
+
+    /// Fee charged to the taker on what it receives, negative for a rebate.
+    #[inline]
+    pub fn taker_fee(&self) -> i64 {
+        self.taker_fee
+    }
+
+    /// Fee charged to the maker on what it receives, negative for a rebate.
+    #[inline]
+    pub fn maker_fee(&self) -> i64 {
+        self.maker_fee
+    }
+}
+
+/// What a trade discloses to the market: the accounts, orders and fees of
+/// its parties are only reported to their owners.
+#[derive(Clone, Debug, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub struct PublicTrade {
+    pub id: u64,
+    pub pair: CompactString,
+    pub price: u64,
+    pub amount: u64,
+    /// Side of the incoming order which took liquidity.
+    pub aggressor: OrderSide,
+    /// Milliseconds since the Unix epoch when the trade happened.
+    pub timestamp: u64,
+}
+
+impl From<&Trade> for PublicTrade {
+    #[inline]
+    fn from(trade: &Trade) -> Self {
+        Self {
+            id: trade.id,
+            pair: trade.pair.clone(),
+            price: trade.price,
+            amount: trade.amount,
+            aggressor: trade.aggressor,
+            timestamp: trade.timestamp,
+        }
+    }
 }
 
 impl<Order: Asset<Trade = Self>> TryFrom<(&mut Order, &mut Order)> for Trade {
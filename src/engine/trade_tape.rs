@@ -0,0 +1,79 @@
+use std::collections::{HashMap, VecDeque};
+
+use compact_str::CompactString;
+
+use super::{Event, Order, Trade};
+
+/// Bounded record of the most recent trades of each pair.
+#[derive(Debug)]
+pub struct TradeTape {
+    capacity: usize,
+    pairs: HashMap<CompactString, VecDeque<Trade>>,
+}
+
+impl TradeTape {
+    /// Create a tape keeping up to `capacity` trades per pair.
+    #[inline]
+    pub fn new(capacity: usize) -> Self {
+        Self {
+            capacity,
+            pairs: HashMap::new(),
+        }
+    }
+
+    #[inline]
+    pub fn capacity(&self) -> usize {
+        self.capacity
+    }
+
+    /// Record the trades among `events`.
+    pub fn record(&mut self, events: &[Event<Order>]) {
+        for event in events {
+            if let Event::Traded(trade) = event {
+                self.push(trade.clone());
+            }
+        }
+    }
+
+    /// Record `trade`, dropping the oldest one of its pair when full.
+    pub fn push(&mut self, trade: Trade) {
+        if self.capacity == 0 {
+            return;
+        }
+
+        let trades = self.pairs.entry(trade.pair.clone()).or_default();
+        if trades.len() == self.capacity {
+            trades.pop_front();
+        }
+        trades.push_back(trade);
+    }
+
+    /// Iterate over the trades kept for `pair`, oldest first.
+    #[inline]
+    pub fn trades(
+        &self,
+        pair: &str,
+    ) -> impl DoubleEndedIterator<Item = &Trade> + '_ {
+        self.pairs.get(pair).into_iter().flatten()
+    }
+
+    /// Iterate over the `count` most recent trades of `pair`, newest first.
+    #[inline]
+    pub fn recent(
+        &self,
+        pair: &str,
+        count: usize,
+    ) -> impl Iterator<Item = &Trade> + '_ {
+        self.trades(pair).rev().take(count)
+    }
+
+    /// Iterate over the trades of `pair` following `trade_id`, oldest first.
+    #[inline]
+    pub fn since(
+        &self,
+        pair: &str,
+        trade_id: u64,
+    ) -> impl Iterator<Item = &Trade> + '_ {
+        self.trades(pair).filter(move |trade| trade.id > trade_id)
+    }
+}
@@ -0,0 +1,330 @@
+use std::collections::HashMap;
+use std::fmt::Display;
+use std::io::{self, Write};
+use std::net::{SocketAddr, TcpListener, TcpStream, ToSocketAddrs};
+use std::sync::{Arc, Mutex, PoisonError};
+use std::thread;
+use std::time::{SystemTime, UNIX_EPOCH};
+
+use compact_str::{format_compact, CompactString};
+
+use super::{
+    decode_request, encode_cancel_reject, encode_execution_report, msg_type,
+    tag, FixError, Message, MessageReader,
+};
+use crate::engine::{
+    Engine, Event, ExecType, OrderId, OrderRequest, OrderStatus, RejectReason,
+    ReportGenerator,
+};
+
+/// Accepts FIX sessions over TCP and runs their orders through a single
+/// [`Engine`].
+///
+/// Every session is served by its own thread, while requests are processed
+/// one at a time in arrival order. Execution reports are sent to the session
+/// which owns the order, so both sides of a trade are notified.
+pub struct Acceptor {
+    listener: TcpListener,
+    shared: Arc<Mutex<Shared>>,
+}
+
+impl Acceptor {
+    pub fn bind(addr: impl ToSocketAddrs, engine: Engine) -> io::Result<Self> {
+        Ok(Self {
+            listener: TcpListener::bind(addr)?,
+            shared: Arc::new(Mutex::new(Shared {
+                engine,
+                reports: ReportGenerator::new(),
+                owners: HashMap::new(),
+                sessions: HashMap::new(),
+                next_session: 0,
+                next_exec_id: 1,
+            })),
+        })
+    }
+
+    #[inline]
+    pub fn local_addr(&self) -> io::Result<SocketAddr> {
+        self.listener.local_addr()
+    }
+
+    /// Accept sessions until the listener fails.
+    pub fn run(self) -> io::Result<()> {
+        for stream in self.listener.incoming() {
+            let stream = stream?;
+            let shared = Arc::clone(&self.shared);
+            thread::spawn(move || serve(shared, stream));
+        }
+
+        Ok(())
+    }
+}
+
+struct Session {
+    stream: TcpStream,
+    sender_comp_id: CompactString,
+    target_comp_id: CompactString,
+    seq_num: u64,
+}
+
+impl Session {
+    fn send(&mut self, message: &Message) -> io::Result<()> {
+        let mut header = Message::new(message.msg_type())
+            .with(tag::SENDER_COMP_ID, &self.sender_comp_id)
+            .with(tag::TARGET_COMP_ID, &self.target_comp_id)
+            .with(tag::MSG_SEQ_NUM, self.seq_num)
+            .with(tag::SENDING_TIME, sending_time());
+        for (tag, value) in message.fields() {
+            header.push(tag, value);
+        }
+        self.seq_num += 1;
+
+        self.stream.write_all(&header.encode())
+    }
+}
+
+struct Shared {
+    engine: Engine,
+    reports: ReportGenerator,
+    owners: HashMap<OrderId, usize>,
+    sessions: HashMap<usize, Session>,
+    next_session: usize,
+    next_exec_id: u64,
+}
+
+impl Shared {
+    #[inline]
+    fn send(&mut self, session_id: usize, message: &Message) {
+        if let Some(session) = self.sessions.get_mut(&session_id) {
+            // A broken connection is cleaned up by its own thread.
+            let _ = session.send(message);
+        }
+    }
+
+    /// Handle a message from `session_id`. Return `false` once the session
+    /// has logged out.
+    fn handle(&mut self, session_id: usize, message: Message) -> bool {
+        match message.msg_type() {
+            msg_type::LOGON => {
+                if let Some(session) = self.sessions.get_mut(&session_id) {
+                    session.sender_comp_id = message
+                        .get(tag::TARGET_COMP_ID)
+                        .unwrap_or_default()
+                        .into();
+                    session.target_comp_id = message
+                        .get(tag::SENDER_COMP_ID)
+                        .unwrap_or_default()
+                        .into();
+                }
+                let reply = Message::new(msg_type::LOGON)
+                    .with(tag::ENCRYPT_METHOD, 0)
+                    .with(
+                        tag::HEART_BT_INT,
+                        message.get(tag::HEART_BT_INT).unwrap_or("30"),
+                    );
+                self.send(session_id, &reply);
+            }
+            msg_type::HEARTBEAT => (),
+            msg_type::TEST_REQUEST => {
+                let mut reply = Message::new(msg_type::HEARTBEAT);
+                if let Some(test_req_id) = message.get(tag::TEST_REQ_ID) {
+                    reply.push(tag::TEST_REQ_ID, test_req_id);
+                }
+                self.send(session_id, &reply);
+            }
+            msg_type::LOGOUT => {
+                self.send(session_id, &Message::new(msg_type::LOGOUT));
+                return false;
+            }
+            _ => match decode_request(&message) {
+                Ok(request) => self.process(session_id, &message, request),
+                Err(err) => self.reject(session_id, &message, err),
+            },
+        }
+
+        true
+    }
+
+    /// Reject `message` from `session_id` at the session level.
+    fn reject(
+        &mut self,
+        session_id: usize,
+        message: &Message,
+        text: impl Display,
+    ) {
+        let mut reply = Message::new(msg_type::REJECT);
+        if let Some(seq_num) = message.get(tag::MSG_SEQ_NUM) {
+            reply.push(tag::REF_SEQ_NUM, seq_num);
+        }
+        reply.push(tag::TEXT, text);
+        self.send(session_id, &reply);
+    }
+
+    fn process(
+        &mut self,
+        session_id: usize,
+        message: &Message,
+        request: OrderRequest,
+    ) {
+        match request {
+            OrderRequest::Delete { ref order_id }
+            | OrderRequest::Modify { ref order_id, .. } => {
+                let order_id = OrderId::new(
+                    order_id.parse().expect("validated by the FIX codec"),
+                );
+                // Orders of other sessions are not disclosed.
+                if self.owners.get(&order_id) != Some(&session_id) {
+                    let reply = encode_cancel_reject(
+                        message,
+                        RejectReason::UnknownOrder,
+                    );
+                    self.send(session_id, &reply);
+                    return;
+                }
+            }
+            _ => (),
+        }
+
+        // The phase switches due by now are reported on apart, as the
+        // reports of the request only follow from its own events.
+        let mut events = self.engine.tick();
+        let mut reports = self.reports.reports(&events);
+        let ticked = events.len();
+        let processed =
+            self.engine.try_process_into(request.clone(), &mut events);
+        let events = &events[ticked..];
+        match processed {
+            Ok(()) => {
+                if let OrderRequest::Create { ref order_id, .. } = request {
+                    let order_id = OrderId::new(
+                        order_id.parse().expect("validated by the FIX codec"),
+                    );
+                    // Only accepted orders are owned, so that a duplicate
+                    // does not steal the live order from its owner.
+                    let rejected = events.iter().any(|event| match event {
+                        Event::Rejected(id, _) => *id == order_id,
+                        _ => false,
+                    });
+                    if !rejected {
+                        self.owners.insert(order_id, session_id);
+                    }
+                }
+                reports.extend(self.reports.generate(&request, events))
+            }
+            // The engine has the last word on what the codec let through.
+            Err(err) => self.reject(session_id, message, err),
+        }
+
+        let is_cancel_or_replace = matches!(
+            request,
+            OrderRequest::Delete { .. } | OrderRequest::Modify { .. }
+        );
+        if is_cancel_or_replace {
+            if let Some(reason) = events.iter().find_map(|event| match event {
+                Event::Rejected(_, reason) => Some(*reason),
+                _ => None,
+            }) {
+                self.send(session_id, &encode_cancel_reject(message, reason));
+            }
+        }
+
+        let symbol = CompactString::new(self.engine.orderbook().pair());
+        for report in reports {
+            let owner = match report.exec_type() {
+                ExecType::Rejected if is_cancel_or_replace => continue,
+                ExecType::Rejected => Some(session_id),
+                _ => self.owners.get(&report.order_id()).copied(),
+            };
+            if matches!(
+                report.order_status(),
+                OrderStatus::Completed
+                    | OrderStatus::Cancelled
+                    | OrderStatus::Closed
+            ) {
+                self.owners.remove(&report.order_id());
+            }
+
+            if let Some(owner) = owner {
+                let exec_id = self.next_exec_id;
+                self.next_exec_id += 1;
+                let message =
+                    encode_execution_report(&report, &symbol, exec_id);
+                self.send(owner, &message);
+            }
+        }
+    }
+}
+
+fn serve(shared: Arc<Mutex<Shared>>, stream: TcpStream) {
+    let session_id = {
+        let writer = match stream.try_clone() {
+            Ok(writer) => writer,
+            Err(_) => return,
+        };
+        let mut shared = shared.lock().unwrap_or_else(PoisonError::into_inner);
+        let session_id = shared.next_session;
+        shared.next_session += 1;
+        shared.sessions.insert(
+            session_id,
+            Session {
+                stream: writer,
+                sender_comp_id: CompactString::default(),
+                target_comp_id: CompactString::default(),
+                seq_num: 1,
+            },
+        );
+        session_id
+    };
+
+    let mut reader = MessageReader::new(stream);
+    loop {
+        let message = match reader.read_message() {
+            Ok(Some(message)) => message,
+            // Garbled framing cannot be recovered from, so the session ends
+            // just like a closed connection.
+            Ok(None) | Err(FixError::Io(_) | FixError::BodyLength) => break,
+            Err(_) => continue,
+        };
+
+        let mut shared = shared.lock().unwrap_or_else(PoisonError::into_inner);
+        if !shared.handle(session_id, message) {
+            break;
+        }
+    }
+
+    // The orders of the session rest on, without an owner.
+    let mut shared = shared.lock().unwrap_or_else(PoisonError::into_inner);
+    shared.sessions.remove(&session_id);
+    shared.owners.retain(|_, owner| *owner != session_id);
+}
+
+/// Current UTC time formatted as `YYYYMMDD-HH:MM:SS.sss`.
+fn sending_time() -> CompactString {
+    let elapsed = SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .unwrap_or_default();
+    let secs = elapsed.as_secs();
+    let (days, secs_of_day) = (secs / 86_400, secs % 86_400);
+
+    // Civil date from days since epoch, after Howard Hinnant's algorithm.
+    let z = days as i64 + 719_468;
+    let era = z.div_euclid(146_097);
+    let doe = z.rem_euclid(146_097);
+    let yoe = (doe - doe / 1_460 + doe / 36_524 - doe / 146_096) / 365;
+    let doy = doe - (365 * yoe + yoe / 4 - yoe / 100);
+    let mp = (5 * doy + 2) / 153;
+    let day = doy - (153 * mp + 2) / 5 + 1;
+    let month = if mp < 10 { mp + 3 } else { mp - 9 };
+    let year = yoe + era * 400 + i64::from(month <= 2);
+
+    format_compact!(
+        "{:04}{:02}{:02}-{:02}:{:02}:{:02}.{:03}",
+        year,
+        month,
+        day,
+        secs_of_day / 3_600,
+        secs_of_day % 3_600 / 60,
+        secs_of_day % 60,
+        elapsed.subsec_millis()
+    )
+}
@@ -0,0 +1,188 @@
+use std::str::FromStr;
+
+use compact_str::CompactString;
+use rust_decimal::Decimal;
+
+use super::{msg_type, tag, FixError, Message};
+use crate::engine::{
+    from_units, parse_units, ExecType, ExecutionReport, OrderRequest,
+    OrderStatus, RejectReason,
+};
+use crate::OrderSide;
+
+/// Translate a NewOrderSingle, OrderCancelRequest or
+/// OrderCancelReplaceRequest into an [`OrderRequest`].
+///
+/// Orders are identified by the `ClOrdID` they were created with; cancel and
+/// replace requests refer to them through `OrigClOrdID`.
+pub fn decode_request(message: &Message) -> Result<OrderRequest, FixError> {
+    match message.msg_type() {
+        msg_type::NEW_ORDER_SINGLE => {
+            if message
+                .get(tag::ORD_TYPE)
+                .is_some_and(|ord_type| ord_type != "2")
+            {
+                // Only limit orders are supported.
+                return Err(FixError::InvalidValue(tag::ORD_TYPE));
+            }
+
+            Ok(OrderRequest::Create {
+                account_id: id(message, tag::ACCOUNT)?,
+                amount: decimal(message, tag::ORDER_QTY)?,
+                order_id: id(message, tag::CL_ORD_ID)?,
+                pair: CompactString::new(message.require(tag::SYMBOL)?),
+                limit_price: decimal(message, tag::PRICE)?,
+                side: match message.require(tag::SIDE)? {
+                    "1" => OrderSide::Bid,
+                    "2" => OrderSide::Ask,
+                    _ => return Err(FixError::InvalidValue(tag::SIDE)),
+                },
+            })
+        }
+        msg_type::ORDER_CANCEL_REQUEST => Ok(OrderRequest::Delete {
+            order_id: id(message, tag::ORIG_CL_ORD_ID)?,
+        }),
+        msg_type::ORDER_CANCEL_REPLACE_REQUEST => Ok(OrderRequest::Modify {
+            order_id: id(message, tag::ORIG_CL_ORD_ID)?,
+            amount: decimal(message, tag::ORDER_QTY)?,
+            limit_price: decimal(message, tag::PRICE)?,
+        }),
+        other => Err(FixError::UnsupportedMsgType(CompactString::new(other))),
+    }
+}
+
+/// Build the ExecutionReport message for `report`.
+pub fn encode_execution_report(
+    report: &ExecutionReport,
+    symbol: &str,
+    exec_id: u64,
+) -> Message {
+    let order_id = report.order_id().to_string();
+    let mut message = Message::new(msg_type::EXECUTION_REPORT)
+        .with(tag::ORDER_ID, &order_id)
+        .with(tag::CL_ORD_ID, &order_id)
+        .with(tag::EXEC_ID, exec_id)
+        .with(tag::EXEC_TYPE, exec_type(report.exec_type()))
+        .with(tag::ORD_STATUS, ord_status(report.order_status()))
+        .with(tag::ACCOUNT, report.account_id())
+        .with(tag::SYMBOL, symbol);
+    if let Some(report_side) = report.side() {
+        message.push(tag::SIDE, side(report_side));
+    }
+    // Every price and amount of the report, the average price included, is
+    // in engine units.
+    message
+        .push(tag::PRICE, from_units(report.limit_price()))
+        .push(tag::LEAVES_QTY, from_units(report.leaves_qty()))
+        .push(tag::CUM_QTY, from_units(report.cum_qty()))
+        .push(tag::AVG_PX, from_units(report.avg_px()));
+
+    if report.last_qty() > 0 {
+        message
+            .push(tag::LAST_QTY, from_units(report.last_qty()))
+            .push(tag::LAST_PX, from_units(report.last_px()));
+    }
+    if let Some(reason) = report.reason() {
+        message
+            .push(tag::ORD_REJ_REASON, ord_rej_reason(reason))
+            .push(tag::TEXT, reason);
+    }
+
+    message
+}
+
+/// Build the OrderCancelReject message answering a rejected cancel or
+/// replace request.
+pub fn encode_cancel_reject(
+    request: &Message,
+    reason: RejectReason,
+) -> Message {
+    let response_to = match request.msg_type() {
+        msg_type::ORDER_CANCEL_REPLACE_REQUEST => "2",
+        _ => "1",
+    };
+    let orig_cl_ord_id = request.get(tag::ORIG_CL_ORD_ID).unwrap_or("NONE");
+
+    Message::new(msg_type::ORDER_CANCEL_REJECT)
+        .with(tag::ORDER_ID, orig_cl_ord_id)
+        .with(
+            tag::CL_ORD_ID,
+            request.get(tag::CL_ORD_ID).unwrap_or("NONE"),
+        )
+        .with(tag::ORIG_CL_ORD_ID, orig_cl_ord_id)
+        .with(tag::ORD_STATUS, "8")
+        .with(tag::CXL_REJ_RESPONSE_TO, response_to)
+        .with(
+            tag::CXL_REJ_REASON,
+            match reason {
+                RejectReason::UnknownOrder => "1",
+                _ => "99",
+            },
+        )
+        .with(tag::TEXT, reason)
+}
+
+#[inline]
+fn id(message: &Message, tag: u32) -> Result<CompactString, FixError> {
+    let value = message.require(tag)?;
+    // The engine identifies orders and accounts by number.
+    value
+        .parse::<u64>()
+        .map(|_| CompactString::new(value))
+        .map_err(|_| FixError::InvalidValue(tag))
+}
+
+#[inline]
+fn decimal(message: &Message, tag: u32) -> Result<Decimal, FixError> {
+    // The value must hold a positive number of engine units.
+    Decimal::from_str(message.require(tag)?)
+        .ok()
+        .filter(|value| parse_units(*value).is_ok_and(|units| units > 0))
+        .ok_or(FixError::InvalidValue(tag))
+}
+
+#[inline]
+fn side(side: OrderSide) -> &'static str {
+    match side {
+        OrderSide::Bid => "1",
+        OrderSide::Ask => "2",
+    }
+}
+
+#[inline]
+fn exec_type(exec_type: ExecType) -> &'static str {
+    match exec_type {
+        ExecType::New => "0",
+        ExecType::PartialFill | ExecType::Fill => "F",
+        ExecType::Canceled => "4",
+        ExecType::Replaced => "5",
+        ExecType::Rejected => "8",
+    }
+}
+
+#[inline]
+fn ord_status(order_status: OrderStatus) -> &'static str {
+    match order_status {
+        OrderStatus::Open => "0",
+        OrderStatus::Partial => "1",
+        OrderStatus::Completed => "2",
+        OrderStatus::Cancelled | OrderStatus::Closed => "4",
+        OrderStatus::Rejected => "8",
+    }
+}
+
+#[inline]
+fn ord_rej_reason(reason: RejectReason) -> &'static str {
+    match reason {
+        RejectReason::UnknownOrder => "5",
+        RejectReason::DuplicateOrder => "6",
+        RejectReason::InvalidAmount => "13",
+        RejectReason::AmountLimit
+        | RejectReason::NotionalLimit
+        | RejectReason::OpenOrdersLimit
+        | RejectReason::PositionLimit => "3",
+        RejectReason::PriceBand => "16",
+        RejectReason::InsufficientBalance => "99",
+        RejectReason::TradingPhase => "2",
+    }
+}
@@ -0,0 +1,249 @@
+use std::fmt::Display;
+use std::io::{self, Read};
+
+use compact_str::{format_compact, CompactString};
+
+use super::{tag, FixError};
+
+/// Field delimiter of the tag=value encoding.
+pub const SOH: u8 = 0x01;
+pub const BEGIN_STRING: &str = "FIX.4.4";
+/// Largest body accepted, so a bogus length cannot exhaust memory.
+pub const MAX_BODY_LENGTH: usize = 1 << 16;
+
+/// A FIX message, without the `BeginString`, `BodyLength` and `CheckSum`
+/// framing fields which are handled by [`Message::encode`] and
+/// [`Message::decode`].
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Message {
+    msg_type: CompactString,
+    fields: Vec<(u32, CompactString)>,
+}
+
+impl Message {
+    #[inline]
+    pub fn new(msg_type: &str) -> Self {
+        Self {
+            msg_type: CompactString::new(msg_type),
+            fields: Vec::with_capacity(16),
+        }
+    }
+
+    #[inline]
+    pub fn msg_type(&self) -> &str {
+        &self.msg_type
+    }
+
+    /// Return the first value of `tag`, if any.
+    #[inline]
+    pub fn get(&self, tag: u32) -> Option<&str> {
+        self.fields
+            .iter()
+            .find(|(field, _)| *field == tag)
+            .map(|(_, value)| value.as_str())
+    }
+
+    /// Return the first value of `tag`, failing if it is missing.
+    #[inline]
+    pub fn require(&self, tag: u32) -> Result<&str, FixError> {
+        self.get(tag).ok_or(FixError::MissingTag(tag))
+    }
+
+    /// Iterate over every body field, in order.
+    #[inline]
+    pub fn fields(&self) -> impl Iterator<Item = (u32, &str)> {
+        self.fields
+            .iter()
+            .map(|(tag, value)| (*tag, value.as_str()))
+    }
+
+    #[inline]
+    pub fn push(&mut self, tag: u32, value: impl Display) -> &mut Self {
+        self.fields.push((tag, format_compact!("{}", value)));
+        self
+    }
+
+    #[inline]
+    pub fn with(mut self, tag: u32, value: impl Display) -> Self {
+        self.push(tag, value);
+        self
+    }
+
+    /// Encode the message, computing its body length and checksum.
+    pub fn encode(&self) -> Vec<u8> {
+        let mut body = Vec::with_capacity(128);
+        write_field(&mut body, tag::MSG_TYPE, &self.msg_type);
+        for (tag, value) in &self.fields {
+            write_field(&mut body, *tag, value);
+        }
+
+        let mut buffer = Vec::with_capacity(body.len() + 32);
+        write_field(&mut buffer, tag::BEGIN_STRING, BEGIN_STRING);
+        write_field(&mut buffer, tag::BODY_LENGTH, body.len());
+        buffer.extend_from_slice(&body);
+        let checksum = checksum(&buffer);
+        write_field(&mut buffer, tag::CHECKSUM, format_args!("{checksum:03}"));
+
+        buffer
+    }
+
+    /// Decode the first message in `buffer`.
+    ///
+    /// Return `Ok(None)` when `buffer` does not hold a whole message yet,
+    /// otherwise the message and how many bytes it took.
+    pub fn decode(buffer: &[u8]) -> Result<Option<(Self, usize)>, FixError> {
+        let mut cursor = 0;
+
+        let begin_string = match next_field(buffer, &mut cursor)? {
+            Some(field) => field,
+            None => return Ok(None),
+        };
+        if begin_string.0 != tag::BEGIN_STRING
+            || !begin_string.1.starts_with("FIX")
+        {
+            return Err(FixError::Malformed("message must start with tag 8"));
+        }
+
+        let body_length = match next_field(buffer, &mut cursor)? {
+            Some((tag::BODY_LENGTH, value)) => value
+                .parse::<usize>()
+                .map_err(|_| FixError::InvalidValue(tag::BODY_LENGTH))?,
+            Some(_) => {
+                return Err(FixError::Malformed("tag 9 must follow tag 8"))
+            }
+            None => return Ok(None),
+        };
+
+        // The length comes from the peer, so it may point anywhere.
+        if body_length > MAX_BODY_LENGTH {
+            return Err(FixError::BodyLength);
+        }
+        let body_end = cursor + body_length;
+        // "10=" + three digits + SOH.
+        let message_end = body_end + 7;
+        if buffer.len() < message_end {
+            return Ok(None);
+        }
+
+        let mut trailer = body_end;
+        match next_field(buffer, &mut trailer) {
+            Ok(Some((tag::CHECKSUM, value))) if trailer == message_end => {
+                let expected = checksum(&buffer[..body_end]);
+                let found = value
+                    .parse::<u8>()
+                    .map_err(|_| FixError::InvalidValue(tag::CHECKSUM))?;
+                if expected != found {
+                    return Err(FixError::Checksum { expected, found });
+                }
+            }
+            _ => return Err(FixError::BodyLength),
+        }
+
+        let body = &buffer[..body_end];
+        let msg_type = match next_field(body, &mut cursor)? {
+            Some((tag::MSG_TYPE, value)) => value,
+            _ => return Err(FixError::Malformed("tag 35 must follow tag 9")),
+        };
+
+        let mut message = Message::new(msg_type);
+        while let Some((tag, value)) = next_field(body, &mut cursor)? {
+            message.fields.push((tag, CompactString::new(value)));
+        }
+        if cursor != body_end {
+            return Err(FixError::BodyLength);
+        }
+
+        Ok(Some((message, message_end)))
+    }
+}
+
+/// Sum of every byte modulo 256, as defined by the `CheckSum` field.
+#[inline]
+fn checksum(bytes: &[u8]) -> u8 {
+    bytes.iter().fold(0u8, |sum, byte| sum.wrapping_add(*byte))
+}
+
+#[inline]
+fn write_field(buffer: &mut Vec<u8>, tag: u32, value: impl Display) {
+    use std::io::Write;
+
+    write!(buffer, "{}={}", tag, value).expect("writing into a vector");
+    buffer.push(SOH);
+}
+
+/// Parse the `tag=value` field starting at `cursor`, moving it past the
+/// trailing delimiter. Return `Ok(None)` when the field is incomplete.
+fn next_field<'a>(
+    buffer: &'a [u8],
+    cursor: &mut usize,
+) -> Result<Option<(u32, &'a str)>, FixError> {
+    let rest = &buffer[(*cursor).min(buffer.len())..];
+    let end = match rest.iter().position(|byte| *byte == SOH) {
+        Some(end) => end,
+        None => return Ok(None),
+    };
+    let field = std::str::from_utf8(&rest[..end])
+        .map_err(|_| FixError::Malformed("field is not valid UTF-8"))?;
+    let (tag, value) = field
+        .split_once('=')
+        .ok_or(FixError::Malformed("field must be formatted as tag=value"))?;
+    let tag = tag
+        .parse::<u32>()
+        .map_err(|_| FixError::Malformed("tag must be a number"))?;
+
+    *cursor += end + 1;
+    Ok(Some((tag, value)))
+}
+
+/// Reads FIX messages out of a byte stream.
+pub struct MessageReader<R> {
+    inner: R,
+    buffer: Vec<u8>,
+}
+
+impl<R: Read> MessageReader<R> {
+    #[inline]
+    pub fn new(inner: R) -> Self {
+        Self {
+            inner,
+            buffer: Vec::with_capacity(4096),
+        }
+    }
+
+    /// Read the next message. Return `Ok(None)` once the stream is closed.
+    pub fn read_message(&mut self) -> Result<Option<Message>, FixError> {
+        let mut chunk = [0; 4096];
+        loop {
+            match Message::decode(&self.buffer) {
+                Ok(Some((message, length))) => {
+                    self.buffer.drain(..length);
+                    return Ok(Some(message));
+                }
+                // Whatever frames a body fits in a few bytes, so a longer
+                // message is as garbled as a longer body.
+                Ok(None) if self.buffer.len() > MAX_BODY_LENGTH + 64 => {
+                    self.buffer.clear();
+                    return Err(FixError::BodyLength);
+                }
+                Ok(None) => (),
+                Err(err) => {
+                    // Garbled data is discarded.
+                    self.buffer.clear();
+                    return Err(err);
+                }
+            }
+
+            match self.inner.read(&mut chunk) {
+                Ok(0) if self.buffer.is_empty() => return Ok(None),
+                Ok(0) => {
+                    return Err(FixError::Io(
+                        io::ErrorKind::UnexpectedEof.into(),
+                    ))
+                }
+                Ok(length) => self.buffer.extend_from_slice(&chunk[..length]),
+                Err(err) if err.kind() == io::ErrorKind::Interrupted => (),
+                Err(err) => return Err(err.into()),
+            }
+        }
+    }
+}
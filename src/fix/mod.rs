@@ -0,0 +1,88 @@
+//! FIX 4.4 tag=value gateway: message framing, translation between FIX
+//! application messages and [`OrderRequest`](crate::engine::OrderRequest) /
+//! [`ExecutionReport`](crate::engine::ExecutionReport), and a TCP acceptor.
+
+use std::io;
+
+use compact_str::CompactString;
+use thiserror::Error;
+
+mod acceptor;
+pub use acceptor::Acceptor;
+
+mod codec;
+pub use codec::{
+    decode_request, encode_cancel_reject, encode_execution_report,
+};
+
+mod message;
+pub use message::{Message, MessageReader, BEGIN_STRING, MAX_BODY_LENGTH, SOH};
+
+#[derive(Debug, Error)]
+pub enum FixError {
+    #[error("malformed message: {0}")]
+    Malformed(&'static str),
+    #[error("body length does not match the message")]
+    BodyLength,
+    #[error("checksum mismatch: expected {expected:03}, found {found:03}")]
+    Checksum { expected: u8, found: u8 },
+    #[error("required tag {0} is missing")]
+    MissingTag(u32),
+    #[error("tag {0} has an invalid value")]
+    InvalidValue(u32),
+    #[error("unsupported message type {0}")]
+    UnsupportedMsgType(CompactString),
+    #[error(transparent)]
+    Io(#[from] io::Error),
+}
+
+/// Tags used by the gateway.
+pub mod tag {
+    pub const ACCOUNT: u32 = 1;
+    pub const AVG_PX: u32 = 6;
+    pub const BEGIN_STRING: u32 = 8;
+    pub const BODY_LENGTH: u32 = 9;
+    pub const CHECKSUM: u32 = 10;
+    pub const CL_ORD_ID: u32 = 11;
+    pub const CUM_QTY: u32 = 14;
+    pub const EXEC_ID: u32 = 17;
+    pub const LAST_PX: u32 = 31;
+    pub const LAST_QTY: u32 = 32;
+    pub const MSG_SEQ_NUM: u32 = 34;
+    pub const MSG_TYPE: u32 = 35;
+    pub const ORDER_ID: u32 = 37;
+    pub const ORDER_QTY: u32 = 38;
+    pub const ORD_STATUS: u32 = 39;
+    pub const ORD_TYPE: u32 = 40;
+    pub const ORIG_CL_ORD_ID: u32 = 41;
+    pub const PRICE: u32 = 44;
+    pub const REF_SEQ_NUM: u32 = 45;
+    pub const SENDER_COMP_ID: u32 = 49;
+    pub const SENDING_TIME: u32 = 52;
+    pub const SIDE: u32 = 54;
+    pub const SYMBOL: u32 = 55;
+    pub const TARGET_COMP_ID: u32 = 56;
+    pub const TEXT: u32 = 58;
+    pub const ENCRYPT_METHOD: u32 = 98;
+    pub const CXL_REJ_REASON: u32 = 102;
+    pub const ORD_REJ_REASON: u32 = 103;
+    pub const HEART_BT_INT: u32 = 108;
+    pub const TEST_REQ_ID: u32 = 112;
+    pub const EXEC_TYPE: u32 = 150;
+    pub const LEAVES_QTY: u32 = 151;
+    pub const CXL_REJ_RESPONSE_TO: u32 = 434;
+}
+
+/// Message types used by the gateway.
+pub mod msg_type {
+    pub const HEARTBEAT: &str = "0";
+    pub const TEST_REQUEST: &str = "1";
+    pub const REJECT: &str = "3";
+    pub const LOGOUT: &str = "5";
+    pub const EXECUTION_REPORT: &str = "8";
+    pub const ORDER_CANCEL_REJECT: &str = "9";
+    pub const LOGON: &str = "A";
+    pub const NEW_ORDER_SINGLE: &str = "D";
+    pub const ORDER_CANCEL_REQUEST: &str = "F";
+    pub const ORDER_CANCEL_REPLACE_REQUEST: &str = "G";
+}
@@ -0,0 +1,272 @@
+//! Random flows of order requests, as `orders_generator` writes them.
+//!
+//! Limit prices are drawn around a mid price which follows a random walk, and
+//! every request is replayed through an engine of its pair, so that deletes and
+//! modifies only target orders still resting. The whole flow is determined by
+//! its seed.
+
+use std::f64::consts::TAU;
+
+use compact_str::{format_compact, CompactString};
+use rand::rngs::StdRng;
+use rand::{Rng, SeedableRng};
+use rust_decimal::Decimal;
+
+use crate::engine::{Engine, Event, Order, OrderId, OrderRequest};
+use crate::{Asset, OrderSide};
+
+/// Base assets of the generated pairs, all quoted in `USDC`.
+const BASES: [&str; 8] =
+    ["BTC", "ETH", "SOL", "XRP", "ADA", "DOT", "LTC", "BNB"];
+
+/// Distribution of the limit prices around the mid.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum Distribution {
+    /// Uniform within the width.
+    Uniform,
+    /// Normal, with the width as standard deviation.
+    #[default]
+    Normal,
+}
+
+impl Distribution {
+    /// Draw a limit price around `mid`, rounded to cents and at least one.
+    fn price(self, rng: &mut StdRng, mid: f64, width: f64) -> Decimal {
+        let offset = match self {
+            Distribution::Uniform => rng.gen_range(-1.0..=1.0) * width,
+            Distribution::Normal => normal(rng) * width,
+        };
+        Decimal::new(((mid + offset) * 100.0).round().max(1.0) as i64, 2)
+    }
+}
+
+/// A traded pair, replayed through an engine to know which orders rest.
+struct Market {
+    pair: CompactString,
+    mid: f64,
+    engine: Engine,
+    /// Orders created on the pair, some of which may have left the book
+    /// since. They are dropped when drawn, and whenever the list doubled.
+    orders: Vec<u64>,
+    /// Length of `orders` from which those which left are dropped.
+    prune_at: usize,
+}
+
+impl Market {
+    fn new(index: usize, mid: f64) -> Self {
+        let pair = match BASES.get(index) {
+            Some(base) => format_compact!("{base}/USDC"),
+            None => format_compact!("A{index}/USDC"),
+        };
+        Self {
+            engine: Engine::new(&pair),
+            pair,
+            mid,
+            orders: Vec::new(),
+            prune_at: 1024,
+        }
+    }
+
+    /// Draw one of the orders resting on the pair, if any.
+    fn live(&mut self, rng: &mut StdRng) -> Option<u64> {
+        while !self.orders.is_empty() {
+            let index = rng.gen_range(0..self.orders.len());
+            let order_id = self.orders[index];
+            if self.engine.order(&OrderId::new(order_id)).is_some() {
+                return Some(order_id);
+            }
+            self.orders.swap_remove(index);
+        }
+        None
+    }
+
+    /// Drop the orders which left the book, once `orders` doubled since the
+    /// last time, so that flows rarely drawing orders do not hold them all.
+    fn prune(&mut self) {
+        if self.orders.len() < self.prune_at {
+            return;
+        }
+        let engine = &self.engine;
+        self.orders.retain(|order_id| {
+            engine.order(&OrderId::new(*order_id)).is_some()
+        });
+        self.prune_at = (2 * self.orders.len()).max(1024);
+    }
+}
+
+/// Endless flow of creates, deletes and modifies over one or more pairs.
+///
+/// Order ids are unique across pairs, and only creates name their pair.
+pub struct Generator {
+    rng: StdRng,
+    markets: Vec<Market>,
+    accounts: u64,
+    cancel_ratio: f64,
+    modify_ratio: f64,
+    prices: Distribution,
+    mid: f64,
+    width: f64,
+    volatility: f64,
+    order_id: u64,
+    events: Vec<Event<Order>>,
+}
+
+impl Generator {
+    /// Create a flow on `BTC/USDC` for 9 accounts, deleting a live order once
+    /// in a thousand requests, with prices normally distributed 50 around a
+    /// mid of 1500 moving by 0.5 on each request.
+    pub fn new(seed: u64) -> Self {
+        Self {
+            rng: StdRng::seed_from_u64(seed),
+            markets: vec![Market::new(0, 1500.0)],
+            accounts: 9,
+            cancel_ratio: 0.001,
+            modify_ratio: 0.0,
+            prices: Distribution::default(),
+            mid: 1500.0,
+            width: 50.0,
+            volatility: 0.5,
+            order_id: 0,
+            events: Vec::with_capacity(64),
+        }
+    }
+
+    /// Trade `pairs` pairs, `BTC/USDC` first, each request going to one of
+    /// them at random.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `pairs` is zero.
+    pub fn with_pairs(mut self, pairs: usize) -> Self {
+        assert!(pairs > 0, "at least one pair should be traded");
+        self.markets = (0..pairs).map(|i| Market::new(i, self.mid)).collect();
+        self
+    }
+
+    /// Draw the accounts creating orders among `1..=accounts`.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `accounts` is zero.
+    #[inline]
+    pub fn with_accounts(mut self, accounts: u64) -> Self {
+        assert!(accounts > 0, "at least one account should trade");
+        self.accounts = accounts;
+        self
+    }
+
+    /// Delete a live order in a `cancel` share of the requests, and modify one
+    /// in a `modify` share. The others are creates.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the shares are not within 0 and 1, or add up to more than 1.
+    pub fn with_ratios(mut self, cancel: f64, modify: f64) -> Self {
+        assert!(
+            [cancel, modify]
+                .iter()
+                .all(|ratio| (0.0..=1.0).contains(ratio))
+                && cancel + modify <= 1.0,
+            "the cancel and modify ratios should add up to at most 1"
+        );
+        self.cancel_ratio = cancel;
+        self.modify_ratio = modify;
+        self
+    }
+
+    /// Draw limit prices following `prices`, `width` around a mid starting
+    /// at `mid` on every pair.
+    pub fn with_prices(
+        mut self,
+        prices: Distribution,
+        mid: f64,
+        width: f64,
+    ) -> Self {
+        self.prices = prices;
+        self.mid = mid;
+        self.width = width;
+        for market in &mut self.markets {
+            market.mid = mid;
+        }
+        self
+    }
+
+    /// Move the mid of the pair by a normal step of standard deviation
+    /// `volatility` on each request.
+    #[inline]
+    pub fn with_volatility(mut self, volatility: f64) -> Self {
+        self.volatility = volatility;
+        self
+    }
+}
+
+impl Iterator for Generator {
+    type Item = OrderRequest;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        let rng = &mut self.rng;
+        let index = rng.gen_range(0..self.markets.len());
+        let market = &mut self.markets[index];
+        // Keep the mid above the width, so that most prices stay positive.
+        market.mid =
+            (market.mid + normal(rng) * self.volatility).max(self.width);
+
+        let roll = rng.gen::<f64>();
+        let existing = match roll < self.cancel_ratio + self.modify_ratio {
+            true => market.live(rng),
+            false => None,
+        };
+        let request = match existing {
+            Some(order_id) if roll < self.cancel_ratio => {
+                OrderRequest::Delete {
+                    order_id: format_compact!("{order_id}"),
+                }
+            }
+            Some(order_id) => {
+                // The new amount has to exceed what was already filled.
+                let filled = market
+                    .engine
+                    .order(&OrderId::new(order_id))
+                    .map_or(0, |order| order.filled());
+                OrderRequest::Modify {
+                    order_id: format_compact!("{order_id}"),
+                    amount: Decimal::new(filled as i64, 2)
+                        + Decimal::from(rng.gen_range(1000..2000)),
+                    limit_price: self.prices.price(rng, market.mid, self.width),
+                }
+            }
+            None => {
+                self.order_id += 1;
+                market.orders.push(self.order_id);
+                OrderRequest::Create {
+                    account_id: format_compact!(
+                        "{}",
+                        rng.gen_range(1..=self.accounts)
+                    ),
+                    amount: rng.gen_range(1000..2000).into(),
+                    order_id: format_compact!("{}", self.order_id),
+                    pair: market.pair.clone(),
+                    limit_price: self.prices.price(rng, market.mid, self.width),
+                    side: match rng.gen_range(0..2) {
+                        0 => OrderSide::Ask,
+                        _ => OrderSide::Bid,
+                    },
+                }
+            }
+        };
+
+        self.events.clear();
+        market
+            .engine
+            .try_process_into(request.clone(), &mut self.events)
+            .expect("generated requests are well formed");
+        market.prune();
+        Some(request)
+    }
+}
+
+/// Draw from the standard normal distribution.
+fn normal(rng: &mut StdRng) -> f64 {
+    let (u, v) = (1.0 - rng.gen::<f64>(), rng.gen::<f64>());
+    (-2.0 * u.ln()).sqrt() * (TAU * v).cos()
+}
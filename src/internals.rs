@@ -8,7 +8,35 @@ pub trait Exchange {
         order: &<Self::Order as Asset>::OrderId,
     ) -> Option<Self::Order>;
     fn matching(&mut self, order: Self::Order) -> Vec<Self::Event> {
+        self.matching_with(order, |_, _| true)
+    }
+    /// Match `order` like [`Exchange::matching`], but ask `guard` before
+    /// trying each top order, whether it crosses or not. Matching stops at the
+    /// first top order `guard` refuses, and what is left of `order` rests in
+    /// the book.
+    fn matching_with<F>(
+        &mut self,
+        order: Self::Order,
+        guard: F,
+    ) -> Vec<Self::Event>
+    where
+        F: FnMut(&Self::Order, &Self::Order) -> bool,
+    {
         let mut events = Vec::with_capacity(32);
+        self.matching_into(order, guard, &mut events);
+        events
+    }
+    /// Match `order` like [`Exchange::matching_with`], appending the events
+    /// to `events` instead, so the same buffer can be reused from one order
+    /// to the next.
+    fn matching_into<F>(
+        &mut self,
+        order: Self::Order,
+        mut guard: F,
+        events: &mut Vec<Self::Event>,
+    ) where
+        F: FnMut(&Self::Order, &Self::Order) -> bool,
+    {
         let mut incoming_order = order;
         while let (false, Some(top_order)) = (
             incoming_order.is_closed(),
@@ -19,6 +47,10 @@ pub trait Exchange {
                 "top order cannot be closed before try to match"
             );
 
+            if !guard(&incoming_order, top_order) {
+                break;
+            }
+
             if let Some(trade) = incoming_order.trade(top_order) {
                 events.push(Self::Event::traded(trade));
                 match (incoming_order.is_closed(), top_order.is_closed()) {
@@ -45,8 +77,6 @@ pub trait Exchange {
             events.push(Self::Event::added(incoming_order.id()));
             self.insert(incoming_order);
         }
-
-        events
     }
     fn peek(
         &self,
@@ -88,16 +118,31 @@ pub trait Asset<Order = Self>: Ord + Eq {
     type Trade;
     /// Return order unique identifier.
     fn id(&self) -> Self::OrderId;
+    /// Return order owner account identifier.
+    fn account_id(&self) -> u64;
     /// Return order side.
     fn side(&self) -> Self::OrderSide;
     /// Return order limit price.
     fn limit_price(&self) -> u64;
+    /// Return order original amount.
+    fn amount(&self) -> u64;
+    /// Return order amount filled so far.
+    fn filled(&self) -> u64;
     /// Return order remaining amount.
     fn remaining(&self) -> u64;
     /// Return current order status.
     fn status(&self) -> Self::OrderStatus;
     fn is_closed(&self) -> bool;
-    fn trade(&mut self, order: &mut Order) -> Option<Self::Trade>;
+    /// Trade as much as possible with `order`, if their prices cross.
+    fn trade(&mut self, order: &mut Order) -> Option<Self::Trade> {
+        self.trade_up_to(order, u64::MAX)
+    }
+    /// Trade at most `amount` with `order`, if their prices cross.
+    fn trade_up_to(
+        &mut self,
+        order: &mut Order,
+        amount: u64,
+    ) -> Option<Self::Trade>;
     fn cancel(&mut self);
 }
 
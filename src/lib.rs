@@ -1,6 +1,3 @@
-#![feature(map_first_last)]
-#![feature(const_btree_new)]
-
 #[cfg(test)]
 mod tests;
 
@@ -12,4 +9,13 @@ pub use crate::internals::{
 mod order_side;
 pub use crate::order_side::OrderSide;
 
+pub mod binary;
+pub mod candles;
 pub mod engine;
+pub mod fix;
+pub mod generator;
+#[cfg(feature = "serde")]
+pub mod market_data;
+#[cfg(feature = "serde")]
+pub mod server;
+pub mod simulation;
@@ -1,22 +1,46 @@
+use std::collections::{BTreeMap, HashMap};
+use std::fmt::Write as _;
 use std::io::Read;
-use std::io::Result;
-use std::path::PathBuf;
+use std::io::{Error, ErrorKind, Result};
+use std::path::{Path, PathBuf};
+use std::sync::atomic::AtomicU64;
+use std::sync::Arc;
 use std::time::Instant;
 
-use clap::Parser;
+use clap::{ArgEnum, Parser, Subcommand};
 use compact_str::CompactString;
+use hdrhistogram::Histogram;
 
-use orderbook::engine::Engine;
-use orderbook::engine::OrderRequest;
+use orderbook::binary::{decode_request, Request};
+use orderbook::candles::{CandleAggregator, Interval};
+use orderbook::engine::{
+    Engine, Event, Order, OrderId, OrderRequest, OrderRequestError,
+};
+#[cfg(feature = "websocket")]
+use orderbook::market_data::MarketDataServer;
+use orderbook::server::Server;
 use orderbook::ExchangeExt;
 
 #[derive(Parser)]
 #[clap(author, version, about)]
 struct Args {
-    #[clap(short, long, default_value = "BTC/USDC")]
+    #[clap(
+        short,
+        long,
+        default_value = "BTC/USDC",
+        help = "Pair of binary requests and of unknown orders"
+    )]
     pair: CompactString,
     #[clap(short, long, parse(from_str), help = "Orders source")]
     input: Option<Input>,
+    #[clap(
+        short,
+        long,
+        arg_enum,
+        default_value = "json",
+        help = "Orders source format"
+    )]
+    format: Format,
     #[clap(
         short,
         long,
@@ -24,59 +48,482 @@ struct Args {
         help = "Orderbook events destination"
     )]
     output: Option<Output>,
+    #[clap(
+        long,
+        value_name = "INTERVAL",
+        help = "Print OHLCV candles of the trades (1s, 1m, 5m, 1h or 1d)"
+    )]
+    candles: Vec<Interval>,
+    #[clap(
+        long,
+        value_name = "DIR",
+        help = "Write the latency distribution of each kind of request in DIR"
+    )]
+    latency_histogram: Option<PathBuf>,
+    #[clap(subcommand)]
+    command: Option<Command>,
+}
+
+#[derive(Subcommand)]
+enum Command {
+    /// Serve length-delimited JSON order requests over TCP
+    Serve {
+        #[clap(short, long, default_value = "127.0.0.1:7878")]
+        addr: String,
+        /// Also publish market data over WebSocket on this address
+        #[cfg(feature = "websocket")]
+        #[clap(short, long)]
+        market_data: Option<String>,
+    },
 }
 
 fn main() -> Result<()> {
     let args = Args::parse();
 
+    if let Some(Command::Serve { addr, .. }) = &args.command {
+        let server = Server::bind(addr, Engine::new(&args.pair))?;
+        eprintln!("Listening on {}", server.local_addr()?);
+
+        #[cfg(feature = "websocket")]
+        let server = match &args.command {
+            Some(Command::Serve {
+                market_data: Some(addr),
+                ..
+            }) => {
+                let (market_data, publisher) = MarketDataServer::bind(addr)?;
+                eprintln!(
+                    "Publishing market data on {}",
+                    market_data.local_addr()?
+                );
+                std::thread::spawn(move || market_data.run());
+                server.with_market_data(publisher)
+            }
+            _ => server,
+        };
+
+        return server.run();
+    }
+
+    if let Some(Output::File(path)) = &args.output {
+        return Err(Error::new(
+            ErrorKind::Unsupported,
+            format!("writing events to {} is not supported", path.display()),
+        ));
+    }
+
     let content = match &args.input.unwrap_or_default() {
-        Input::File(path) => std::fs::read_to_string(&path)?,
+        Input::File(path) => std::fs::read(path)?,
         Input::Stdin => {
-            let mut buffer = String::new();
-            std::io::stdin().read_to_string(&mut buffer)?;
+            let mut buffer = Vec::new();
+            std::io::stdin().read_to_end(&mut buffer)?;
             buffer
         }
     };
-    let orders: Vec<OrderRequest> = serde_json::from_str(&content)?;
 
-    let mut engine = Engine::new(&args.pair);
+    let mut markets = Markets::new(&args.pair);
     let mut events = Vec::with_capacity(1024);
+    let mut latencies = Latencies::new();
 
     let mut i = 0.0f64;
-    let begin = Instant::now();
-    for order in orders {
-        events.append(&mut engine.process(order));
+    let mut process = |order: Replayed| {
+        let kind = Kind::of(&order);
+        let (start, processed) = (events.len(), Instant::now());
+        markets
+            .process(order, &mut events)
+            .map_err(|err| Error::new(ErrorKind::InvalidData, err))?;
+        latencies.record(kind, &events[start..], processed.elapsed());
         i += 1.0;
+        Ok::<_, Error>(())
+    };
+    let begin;
+    match args.format {
+        Format::Json => {
+            let orders: Vec<OrderRequest> = serde_json::from_slice(&content)?;
+
+            begin = Instant::now();
+            for order in orders {
+                process(Replayed::Request(order))?;
+            }
+        }
+        Format::Jsonl => {
+            let orders = serde_json::Deserializer::from_slice(&content)
+                .into_iter()
+                .collect::<serde_json::Result<Vec<OrderRequest>>>()?;
+
+            begin = Instant::now();
+            for order in orders {
+                process(Replayed::Request(order))?;
+            }
+        }
+        Format::Binary => {
+            let mut content = &content[..];
+
+            begin = Instant::now();
+            while let Some((request, length)) = decode_request(content)
+                .map_err(|err| Error::new(ErrorKind::InvalidData, err))?
+            {
+                process(Replayed::Binary(request))?;
+                content = &content[length..];
+            }
+            if !content.is_empty() {
+                return Err(Error::new(
+                    ErrorKind::UnexpectedEof,
+                    "truncated binary message",
+                ));
+            }
+        }
     }
     let end = Instant::now();
 
     let elapsed = end - begin;
-    let (ask_length, bid_length) = engine.orderbook().len();
 
     eprintln!("Elapsed time: {:.2}s", elapsed.as_secs_f64());
     eprintln!("Total:        {}", i.round() as i64);
     eprintln!("Average:      {:.2} orders/s", i / elapsed.as_secs_f64());
     eprintln!();
-    eprintln!("Orderbook infos:");
-    if let Some((ask_price, bid_price)) = engine.orderbook().spread() {
-        eprintln!("  Spread:");
-        eprintln!("    Ask: {}", ask_price);
-        eprintln!("    Bid: {}", bid_price);
-    }
-    eprintln!("  Length:");
-    eprintln!("    Ask: {}", ask_length);
-    eprintln!("    Bid: {}", bid_length);
-
-    match &args.output.unwrap_or_default() {
-        Output::Stdout => {
-            // TODO: impl serde feature
+    for (pair, engine) in &markets.engines {
+        let (ask_length, bid_length) = engine.orderbook().len();
+        eprintln!("Orderbook infos ({pair}):");
+        if let Some((ask_price, bid_price)) = engine.orderbook().spread() {
+            eprintln!("  Spread:");
+            eprintln!("    Ask: {}", ask_price);
+            eprintln!("    Bid: {}", bid_price);
         }
-        Output::File(..) => unimplemented!(),
-    };
+        eprintln!("  Length:");
+        eprintln!("    Ask: {}", ask_length);
+        eprintln!("    Bid: {}", bid_length);
+        eprintln!();
+    }
+    eprintln!("Latency (ns):");
+    latencies.summary();
+    if let Some(dir) = &args.latency_histogram {
+        latencies.write(dir)?;
+    }
+
+    for interval in args.candles {
+        let mut aggregators = BTreeMap::new();
+        for event in &events {
+            if let Event::Traded(trade) = event {
+                aggregators
+                    .entry(trade.pair())
+                    .or_insert_with(|| CandleAggregator::new(interval))
+                    .update(std::slice::from_ref(event));
+            }
+        }
+        for (pair, aggregator) in aggregators {
+            for candle in aggregator.candles() {
+                let mut candle = serde_json::to_value(candle)?;
+                candle["pair"] = pair.into();
+                candle["interval"] = interval.to_string().into();
+                println!("{}", candle);
+            }
+        }
+    }
+
+    // TODO: write the events to the output once they serialize.
 
     Ok(())
 }
 
+/// An engine for every pair met in the requests, so that orders of different
+/// pairs never match. Deletes and modifies go to the pair their order was
+/// created on, and requests without any to the default pair.
+struct Markets {
+    pair: CompactString,
+    engines: BTreeMap<CompactString, Engine>,
+    /// Pair of the orders created on another pair than the default one, as
+    /// long as they rest.
+    orders: HashMap<OrderId, CompactString>,
+    /// Shared by the engines, so that trade ids are unique across pairs.
+    trade_ids: Arc<AtomicU64>,
+}
+
+impl Markets {
+    fn new(pair: &str) -> Self {
+        Self {
+            pair: pair.into(),
+            engines: BTreeMap::new(),
+            orders: HashMap::new(),
+            trade_ids: Arc::default(),
+        }
+    }
+
+    /// Return the engine of `pair`, starting it on first use.
+    fn engine(&mut self, pair: CompactString) -> &mut Engine {
+        let trade_ids = &self.trade_ids;
+        self.engines.entry(pair).or_insert_with_key(|pair| {
+            Engine::new(pair).with_trade_ids(Arc::clone(trade_ids))
+        })
+    }
+
+    /// Process `request` on the engine of its pair, or on every engine for
+    /// the requests which may concern several pairs. Binary requests go to
+    /// the default pair.
+    fn process(
+        &mut self,
+        request: Replayed,
+        events: &mut Vec<Event<Order>>,
+    ) -> std::result::Result<(), OrderRequestError> {
+        let request = match request {
+            Replayed::Request(request) => request,
+            Replayed::Binary(request) => {
+                let engine = self.engine(self.pair.clone());
+                // Like `try_process_into`, switch phases first.
+                engine.tick_into(events);
+                match request {
+                    Request::Create(order) => engine.create_into(order, events),
+                    Request::Cancel(order_id) => {
+                        engine.delete_into(order_id, events)
+                    }
+                    Request::Modify {
+                        order_id,
+                        limit_price,
+                        amount,
+                    } => engine.modify_into(
+                        order_id,
+                        limit_price,
+                        amount,
+                        events,
+                    ),
+                }
+                return Ok(());
+            }
+        };
+        // Malformed identifiers are left for the engine to refuse.
+        let parse = |order_id: &str| order_id.parse().map(OrderId::new).ok();
+        let pair = match &request {
+            OrderRequest::Create { order_id, pair, .. } => {
+                if let Some(order_id) = parse(order_id) {
+                    if *pair != self.pair {
+                        self.orders.insert(order_id, pair.clone());
+                    } else if !self.orders.is_empty() {
+                        self.orders.remove(&order_id);
+                    }
+                }
+                pair.clone()
+            }
+            OrderRequest::Delete { order_id }
+            | OrderRequest::Modify { order_id, .. } => parse(order_id)
+                .and_then(|order_id| self.orders.get(&order_id))
+                .unwrap_or(&self.pair)
+                .clone(),
+            OrderRequest::DeleteAll { .. } | OrderRequest::SetPhase { .. } => {
+                let pairs = self.engines.keys().cloned().collect::<Vec<_>>();
+                for pair in pairs {
+                    let start = events.len();
+                    self.engine(pair.clone())
+                        .try_process_into(request.clone(), events)?;
+                    self.prune(&pair, &events[start..]);
+                }
+                return Ok(());
+            }
+        };
+
+        let start = events.len();
+        self.engine(pair.clone())
+            .try_process_into(request, events)?;
+        self.prune(&pair, &events[start..]);
+        Ok(())
+    }
+
+    /// Forget the orders of `pair` which `events` took out of its book.
+    fn prune(&mut self, pair: &str, events: &[Event<Order>]) {
+        let Some(engine) = self.engines.get(pair) else {
+            return;
+        };
+        for event in events {
+            let order_ids = match event {
+                Event::Removed(order_id) | Event::Rejected(order_id, _) => {
+                    [Some(*order_id), None]
+                }
+                Event::Traded(trade) => {
+                    [Some(trade.taker()), Some(trade.maker())]
+                }
+                _ => continue,
+            };
+            for order_id in order_ids.into_iter().flatten() {
+                let left = engine.order(&order_id).is_none()
+                    && self
+                        .orders
+                        .get(&order_id)
+                        .is_some_and(|other| other.as_str() == pair);
+                if left {
+                    self.orders.remove(&order_id);
+                }
+            }
+        }
+    }
+}
+
+/// A request read from the source.
+enum Replayed {
+    Request(OrderRequest),
+    /// Decoded straight into engine types, and processed as such.
+    Binary(Request),
+}
+
+/// Which kind of request a latency is recorded for.
+#[derive(Clone, Copy)]
+enum Kind {
+    Create,
+    Delete,
+    Other,
+}
+
+impl Kind {
+    fn of(request: &Replayed) -> Self {
+        match request {
+            Replayed::Request(OrderRequest::Create { .. })
+            | Replayed::Binary(Request::Create(_)) => Kind::Create,
+            Replayed::Request(OrderRequest::Delete { .. })
+            | Replayed::Binary(Request::Cancel(_)) => Kind::Delete,
+            _ => Kind::Other,
+        }
+    }
+}
+
+/// Latency of every processed request, in nanoseconds, split by what the
+/// request did.
+struct Latencies {
+    histograms: [(&'static str, Histogram<u64>); 5],
+}
+
+impl Latencies {
+    fn new() -> Self {
+        // Up to a minute, with 3 significant digits.
+        let histogram = || {
+            Histogram::new_with_bounds(1, 60_000_000_000, 3)
+                .expect("valid histogram bounds")
+        };
+        Self {
+            histograms: [
+                ("create-passive", histogram()),
+                ("create-aggressive", histogram()),
+                ("delete", histogram()),
+                ("other", histogram()),
+                ("rejected", histogram()),
+            ],
+        }
+    }
+
+    /// Record a request of `kind` taking `elapsed` and emitting `events`.
+    /// Requests which were rejected are recorded apart, whatever their kind.
+    fn record(
+        &mut self,
+        kind: Kind,
+        events: &[Event<Order>],
+        elapsed: std::time::Duration,
+    ) {
+        let index = match kind {
+            _ if events
+                .iter()
+                .any(|event| matches!(event, Event::Rejected(..))) =>
+            {
+                4
+            }
+            Kind::Create
+                if events
+                    .iter()
+                    .any(|event| matches!(event, Event::Traded(_))) =>
+            {
+                1
+            }
+            Kind::Create => 0,
+            Kind::Delete => 2,
+            Kind::Other => 3,
+        };
+        self.histograms[index]
+            .1
+            .saturating_record(elapsed.as_nanos() as u64);
+    }
+
+    fn recorded(&self) -> impl Iterator<Item = &(&str, Histogram<u64>)> {
+        self.histograms
+            .iter()
+            .filter(|(_, histogram)| !histogram.is_empty())
+    }
+
+    fn summary(&self) {
+        eprintln!(
+            "  {:<18} {:>10} {:>8} {:>8} {:>8} {:>8} {:>10}",
+            "", "count", "p50", "p90", "p99", "p99.9", "max"
+        );
+        for (name, histogram) in self.recorded() {
+            eprintln!(
+                "  {:<18} {:>10} {:>8} {:>8} {:>8} {:>8} {:>10}",
+                name,
+                histogram.len(),
+                histogram.value_at_quantile(0.5),
+                histogram.value_at_quantile(0.9),
+                histogram.value_at_quantile(0.99),
+                histogram.value_at_quantile(0.999),
+                histogram.max(),
+            );
+        }
+    }
+
+    /// Write the percentile distribution of each kind of request into
+    /// `<kind>.hgrm` in `dir`.
+    fn write(&self, dir: &Path) -> Result<()> {
+        std::fs::create_dir_all(dir)?;
+        for (name, histogram) in self.recorded() {
+            std::fs::write(
+                dir.join(format!("{name}.hgrm")),
+                distribution(histogram),
+            )?;
+        }
+        Ok(())
+    }
+}
+
+/// Return the percentile distribution of `histogram`, in the text format
+/// HdrHistogram plotters read.
+fn distribution(histogram: &Histogram<u64>) -> String {
+    let mut distribution = String::new();
+    let _ = writeln!(
+        distribution,
+        "{:>12} {:>14} {:>10} {:>14}",
+        "Value", "Percentile", "TotalCount", "1/(1-Percentile)"
+    );
+    let mut count = 0;
+    for value in histogram.iter_quantiles(1) {
+        count += value.count_since_last_iteration();
+        let quantile = value.quantile_iterated_to();
+        let inverse = match quantile < 1.0 {
+            true => format!("{:.2}", 1.0 / (1.0 - quantile)),
+            false => String::new(),
+        };
+        let _ = writeln!(
+            distribution,
+            "{:>12} {:>14.12} {:>10} {:>14}",
+            value.value_iterated_to(),
+            quantile,
+            count,
+            inverse
+        );
+    }
+    let _ = writeln!(
+        distribution,
+        "#[Mean = {:.2}, StdDeviation = {:.2}]",
+        histogram.mean(),
+        histogram.stdev()
+    );
+    let _ = writeln!(
+        distribution,
+        "#[Max = {}, Total count = {}]",
+        histogram.max(),
+        histogram.len()
+    );
+    distribution
+}
+
+#[derive(Clone, ArgEnum)]
+enum Format {
+    Json,
+    Jsonl,
+    Binary,
+}
+
 #[derive(Debug, Default)]
 enum Input {
     #[default]
@@ -0,0 +1,90 @@
+use std::collections::BTreeMap;
+
+use crate::engine::{Event, Order, Orderbook, Trade};
+use crate::OrderSide;
+
+/// Price and total amount resting at that price.
+pub type Level = (u64, u64);
+
+/// Aggregated depth of both sides of an orderbook.
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct Depth {
+    asks: BTreeMap<u64, u64>,
+    bids: BTreeMap<u64, u64>,
+}
+
+impl Depth {
+    /// Take the full depth of `orderbook`.
+    pub fn new(orderbook: &Orderbook<Order, Event<Order>, Trade>) -> Self {
+        Self {
+            asks: orderbook
+                .depth(OrderSide::Ask, usize::MAX)
+                .into_iter()
+                .collect(),
+            bids: orderbook
+                .depth(OrderSide::Bid, usize::MAX)
+                .into_iter()
+                .collect(),
+        }
+    }
+
+    /// Set the amount resting at `price` on `side`, a zero amount removing
+    /// the level. Return whether the level changed.
+    pub fn set(&mut self, side: OrderSide, price: u64, amount: u64) -> bool {
+        let levels = match side {
+            OrderSide::Ask => &mut self.asks,
+            OrderSide::Bid => &mut self.bids,
+        };
+        let previous = match amount {
+            0 => levels.remove(&price),
+            _ => levels.insert(price, amount),
+        };
+        previous.unwrap_or(0) != amount
+    }
+
+    /// Ask levels, from the best one.
+    #[inline]
+    pub fn asks(&self) -> Vec<Level> {
+        self.asks
+            .iter()
+            .map(|(price, amount)| (*price, *amount))
+            .collect()
+    }
+
+    /// Bid levels, from the best one.
+    #[inline]
+    pub fn bids(&self) -> Vec<Level> {
+        self.bids
+            .iter()
+            .rev()
+            .map(|(price, amount)| (*price, *amount))
+            .collect()
+    }
+
+    /// Return the ask and bid levels which changed from `self` to `other`.
+    /// Levels which were emptied are reported with a zero amount.
+    pub fn diff(&self, other: &Depth) -> (Vec<Level>, Vec<Level>) {
+        #[inline(always)]
+        fn changes(
+            before: &BTreeMap<u64, u64>,
+            after: &BTreeMap<u64, u64>,
+        ) -> Vec<Level> {
+            let removed = before
+                .keys()
+                .filter(|price| !after.contains_key(price))
+                .map(|price| (*price, 0));
+            let changed = after
+                .iter()
+                .filter(|(price, amount)| before.get(price) != Some(amount))
+                .map(|(price, amount)| (*price, *amount));
+
+            let mut changes = removed.chain(changed).collect::<Vec<_>>();
+            changes.sort_unstable();
+            changes
+        }
+
+        let mut bids = changes(&self.bids, &other.bids);
+        bids.reverse();
+        (changes(&self.asks, &other.asks), bids)
+    }
+}
@@ -0,0 +1,33 @@
+use compact_str::CompactString;
+use serde::{Deserialize, Serialize};
+
+use super::Level;
+use crate::engine::PublicTrade;
+
+/// Message published to market data clients.
+#[derive(Debug, Serialize)]
+#[serde(tag = "type", rename_all = "UPPERCASE")]
+pub enum MarketDataMessage {
+    /// Every level of the book, sent upon subscription.
+    Snapshot {
+        pair: CompactString,
+        asks: Vec<Level>,
+        bids: Vec<Level>,
+    },
+    /// Levels which changed since the previous message; a zero amount means
+    /// the level is gone.
+    Update {
+        pair: CompactString,
+        asks: Vec<Level>,
+        bids: Vec<Level>,
+    },
+    Trade(PublicTrade),
+}
+
+/// Message sent by market data clients.
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(tag = "type", rename_all = "UPPERCASE")]
+pub enum Subscription {
+    Subscribe { pair: CompactString },
+    Unsubscribe { pair: CompactString },
+}
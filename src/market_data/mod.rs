@@ -0,0 +1,13 @@
+//! Level 2 market data: depth snapshots, incremental depth updates and the
+//! public trade stream, optionally served over WebSocket.
+
+mod depth;
+pub use depth::{Depth, Level};
+
+mod message;
+pub use message::{MarketDataMessage, Subscription};
+
+#[cfg(feature = "websocket")]
+mod websocket;
+#[cfg(feature = "websocket")]
+pub use websocket::{MarketDataServer, Publisher};
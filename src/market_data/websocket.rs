@@ -0,0 +1,326 @@
+use std::collections::{HashMap, HashSet};
+use std::io;
+use std::net::{SocketAddr, TcpListener, TcpStream, ToSocketAddrs};
+use std::sync::mpsc::{self, Receiver, SyncSender, TryRecvError, TrySendError};
+use std::sync::{Arc, Mutex, PoisonError};
+use std::thread;
+use std::time::Duration;
+
+use compact_str::CompactString;
+use tungstenite::{Message, WebSocket};
+
+use super::{Depth, MarketDataMessage, Subscription};
+use crate::engine::{Engine, Event, Order, OrderId};
+use crate::server::MAX_PENDING_MESSAGES;
+use crate::{Asset, OrderSide};
+
+/// How long a client thread waits for an incoming message before flushing
+/// what was published in the meantime.
+const POLL_INTERVAL: Duration = Duration::from_millis(5);
+
+/// Serves market data to WebSocket clients, which subscribe to pairs with
+/// [`Subscription`] messages and receive [`MarketDataMessage`]s.
+pub struct MarketDataServer {
+    listener: TcpListener,
+    state: Arc<Mutex<State>>,
+}
+
+/// Feeds a [`MarketDataServer`] from an [`Engine`].
+#[derive(Clone)]
+pub struct Publisher {
+    state: Arc<Mutex<State>>,
+}
+
+#[derive(Default)]
+struct State {
+    books: HashMap<CompactString, Book>,
+    clients: HashMap<usize, Client>,
+    next_client: usize,
+}
+
+struct Client {
+    /// Bounded like the queues of the order gateway. Dropping it makes the
+    /// client thread hang up.
+    sender: SyncSender<Arc<str>>,
+    pairs: HashSet<CompactString>,
+}
+
+/// Depth of a pair as last published, along with where each of its orders
+/// rests, so that the levels an event touched are known once the order left.
+#[derive(Default)]
+struct Book {
+    depth: Depth,
+    orders: HashMap<OrderId, (OrderSide, u64)>,
+}
+
+impl Book {
+    fn new(engine: &Engine) -> Self {
+        let orderbook = engine.orderbook();
+        Self {
+            depth: Depth::new(orderbook),
+            orders: orderbook
+                .iter()
+                .map(|order| (order.id(), (order.side(), order.limit_price())))
+                .collect(),
+        }
+    }
+
+    /// Follow `order_id` to where it rests now, recording the levels it
+    /// left and joined into `touched`.
+    fn track(
+        &mut self,
+        engine: &Engine,
+        order_id: OrderId,
+        touched: &mut Vec<(OrderSide, u64)>,
+    ) {
+        touched.extend(self.orders.remove(&order_id));
+        if let Some(order) = engine.order(&order_id) {
+            let level = (order.side(), order.limit_price());
+            self.orders.insert(order_id, level);
+            touched.push(level);
+        }
+    }
+}
+
+impl State {
+    fn broadcast(&mut self, pair: &str, message: &MarketDataMessage) {
+        let payload: Arc<str> = serde_json::to_string(message)
+            .expect("market data is serializable")
+            .into();
+        // A client too slow to keep up is dropped, rather than buffering
+        // without limit.
+        self.clients.retain(|_, client| {
+            !client.pairs.contains(pair)
+                || !matches!(
+                    client.sender.try_send(Arc::clone(&payload)),
+                    Err(TrySendError::Full(_))
+                )
+        });
+    }
+}
+
+impl MarketDataServer {
+    pub fn bind(addr: impl ToSocketAddrs) -> io::Result<(Self, Publisher)> {
+        let state = Arc::new(Mutex::new(State::default()));
+        let server = Self {
+            listener: TcpListener::bind(addr)?,
+            state: Arc::clone(&state),
+        };
+
+        Ok((server, Publisher { state }))
+    }
+
+    #[inline]
+    pub fn local_addr(&self) -> io::Result<SocketAddr> {
+        self.listener.local_addr()
+    }
+
+    /// Accept clients until the listener fails.
+    pub fn run(self) -> io::Result<()> {
+        for stream in self.listener.incoming() {
+            let stream = stream?;
+            let state = Arc::clone(&self.state);
+            thread::spawn(move || serve(state, stream));
+        }
+
+        Ok(())
+    }
+}
+
+impl Publisher {
+    /// Take the depth of `engine`'s book, which clients subscribing before
+    /// anything is published are sent.
+    pub fn attach(&self, engine: &Engine) {
+        let pair = CompactString::new(engine.orderbook().pair());
+        self.state
+            .lock()
+            .unwrap_or_else(PoisonError::into_inner)
+            .books
+            .insert(pair, Book::new(engine));
+    }
+
+    /// Publish the trades among `events` and the depth changes they caused
+    /// to `engine`'s book.
+    ///
+    /// Only the levels the events touched are looked at. A book which was
+    /// not attached is taken whole, and sent as a snapshot to its
+    /// subscribers.
+    pub fn publish(&self, engine: &Engine, events: &[Event<Order>]) {
+        let pair = CompactString::new(engine.orderbook().pair());
+        let mut state =
+            self.state.lock().unwrap_or_else(PoisonError::into_inner);
+
+        for event in events {
+            if let Event::Traded(trade) = event {
+                let trade = MarketDataMessage::Trade(trade.into());
+                state.broadcast(&pair, &trade);
+            }
+        }
+
+        let Some(book) = state.books.get_mut(&pair) else {
+            let book = Book::new(engine);
+            let snapshot = MarketDataMessage::Snapshot {
+                pair: pair.clone(),
+                asks: book.depth.asks(),
+                bids: book.depth.bids(),
+            };
+            state.broadcast(&pair, &snapshot);
+            state.books.insert(pair, book);
+            return;
+        };
+
+        let mut touched = Vec::new();
+        for event in events {
+            match event {
+                Event::Added(order_id)
+                | Event::Removed(order_id)
+                | Event::Modified(order_id) => {
+                    book.track(engine, *order_id, &mut touched)
+                }
+                Event::Traded(trade) => {
+                    book.track(engine, trade.taker(), &mut touched);
+                    book.track(engine, trade.maker(), &mut touched);
+                }
+                Event::Rejected(..)
+                | Event::Indicative(_)
+                | Event::Phase(_) => {}
+            }
+        }
+        touched.sort_unstable_by_key(|&(side, price)| {
+            (side == OrderSide::Bid, price)
+        });
+        touched.dedup();
+
+        let (mut asks, mut bids) = (Vec::new(), Vec::new());
+        for (side, price) in touched {
+            let amount = engine.orderbook().depth_at(side, price);
+            if book.depth.set(side, price, amount) {
+                match side {
+                    OrderSide::Ask => asks.push((price, amount)),
+                    OrderSide::Bid => bids.push((price, amount)),
+                }
+            }
+        }
+        // Bids go from the best one, like in snapshots.
+        bids.reverse();
+        if !asks.is_empty() || !bids.is_empty() {
+            state.broadcast(
+                &pair,
+                &MarketDataMessage::Update {
+                    pair: pair.clone(),
+                    asks,
+                    bids,
+                },
+            );
+        }
+    }
+}
+
+fn serve(state: Arc<Mutex<State>>, stream: TcpStream) {
+    let mut socket = match tungstenite::accept(stream) {
+        Ok(socket) => socket,
+        Err(_) => return,
+    };
+    if socket
+        .get_ref()
+        .set_read_timeout(Some(POLL_INTERVAL))
+        .is_err()
+    {
+        return;
+    }
+
+    let (sender, outgoing) = mpsc::sync_channel(MAX_PENDING_MESSAGES);
+    let client_id = {
+        let mut state = state.lock().unwrap_or_else(PoisonError::into_inner);
+        let client_id = state.next_client;
+        state.next_client += 1;
+        state.clients.insert(
+            client_id,
+            Client {
+                sender,
+                pairs: HashSet::new(),
+            },
+        );
+        client_id
+    };
+
+    poll(&state, client_id, &mut socket, &outgoing);
+
+    state
+        .lock()
+        .unwrap_or_else(PoisonError::into_inner)
+        .clients
+        .remove(&client_id);
+}
+
+fn poll(
+    state: &Mutex<State>,
+    client_id: usize,
+    socket: &mut WebSocket<TcpStream>,
+    outgoing: &Receiver<Arc<str>>,
+) {
+    // Any error means the client went away.
+    loop {
+        loop {
+            match outgoing.try_recv() {
+                Ok(payload) => {
+                    if socket.send(Message::Text(payload.to_string())).is_err()
+                    {
+                        return;
+                    }
+                }
+                Err(TryRecvError::Empty) => break,
+                // The client was dropped for falling behind.
+                Err(TryRecvError::Disconnected) => return,
+            }
+        }
+
+        let text = match socket.read() {
+            Ok(Message::Text(text)) => text,
+            Ok(Message::Close(_)) => return,
+            Ok(_) => continue,
+            Err(tungstenite::Error::Io(err))
+                if matches!(
+                    err.kind(),
+                    io::ErrorKind::WouldBlock | io::ErrorKind::TimedOut
+                ) =>
+            {
+                continue
+            }
+            Err(_) => return,
+        };
+
+        let mut state = state.lock().unwrap_or_else(PoisonError::into_inner);
+        match serde_json::from_str::<Subscription>(&text) {
+            Ok(Subscription::Subscribe { pair }) => {
+                // The snapshot is queued while holding the lock, so no update
+                // can slip in between.
+                let empty = Depth::default();
+                let depth =
+                    state.books.get(&pair).map_or(&empty, |book| &book.depth);
+                let snapshot = MarketDataMessage::Snapshot {
+                    pair: pair.clone(),
+                    asks: depth.asks(),
+                    bids: depth.bids(),
+                };
+                let payload = serde_json::to_string(&snapshot)
+                    .expect("market data is serializable")
+                    .into();
+                let Some(client) = state.clients.get_mut(&client_id) else {
+                    return;
+                };
+                if client.sender.try_send(payload).is_err() {
+                    state.clients.remove(&client_id);
+                    return;
+                }
+                client.pairs.insert(pair);
+            }
+            Ok(Subscription::Unsubscribe { pair }) => {
+                if let Some(client) = state.clients.get_mut(&client_id) {
+                    client.pairs.remove(&pair);
+                }
+            }
+            Err(_) => (),
+        }
+    }
+}
@@ -0,0 +1,45 @@
+use std::io::{self, Read, Write};
+
+/// Largest frame accepted, so a bogus length cannot exhaust memory.
+pub const MAX_FRAME_LENGTH: usize = 1 << 20;
+
+/// Read a frame prefixed by its big-endian `u32` length. Return `Ok(None)`
+/// once the stream is closed between frames.
+pub fn read_frame(reader: &mut impl Read) -> io::Result<Option<Vec<u8>>> {
+    let mut length = [0; 4];
+    match reader.read_exact(&mut length) {
+        Ok(()) => (),
+        Err(err) if err.kind() == io::ErrorKind::UnexpectedEof => {
+            return Ok(None)
+        }
+        Err(err) => return Err(err),
+    }
+
+    let length = u32::from_be_bytes(length) as usize;
+    if length > MAX_FRAME_LENGTH {
+        return Err(io::Error::new(
+            io::ErrorKind::InvalidData,
+            "frame exceeds the maximum length",
+        ));
+    }
+
+    let mut frame = vec![0; length];
+    reader.read_exact(&mut frame)?;
+    Ok(Some(frame))
+}
+
+/// Write `payload` prefixed by its big-endian `u32` length.
+pub fn write_frame(writer: &mut impl Write, payload: &[u8]) -> io::Result<()> {
+    let length = u32::try_from(payload.len())
+        .ok()
+        .filter(|length| *length as usize <= MAX_FRAME_LENGTH)
+        .ok_or_else(|| {
+            io::Error::new(
+                io::ErrorKind::InvalidInput,
+                "frame exceeds the maximum length",
+            )
+        })?;
+
+    writer.write_all(&length.to_be_bytes())?;
+    writer.write_all(payload)
+}
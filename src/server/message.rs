@@ -0,0 +1,35 @@
+use serde::{Deserialize, Serialize};
+
+use crate::engine::{ExecutionReport, OrderRequest, PublicTrade};
+
+/// Message sent by a client session.
+#[derive(Debug, Serialize, Deserialize)]
+#[serde(untagged)]
+pub enum ClientMessage {
+    Order(OrderRequest),
+    Control(Control),
+}
+
+/// Session control message.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(tag = "type_op", rename_all = "UPPERCASE")]
+pub enum Control {
+    /// Start receiving the public trade stream.
+    Subscribe,
+    /// Stop receiving the public trade stream.
+    Unsubscribe,
+}
+
+/// Message sent to a client session.
+#[derive(Debug, Serialize, Deserialize)]
+#[serde(tag = "type", rename_all = "UPPERCASE")]
+pub enum ServerMessage {
+    /// Execution report about an order owned by the session.
+    Report(ExecutionReport),
+    /// Public trade, sent to subscribed sessions.
+    Trade(PublicTrade),
+    /// Acknowledges a control message.
+    Control(Control),
+    /// The last message of the session could not be processed.
+    Error { message: String },
+}
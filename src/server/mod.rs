@@ -0,0 +1,346 @@
+//! TCP order gateway.
+//!
+//! Clients exchange JSON messages framed by their big-endian `u32` length.
+//! Sessions send [`ClientMessage`]s and receive [`ServerMessage`]s: the
+//! execution reports about the orders they own, and, once subscribed, the
+//! public trade stream.
+//!
+//! An account belongs to the first session placing an order for it: only
+//! that session may place orders for it or cancel them all at once. Orders
+//! may only be cancelled or replaced by the session which placed them.
+//!
+//! Once a session is gone, its accounts are free again and its orders rest
+//! without an owner: a new session taking the account over may cancel them
+//! all at once.
+
+use std::collections::{HashMap, HashSet};
+use std::io;
+use std::net::{SocketAddr, TcpListener, TcpStream, ToSocketAddrs};
+use std::sync::mpsc::{self, Receiver, Sender, SyncSender, TrySendError};
+use std::thread;
+
+use crate::engine::{
+    parse_id, Engine, Event, ExecType, ExecutionReport, Order, OrderId,
+    OrderRequest, OrderStatus, PublicTrade, RejectReason, ReportGenerator,
+};
+#[cfg(feature = "websocket")]
+use crate::market_data::Publisher;
+
+mod frame;
+pub use frame::{read_frame, write_frame, MAX_FRAME_LENGTH};
+
+mod message;
+pub use message::{ClientMessage, Control, ServerMessage};
+
+/// Messages queued for a session before it is disconnected for not reading
+/// them.
+pub const MAX_PENDING_MESSAGES: usize = 4096;
+
+/// Runs requests from many sessions through a single [`Engine`], in arrival
+/// order.
+///
+/// Each session has a reader and a writer thread, while a dedicated thread
+/// owns the engine, so a slow client never holds back matching.
+pub struct Server {
+    listener: TcpListener,
+    engine: Engine,
+    #[cfg(feature = "websocket")]
+    publisher: Option<Publisher>,
+}
+
+enum Command {
+    Connect(usize, SyncSender<Vec<u8>>),
+    Message(usize, ClientMessage),
+    Invalid(usize, String),
+    Disconnect(usize),
+}
+
+impl Server {
+    pub fn bind(addr: impl ToSocketAddrs, engine: Engine) -> io::Result<Self> {
+        Ok(Self {
+            listener: TcpListener::bind(addr)?,
+            engine,
+            #[cfg(feature = "websocket")]
+            publisher: None,
+        })
+    }
+
+    /// Publish the market data of the engine through `publisher`.
+    #[cfg(feature = "websocket")]
+    #[inline]
+    pub fn with_market_data(mut self, publisher: Publisher) -> Self {
+        publisher.attach(&self.engine);
+        self.publisher = Some(publisher);
+        self
+    }
+
+    #[inline]
+    pub fn local_addr(&self) -> io::Result<SocketAddr> {
+        self.listener.local_addr()
+    }
+
+    /// Accept sessions until the listener fails.
+    pub fn run(self) -> io::Result<()> {
+        let (commands, receiver) = mpsc::channel();
+        let mut dispatcher = Dispatcher {
+            engine: self.engine,
+            reports: ReportGenerator::new(),
+            owners: HashMap::new(),
+            accounts: HashMap::new(),
+            sessions: HashMap::new(),
+            subscribers: HashSet::new(),
+            #[cfg(feature = "websocket")]
+            publisher: self.publisher,
+        };
+        thread::spawn(move || dispatcher.run(receiver));
+
+        for (session_id, stream) in self.listener.incoming().enumerate() {
+            let stream = stream?;
+            let writer = stream.try_clone()?;
+            let (sender, outgoing) = mpsc::sync_channel(MAX_PENDING_MESSAGES);
+            if commands.send(Command::Connect(session_id, sender)).is_err() {
+                break;
+            }
+
+            thread::spawn(move || write_session(writer, outgoing));
+            let commands = commands.clone();
+            thread::spawn(move || read_session(session_id, stream, commands));
+        }
+
+        Ok(())
+    }
+}
+
+fn read_session(
+    session_id: usize,
+    mut stream: TcpStream,
+    commands: Sender<Command>,
+) {
+    while let Ok(Some(frame)) = read_frame(&mut stream) {
+        let command = match serde_json::from_slice(&frame) {
+            Ok(message) => Command::Message(session_id, message),
+            Err(err) => Command::Invalid(session_id, err.to_string()),
+        };
+        if commands.send(command).is_err() {
+            return;
+        }
+    }
+
+    let _ = commands.send(Command::Disconnect(session_id));
+}
+
+fn write_session(mut stream: TcpStream, outgoing: Receiver<Vec<u8>>) {
+    for payload in outgoing {
+        if write_frame(&mut stream, &payload).is_err() {
+            break;
+        }
+    }
+
+    let _ = stream.shutdown(std::net::Shutdown::Both);
+}
+
+struct Dispatcher {
+    engine: Engine,
+    reports: ReportGenerator,
+    owners: HashMap<OrderId, usize>,
+    accounts: HashMap<u64, usize>,
+    sessions: HashMap<usize, SyncSender<Vec<u8>>>,
+    subscribers: HashSet<usize>,
+    #[cfg(feature = "websocket")]
+    publisher: Option<Publisher>,
+}
+
+impl Dispatcher {
+    fn run(&mut self, commands: Receiver<Command>) {
+        for command in commands {
+            match command {
+                Command::Connect(session_id, sender) => {
+                    self.sessions.insert(session_id, sender);
+                }
+                // Sessions dropped for not reading their messages are
+                // ignored until their reader notices.
+                Command::Message(session_id, _)
+                    if !self.sessions.contains_key(&session_id) => {}
+                Command::Message(session_id, ClientMessage::Order(request)) => {
+                    self.process(session_id, request)
+                }
+                Command::Message(
+                    session_id,
+                    ClientMessage::Control(control),
+                ) => {
+                    match control {
+                        Control::Subscribe => {
+                            self.subscribers.insert(session_id);
+                        }
+                        Control::Unsubscribe => {
+                            self.subscribers.remove(&session_id);
+                        }
+                    }
+                    self.send(session_id, &ServerMessage::Control(control));
+                }
+                Command::Invalid(session_id, message) => {
+                    self.send(session_id, &ServerMessage::Error { message });
+                }
+                Command::Disconnect(session_id) => self.disconnect(session_id),
+            }
+        }
+    }
+
+    fn process(&mut self, session_id: usize, request: OrderRequest) {
+        // Malformed identifiers are left for the engine to refuse.
+        match &request {
+            OrderRequest::Create { account_id, .. } => {
+                if let Ok(account_id) = parse_id(account_id) {
+                    let owner =
+                        *self.accounts.entry(account_id).or_insert(session_id);
+                    if owner != session_id {
+                        let message =
+                            format!("account {account_id} is not yours");
+                        self.send(
+                            session_id,
+                            &ServerMessage::Error { message },
+                        );
+                        return;
+                    }
+                }
+            }
+            OrderRequest::Delete { order_id }
+            | OrderRequest::Modify { order_id, .. } => {
+                if let Ok(order_id) = parse_id(order_id).map(OrderId::new) {
+                    // The orders of other sessions look unknown.
+                    if self.owners.get(&order_id) != Some(&session_id) {
+                        let report = ExecutionReport::rejected(
+                            order_id,
+                            RejectReason::UnknownOrder,
+                        );
+                        self.send(session_id, &ServerMessage::Report(report));
+                        return;
+                    }
+                }
+            }
+            OrderRequest::DeleteAll { account_id, .. } => {
+                if let Ok(account_id) = parse_id(account_id) {
+                    if self.accounts.get(&account_id) != Some(&session_id) {
+                        let message =
+                            format!("account {account_id} is not yours");
+                        self.send(
+                            session_id,
+                            &ServerMessage::Error { message },
+                        );
+                        return;
+                    }
+                }
+            }
+            OrderRequest::SetPhase { .. } => {
+                let message = String::from(
+                    "phase changes are not accepted from sessions",
+                );
+                self.send(session_id, &ServerMessage::Error { message });
+                return;
+            }
+        }
+
+        // The phase switches due by now are reported on apart, as the
+        // reports of the request only follow from its own events.
+        let mut events = self.engine.tick();
+        let mut reports = self.reports.reports(&events);
+        let ticked = events.len();
+        match self.engine.try_process_into(request.clone(), &mut events) {
+            Ok(()) => {
+                self.own(session_id, &request, &events[ticked..]);
+                reports
+                    .extend(self.reports.generate(&request, &events[ticked..]))
+            }
+            Err(err) => {
+                let message = err.to_string();
+                self.send(session_id, &ServerMessage::Error { message });
+            }
+        }
+
+        for report in reports {
+            let owner = match report.exec_type() {
+                ExecType::Rejected => Some(session_id),
+                _ => self.owners.get(&report.order_id()).copied(),
+            };
+            if matches!(
+                report.order_status(),
+                OrderStatus::Completed
+                    | OrderStatus::Cancelled
+                    | OrderStatus::Closed
+            ) {
+                self.owners.remove(&report.order_id());
+            }
+
+            if let Some(owner) = owner {
+                self.send(owner, &ServerMessage::Report(report));
+            }
+        }
+
+        #[cfg(feature = "websocket")]
+        if let Some(publisher) = &self.publisher {
+            publisher.publish(&self.engine, &events);
+        }
+
+        for event in events {
+            if let Event::Traded(trade) = event {
+                let trade = PublicTrade::from(&trade);
+                let payload = serde_json::to_vec(&ServerMessage::Trade(trade))
+                    .expect("trades are serializable");
+                let subscribers =
+                    self.subscribers.iter().copied().collect::<Vec<_>>();
+                for subscriber in subscribers {
+                    self.deliver(subscriber, payload.clone());
+                }
+            }
+        }
+    }
+
+    #[inline]
+    fn send(&mut self, session_id: usize, message: &ServerMessage) {
+        let payload =
+            serde_json::to_vec(message).expect("messages are serializable");
+        self.deliver(session_id, payload);
+    }
+
+    fn deliver(&mut self, session_id: usize, payload: Vec<u8>) {
+        let Some(session) = self.sessions.get(&session_id) else {
+            return;
+        };
+        match session.try_send(payload) {
+            // A closed session is cleaned up once its reader notices.
+            Ok(()) | Err(TrySendError::Disconnected(_)) => {}
+            // Dropping the sender stops the writer, which closes the stream.
+            Err(TrySendError::Full(_)) => self.disconnect(session_id),
+        }
+    }
+
+    /// Make `session_id` the owner of the order `request` created, unless
+    /// `events` rejected it, so that a duplicate does not steal the live
+    /// order from its owner.
+    fn own(
+        &mut self,
+        session_id: usize,
+        request: &OrderRequest,
+        events: &[Event<Order>],
+    ) {
+        let OrderRequest::Create { order_id, .. } = request else {
+            return;
+        };
+        let Ok(order_id) = parse_id(order_id).map(OrderId::new) else {
+            return;
+        };
+        if !events.iter().any(
+            |event| matches!(event, Event::Rejected(id, _) if *id == order_id),
+        ) {
+            self.owners.insert(order_id, session_id);
+        }
+    }
+
+    /// Forget `session_id`, releasing its accounts and its orders.
+    fn disconnect(&mut self, session_id: usize) {
+        self.sessions.remove(&session_id);
+        self.subscribers.remove(&session_id);
+        self.accounts.retain(|_, owner| *owner != session_id);
+        self.owners.retain(|_, owner| *owner != session_id);
+    }
+}
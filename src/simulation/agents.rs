@@ -0,0 +1,266 @@
+use compact_str::CompactString;
+use rand::rngs::StdRng;
+use rand::Rng;
+
+use super::{Action, Agent, View};
+use crate::engine::{Event, Order};
+use crate::{Asset, OrderSide};
+
+/// Trader without information, placing orders of random sides, amounts and
+/// prices around the mid, and cancelling some of them.
+pub struct NoiseTrader {
+    name: CompactString,
+    rate: f64,
+    reference: u64,
+    width: u64,
+    amount: u64,
+    cancel_ratio: f64,
+}
+
+impl NoiseTrader {
+    /// Create a trader arriving `rate` times per second, pricing around
+    /// `reference` until the book has a mid.
+    #[inline]
+    pub fn new(name: &str, rate: f64, reference: u64) -> Self {
+        Self {
+            name: name.into(),
+            rate,
+            reference,
+            width: (reference / 100).max(1),
+            amount: 100,
+            cancel_ratio: 0.5,
+        }
+    }
+
+    /// Draw prices up to `width` away from the mid, on either side, instead
+    /// of one percent of the reference.
+    #[inline]
+    pub fn with_width(mut self, width: u64) -> Self {
+        self.width = width;
+        self
+    }
+
+    /// Draw amounts up to `amount` instead of one unit.
+    #[inline]
+    pub fn with_amount(mut self, amount: u64) -> Self {
+        self.amount = amount.max(1);
+        self
+    }
+
+    /// Cancel one of the resting orders on this share of the arrivals
+    /// instead of half of them.
+    #[inline]
+    pub fn with_cancel_ratio(mut self, cancel_ratio: f64) -> Self {
+        self.cancel_ratio = cancel_ratio.clamp(0.0, 1.0);
+        self
+    }
+}
+
+impl Agent for NoiseTrader {
+    #[inline]
+    fn name(&self) -> &str {
+        &self.name
+    }
+
+    #[inline]
+    fn rate(&self) -> f64 {
+        self.rate
+    }
+
+    fn act(&mut self, view: &View<'_>, rng: &mut StdRng) -> Vec<Action> {
+        let mut actions = Vec::with_capacity(2);
+        if rng.gen_bool(self.cancel_ratio) {
+            let orders = view.open_orders().map(Asset::id).collect::<Vec<_>>();
+            if !orders.is_empty() {
+                let order_id = orders[rng.gen_range(0..orders.len())];
+                actions.push(Action::Cancel(order_id));
+            }
+        }
+
+        let mid = view.mid().or(view.last_price()).unwrap_or(self.reference);
+        let offset = rng.gen_range(0..=self.width);
+        let price = match rng.gen_bool(0.5) {
+            true => mid.saturating_add(offset),
+            false => mid.saturating_sub(offset).max(1),
+        };
+        actions.push(Action::Limit {
+            side: match rng.gen_bool(0.5) {
+                true => OrderSide::Ask,
+                false => OrderSide::Bid,
+            },
+            price,
+            amount: rng.gen_range(1..=self.amount),
+        });
+        actions
+    }
+}
+
+/// Liquidity provider replacing a bid and an ask around the mid on every
+/// arrival.
+pub struct MarketMaker {
+    name: CompactString,
+    rate: f64,
+    reference: u64,
+    half_spread: u64,
+    size: u64,
+    skew: u64,
+}
+
+impl MarketMaker {
+    /// Create a market maker arriving `rate` times per second, quoting
+    /// around `reference` until the book has a mid.
+    #[inline]
+    pub fn new(name: &str, rate: f64, reference: u64) -> Self {
+        let half_spread = (reference / 2_000).max(1);
+        Self {
+            name: name.into(),
+            rate,
+            reference,
+            half_spread,
+            size: 500,
+            skew: 0,
+        }
+    }
+
+    /// Quote `half_spread` away from the mid instead of 5 basis points of
+    /// the reference.
+    #[inline]
+    pub fn with_half_spread(mut self, half_spread: u64) -> Self {
+        self.half_spread = half_spread;
+        self
+    }
+
+    /// Quote `size` on each side instead of five units.
+    #[inline]
+    pub fn with_size(mut self, size: u64) -> Self {
+        self.size = size.max(1);
+        self
+    }
+
+    /// Shift the quotes against the position by `skew` for each `size` held,
+    /// to shed inventory.
+    #[inline]
+    pub fn with_skew(mut self, skew: u64) -> Self {
+        self.skew = skew;
+        self
+    }
+}
+
+impl Agent for MarketMaker {
+    #[inline]
+    fn name(&self) -> &str {
+        &self.name
+    }
+
+    #[inline]
+    fn rate(&self) -> f64 {
+        self.rate
+    }
+
+    fn act(&mut self, view: &View<'_>, _rng: &mut StdRng) -> Vec<Action> {
+        let mut actions = view
+            .open_orders()
+            .map(|order| Action::Cancel(order.id()))
+            .collect::<Vec<_>>();
+
+        let mid = view.mid().or(view.last_price()).unwrap_or(self.reference);
+        let shift =
+            view.position() * i128::from(self.skew) / i128::from(self.size);
+        let mid = (i128::from(mid) - shift).clamp(1, i128::from(u64::MAX));
+        let mid = mid as u64;
+        actions.push(Action::Limit {
+            side: OrderSide::Bid,
+            price: mid.saturating_sub(self.half_spread).max(1),
+            amount: self.size,
+        });
+        actions.push(Action::Limit {
+            side: OrderSide::Ask,
+            price: mid.saturating_add(self.half_spread),
+            amount: self.size,
+        });
+        actions
+    }
+}
+
+/// Taker following the trend, buying the best ask once the price rose by
+/// more than a threshold since it last traded, and selling the best bid once
+/// it fell as much. What it could not take is cancelled on its next arrival.
+pub struct MomentumTaker {
+    name: CompactString,
+    rate: f64,
+    threshold: u64,
+    amount: u64,
+    anchor: Option<u64>,
+}
+
+impl MomentumTaker {
+    /// Create a taker arriving `rate` times per second, trading on moves
+    /// larger than `threshold`.
+    #[inline]
+    pub fn new(name: &str, rate: f64, threshold: u64) -> Self {
+        Self {
+            name: name.into(),
+            rate,
+            threshold,
+            amount: 200,
+            anchor: None,
+        }
+    }
+
+    /// Take `amount` on each signal instead of two units.
+    #[inline]
+    pub fn with_amount(mut self, amount: u64) -> Self {
+        self.amount = amount.max(1);
+        self
+    }
+}
+
+impl Agent for MomentumTaker {
+    #[inline]
+    fn name(&self) -> &str {
+        &self.name
+    }
+
+    #[inline]
+    fn rate(&self) -> f64 {
+        self.rate
+    }
+
+    fn act(&mut self, view: &View<'_>, _rng: &mut StdRng) -> Vec<Action> {
+        let mut actions = view
+            .open_orders()
+            .map(|order| Action::Cancel(order.id()))
+            .collect::<Vec<_>>();
+
+        let (Some(price), Some(anchor)) =
+            (view.last_price().or(view.mid()), self.anchor)
+        else {
+            return actions;
+        };
+        let (side, price) = if price > anchor.saturating_add(self.threshold) {
+            (OrderSide::Bid, view.best_ask())
+        } else if price < anchor.saturating_sub(self.threshold) {
+            (OrderSide::Ask, view.best_bid())
+        } else {
+            (OrderSide::Bid, None)
+        };
+        if let Some(price) = price {
+            actions.push(Action::Limit {
+                side,
+                price,
+                amount: self.amount,
+            });
+        }
+        actions
+    }
+
+    /// Measure the next move from the price left behind, so that the taker
+    /// does not chase its own impact.
+    fn observe(&mut self, events: &[Event<Order>], view: &View<'_>) {
+        let traded =
+            events.iter().any(|event| matches!(event, Event::Traded(_)));
+        if traded || self.anchor.is_none() {
+            self.anchor = view.last_price().or(view.mid());
+        }
+    }
+}
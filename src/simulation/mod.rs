@@ -0,0 +1,323 @@
+//! Agent-based simulation of a market around an [`Engine`].
+//!
+//! Agents arrive on the market as independent Poisson processes, on a
+//! simulated clock which the engine timestamps trades with. On each arrival
+//! an agent sees the book and sends orders or cancels, then observes the
+//! events they caused. The whole run is determined by its seed.
+
+use std::cmp::Reverse;
+use std::collections::BinaryHeap;
+
+use rand::rngs::StdRng;
+use rand::{Rng, SeedableRng};
+
+use crate::engine::{Engine, Event, ManualClock, Order, OrderId};
+use crate::{ExchangeExt, Opposite, OrderSide};
+
+mod agents;
+pub use agents::{MarketMaker, MomentumTaker, NoiseTrader};
+
+mod report;
+pub use report::{AgentReport, Report, SpreadSample};
+
+/// Trading strategy taking part in a [`Simulation`].
+pub trait Agent {
+    /// Name shown in the report.
+    fn name(&self) -> &str;
+
+    /// Mean number of arrivals per second.
+    fn rate(&self) -> f64;
+
+    /// Decide what to send to the market on arrival.
+    fn act(&mut self, view: &View<'_>, rng: &mut StdRng) -> Vec<Action>;
+
+    /// See the `events` caused by the actions just sent, and the market
+    /// after them.
+    fn observe(&mut self, _events: &[Event<Order>], _view: &View<'_>) {}
+}
+
+/// Request an agent sends to the market.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Action {
+    /// Place a limit order of `amount` at `price`.
+    Limit {
+        side: OrderSide,
+        price: u64,
+        amount: u64,
+    },
+    /// Cancel one of the agent's resting orders.
+    Cancel(OrderId),
+}
+
+/// What an agent sees of the market.
+pub struct View<'a> {
+    engine: &'a Engine,
+    account_id: u64,
+    position: i128,
+    last_price: Option<u64>,
+}
+
+impl<'a> View<'a> {
+    /// Milliseconds elapsed on the simulated clock.
+    #[inline]
+    pub fn now(&self) -> u64 {
+        self.engine.now()
+    }
+
+    #[inline]
+    pub fn best_ask(&self) -> Option<u64> {
+        let depth = self.engine.orderbook().depth(OrderSide::Ask, 1);
+        depth.first().map(|&(price, _)| price)
+    }
+
+    #[inline]
+    pub fn best_bid(&self) -> Option<u64> {
+        let depth = self.engine.orderbook().depth(OrderSide::Bid, 1);
+        depth.first().map(|&(price, _)| price)
+    }
+
+    /// Midpoint of the best prices, when both sides rest.
+    #[inline]
+    pub fn mid(&self) -> Option<u64> {
+        let (ask, bid) = self.engine.orderbook().spread()?;
+        Some(ask.midpoint(bid))
+    }
+
+    /// Price of the last trade of the simulation.
+    #[inline]
+    pub fn last_price(&self) -> Option<u64> {
+        self.last_price
+    }
+
+    /// Return up to `levels` price levels of `side`, from the best one.
+    #[inline]
+    pub fn depth(&self, side: OrderSide, levels: usize) -> Vec<(u64, u64)> {
+        self.engine.orderbook().depth(side, levels)
+    }
+
+    /// Amount bought minus amount sold by the agent so far.
+    #[inline]
+    pub fn position(&self) -> i128 {
+        self.position
+    }
+
+    /// Iterate over the agent's resting orders.
+    #[inline]
+    pub fn open_orders(&self) -> impl Iterator<Item = &'a Order> + 'a {
+        self.engine.open_orders(self.account_id)
+    }
+}
+
+/// Agent along with what it did so far. Its account is its index plus one.
+struct Participant {
+    agent: Box<dyn Agent>,
+    report: AgentReport,
+}
+
+/// Market of a single pair where agents trade against each other.
+pub struct Simulation {
+    engine: Engine,
+    clock: ManualClock,
+    rng: StdRng,
+    participants: Vec<Participant>,
+    /// Next arrival of each agent, in microseconds.
+    arrivals: BinaryHeap<Reverse<(u64, usize)>>,
+    now: u64,
+    order_id: u64,
+    last_price: Option<u64>,
+    interval: u64,
+    volume: u64,
+    trades: u64,
+    spreads: Vec<SpreadSample>,
+    events: Vec<Event<Order>>,
+}
+
+impl Simulation {
+    /// Create an empty market trading `pair`, drawing every random number
+    /// from `seed`.
+    pub fn new(pair: &str, seed: u64) -> Self {
+        let clock = ManualClock::new(0);
+        Self {
+            engine: Engine::new(pair).with_clock(clock.clone()),
+            clock,
+            rng: StdRng::seed_from_u64(seed),
+            participants: Vec::new(),
+            arrivals: BinaryHeap::new(),
+            now: 0,
+            order_id: 0,
+            last_price: None,
+            interval: 1_000,
+            volume: 0,
+            trades: 0,
+            spreads: Vec::new(),
+            events: Vec::new(),
+        }
+    }
+
+    /// Sample the best prices every `interval` milliseconds instead of every
+    /// second.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `interval` is zero.
+    #[inline]
+    pub fn with_interval(mut self, interval: u64) -> Self {
+        assert!(interval > 0, "a sampling interval of at least 1ms");
+        self.interval = interval;
+        self
+    }
+
+    /// Add `agent` to the market, returning its account identifier.
+    pub fn add(&mut self, agent: impl Agent + 'static) -> u64 {
+        let index = self.participants.len();
+        let arrival = self.now.saturating_add(gap(&mut self.rng, agent.rate()));
+        self.arrivals.push(Reverse((arrival, index)));
+        self.participants.push(Participant {
+            report: AgentReport::new(agent.name()),
+            agent: Box::new(agent),
+        });
+        index as u64 + 1
+    }
+
+    /// Run the market for `duration` more milliseconds.
+    pub fn run(&mut self, duration: u64) {
+        let end = self.now.saturating_add(duration.saturating_mul(1_000));
+        while let Some(&Reverse((arrival, index))) = self.arrivals.peek() {
+            // Arrivals at the end of time never come.
+            if arrival > end || arrival == u64::MAX {
+                break;
+            }
+            self.arrivals.pop();
+            self.advance(arrival);
+            self.step(index);
+
+            let rate = self.participants[index].agent.rate();
+            let next = arrival.saturating_add(gap(&mut self.rng, rate));
+            self.arrivals.push(Reverse((next, index)));
+        }
+        self.advance(end);
+    }
+
+    #[inline]
+    pub fn engine(&self) -> &Engine {
+        &self.engine
+    }
+
+    /// Summarize the run so far, marking positions to the last price.
+    pub fn report(&self) -> Report {
+        let agents = self
+            .participants
+            .iter()
+            .map(|participant| participant.report.marked(self.last_price))
+            .collect();
+        Report::new(
+            self.trades,
+            self.volume,
+            self.last_price,
+            self.spreads.clone(),
+            agents,
+        )
+    }
+
+    /// Move the clock to `now` microseconds, sampling the best prices at
+    /// every interval crossed.
+    fn advance(&mut self, now: u64) {
+        let interval = self.interval.saturating_mul(1_000);
+        let mut sample = match self.spreads.last() {
+            Some(last) => (last.time() * 1_000).checked_add(interval),
+            None => Some(0),
+        };
+        while let Some(time) = sample.filter(|time| *time <= now) {
+            let spread = self.engine.orderbook().spread();
+            self.spreads.push(SpreadSample::new(
+                time / 1_000,
+                spread.map(|(_, bid)| bid),
+                spread.map(|(ask, _)| ask),
+            ));
+            sample = time.checked_add(interval);
+        }
+        self.now = now;
+        self.clock.set(now / 1_000);
+    }
+
+    /// Let the agent at `index` act, then show it what it caused.
+    fn step(&mut self, index: usize) {
+        let account_id = index as u64 + 1;
+        let participant = &mut self.participants[index];
+        let view = View {
+            engine: &self.engine,
+            account_id,
+            position: participant.report.position(),
+            last_price: self.last_price,
+        };
+        let actions = participant.agent.act(&view, &mut self.rng);
+
+        self.events.clear();
+        for action in actions {
+            let (events, placing) = match action {
+                Action::Limit {
+                    side,
+                    price,
+                    amount,
+                } => {
+                    self.order_id += 1;
+                    let order_id = OrderId::new(self.order_id);
+                    let order =
+                        Order::new(order_id, account_id, side, price, amount);
+                    (self.engine.create(order), true)
+                }
+                Action::Cancel(order_id) => {
+                    (self.engine.delete(order_id), false)
+                }
+            };
+            let report = &mut participant.report;
+            let rejected = events
+                .iter()
+                .any(|event| matches!(event, Event::Rejected(..)));
+            match (rejected, placing) {
+                (true, _) => report.rejects += 1,
+                (false, true) => report.orders += 1,
+                (false, false) => report.cancels += 1,
+            }
+            self.events.extend(events);
+        }
+
+        for event in &self.events {
+            if let Event::Traded(trade) = event {
+                let side = trade.aggressor();
+                for (account_id, side) in [
+                    (trade.taker_account(), side),
+                    (trade.maker_account(), side.opposite()),
+                ] {
+                    self.participants[account_id as usize - 1].report.fill(
+                        side,
+                        trade.amount(),
+                        trade.price(),
+                    );
+                }
+                self.trades += 1;
+                self.volume = self.volume.saturating_add(trade.amount());
+                self.last_price = Some(trade.price());
+            }
+        }
+
+        let participant = &mut self.participants[index];
+        let view = View {
+            engine: &self.engine,
+            account_id,
+            position: participant.report.position(),
+            last_price: self.last_price,
+        };
+        participant.agent.observe(&self.events, &view);
+    }
+}
+
+/// Draw the microseconds until the next arrival of a Poisson process of
+/// `rate` arrivals per second.
+fn gap(rng: &mut StdRng, rate: f64) -> u64 {
+    if rate <= 0.0 {
+        return u64::MAX;
+    }
+    let uniform = 1.0 - rng.gen::<f64>();
+    (-uniform.ln() / rate * 1_000_000.0).round() as u64
+}
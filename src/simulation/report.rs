@@ -0,0 +1,221 @@
+use compact_str::CompactString;
+
+use crate::OrderSide;
+
+#[cfg(feature = "serde")]
+use serde::{Deserialize, Serialize};
+
+/// Outcome of a simulation run.
+#[derive(Clone, Debug, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub struct Report {
+    trades: u64,
+    volume: u64,
+    last_price: Option<u64>,
+    spreads: Vec<SpreadSample>,
+    agents: Vec<AgentReport>,
+}
+
+impl Report {
+    #[inline]
+    pub(super) fn new(
+        trades: u64,
+        volume: u64,
+        last_price: Option<u64>,
+        spreads: Vec<SpreadSample>,
+        agents: Vec<AgentReport>,
+    ) -> Self {
+        Self {
+            trades,
+            volume,
+            last_price,
+            spreads,
+            agents,
+        }
+    }
+
+    /// Number of trades.
+    #[inline]
+    pub fn trades(&self) -> u64 {
+        self.trades
+    }
+
+    /// Amount exchanged over every trade.
+    #[inline]
+    pub fn volume(&self) -> u64 {
+        self.volume
+    }
+
+    /// Price of the last trade, which positions are marked to.
+    #[inline]
+    pub fn last_price(&self) -> Option<u64> {
+        self.last_price
+    }
+
+    /// Best prices sampled at every interval, from the start of the run.
+    #[inline]
+    pub fn spreads(&self) -> &[SpreadSample] {
+        &self.spreads
+    }
+
+    /// What each agent did, in the order they were added.
+    #[inline]
+    pub fn agents(&self) -> &[AgentReport] {
+        &self.agents
+    }
+}
+
+/// Best prices of the book at some point of a run.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub struct SpreadSample {
+    time: u64,
+    bid: Option<u64>,
+    ask: Option<u64>,
+}
+
+impl SpreadSample {
+    #[inline]
+    pub(super) fn new(time: u64, bid: Option<u64>, ask: Option<u64>) -> Self {
+        Self { time, bid, ask }
+    }
+
+    /// Milliseconds elapsed on the simulated clock.
+    #[inline]
+    pub fn time(&self) -> u64 {
+        self.time
+    }
+
+    #[inline]
+    pub fn bid(&self) -> Option<u64> {
+        self.bid
+    }
+
+    #[inline]
+    pub fn ask(&self) -> Option<u64> {
+        self.ask
+    }
+
+    /// Distance between the best prices, when both sides rest.
+    #[inline]
+    pub fn spread(&self) -> Option<u64> {
+        Some(self.ask? - self.bid?)
+    }
+}
+
+/// What an agent did over a run.
+///
+/// Amounts and prices are expressed in the same units as
+/// [`Order`](crate::engine::Order), and cash and PnL in units of the quote
+/// asset.
+#[derive(Clone, Debug, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub struct AgentReport {
+    name: CompactString,
+    pub(super) orders: u64,
+    pub(super) cancels: u64,
+    pub(super) rejects: u64,
+    trades: u64,
+    volume: u64,
+    position: i128,
+    /// Quote asset received minus paid, a hundred times over.
+    cash: i128,
+    pnl: i128,
+}
+
+impl AgentReport {
+    #[inline]
+    pub(super) fn new(name: &str) -> Self {
+        Self {
+            name: name.into(),
+            orders: 0,
+            cancels: 0,
+            rejects: 0,
+            trades: 0,
+            volume: 0,
+            position: 0,
+            cash: 0,
+            pnl: 0,
+        }
+    }
+
+    /// Account `amount` bought or sold at `price`.
+    pub(super) fn fill(&mut self, side: OrderSide, amount: u64, price: u64) {
+        let (amount, cost) =
+            (i128::from(amount), i128::from(amount) * i128::from(price));
+        match side {
+            OrderSide::Bid => {
+                self.position += amount;
+                self.cash -= cost;
+            }
+            OrderSide::Ask => {
+                self.position -= amount;
+                self.cash += cost;
+            }
+        }
+        self.trades += 1;
+        self.volume = self.volume.saturating_add(amount as u64);
+    }
+
+    /// Return a copy with the position valued at `price`, if any.
+    pub(super) fn marked(&self, price: Option<u64>) -> Self {
+        let value = self.position * i128::from(price.unwrap_or_default());
+        Self {
+            pnl: (self.cash + value) / 100,
+            ..self.clone()
+        }
+    }
+
+    #[inline]
+    pub fn name(&self) -> &str {
+        &self.name
+    }
+
+    /// Number of orders placed, rejected ones aside.
+    #[inline]
+    pub fn orders(&self) -> u64 {
+        self.orders
+    }
+
+    /// Number of cancels accepted.
+    #[inline]
+    pub fn cancels(&self) -> u64 {
+        self.cancels
+    }
+
+    /// Number of orders and cancels the engine rejected.
+    #[inline]
+    pub fn rejects(&self) -> u64 {
+        self.rejects
+    }
+
+    /// Number of trades taken part in.
+    #[inline]
+    pub fn trades(&self) -> u64 {
+        self.trades
+    }
+
+    /// Amount bought or sold.
+    #[inline]
+    pub fn volume(&self) -> u64 {
+        self.volume
+    }
+
+    /// Amount bought minus amount sold.
+    #[inline]
+    pub fn position(&self) -> i128 {
+        self.position
+    }
+
+    /// Quote asset received minus quote asset paid.
+    #[inline]
+    pub fn cash(&self) -> i128 {
+        self.cash / 100
+    }
+
+    /// Cash plus the position valued at the last price.
+    #[inline]
+    pub fn pnl(&self) -> i128 {
+        self.pnl
+    }
+}
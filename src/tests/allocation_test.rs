@@ -0,0 +1,129 @@
+use std::alloc::{GlobalAlloc, Layout, System};
+use std::cell::Cell;
+
+use crate::engine::{
+    Engine, Event, Order, OrderId, OrderRequest, Orderbook, ProRata, Residual,
+    Trade,
+};
+use crate::{Exchange, ExchangeExt, OrderSide};
+
+/// Counts the allocations made by each thread, so tests running in parallel
+/// do not see each other.
+struct Counting;
+
+thread_local! {
+    static ALLOCATIONS: Cell<usize> = const { Cell::new(0) };
+}
+
+unsafe impl GlobalAlloc for Counting {
+    unsafe fn alloc(&self, layout: Layout) -> *mut u8 {
+        let _ = ALLOCATIONS.try_with(|count| count.set(count.get() + 1));
+        System.alloc(layout)
+    }
+
+    unsafe fn dealloc(&self, ptr: *mut u8, layout: Layout) {
+        System.dealloc(ptr, layout)
+    }
+
+    unsafe fn realloc(
+        &self,
+        ptr: *mut u8,
+        layout: Layout,
+        new_size: usize,
+    ) -> *mut u8 {
+        let _ = ALLOCATIONS.try_with(|count| count.set(count.get() + 1));
+        System.realloc(ptr, layout, new_size)
+    }
+}
+
+#[global_allocator]
+static ALLOCATOR: Counting = Counting;
+
+fn allocations() -> usize {
+    ALLOCATIONS.with(Cell::get)
+}
+
+fn rest(
+    orderbook: &mut Orderbook<Order, Event<Order>, Trade>,
+    first_id: u64,
+    price: u64,
+) {
+    for order_id in first_id..first_id + 4 {
+        orderbook.insert(Order::new(
+            OrderId::new(order_id),
+            order_id,
+            OrderSide::Ask,
+            price,
+            100,
+        ));
+    }
+}
+
+fn sweep(price: u64) -> Order {
+    Order::new(OrderId::new(price), 0, OrderSide::Bid, price, 400)
+}
+
+#[test]
+fn matching_into() {
+    for pro_rata in [false, true] {
+        let mut orderbook =
+            Orderbook::<Order, Event<Order>, Trade>::new("BTC/USDC");
+        if pro_rata {
+            orderbook.set_matching(ProRata::new());
+        }
+        let mut events = Vec::with_capacity(16);
+
+        // Let the scratch buffers grow to the size of a level.
+        rest(&mut orderbook, 1, 1000);
+        orderbook.matching_into(sweep(1000), |_, _| true, &mut events);
+        assert_eq!(events.len(), 4);
+
+        rest(&mut orderbook, 5, 1100);
+        events.clear();
+        let before = allocations();
+        orderbook.matching_into(sweep(1100), |_, _| true, &mut events);
+        assert_eq!(allocations(), before);
+        assert_eq!(events.len(), 4);
+        assert!(orderbook.is_empty());
+    }
+}
+
+#[test]
+fn residual() {
+    let mut orderbook =
+        Orderbook::<Order, Event<Order>, Trade>::new("BTC/USDC")
+            .with_matching(ProRata::new().with_residual(Residual::Largest));
+    let mut events = Vec::with_capacity(16);
+    // Shares of 99.5 leave a residual to hand out on every sweep.
+    let sweep =
+        |price| Order::new(OrderId::new(price), 0, OrderSide::Bid, price, 398);
+
+    rest(&mut orderbook, 1, 1000);
+    orderbook.matching_into(sweep(1000), |_, _| true, &mut events);
+    assert_eq!(events.len(), 4);
+
+    // Asks below the leftovers, which the next sweep does not reach.
+    rest(&mut orderbook, 5, 900);
+    events.clear();
+    let before = allocations();
+    orderbook.matching_into(sweep(900), |_, _| true, &mut events);
+    assert_eq!(allocations(), before);
+    assert_eq!(events.len(), 4);
+    assert_eq!(orderbook.len(), (4, 0));
+}
+
+#[test]
+fn process_into() {
+    let mut engine = Engine::new("BTC/USDC");
+    let mut events = Vec::new();
+    for (order_id, side) in [(1, "SELL"), (2, "BUY")] {
+        let request: OrderRequest = serde_json::from_str(&format!(
+            r#"{{"type_op": "CREATE", "account_id": "{order_id}", "amount": "1", "order_id": "{order_id}", "pair": "BTC/USDC", "limit_price": "10", "side": "{side}"}}"#,
+        ))
+        .expect("a valid order request");
+        engine
+            .try_process_into(request, &mut events)
+            .expect("a well-formed request");
+    }
+    assert!(matches!(events[..], [Event::Added(_), Event::Traded(_)]));
+}
@@ -0,0 +1,122 @@
+use crate::engine::{Engine, Equilibrium, Event, OrderId, OrderRequest, Phase};
+use crate::{Asset, ExchangeExt, OrderSide};
+
+fn call(orders: &[(u64, &str, &str, &str)]) -> Engine {
+    let mut engine = Engine::new("BTC/USDC");
+    assert!(matches!(
+        engine.set_phase(Phase::Auction)[..],
+        [Event::Phase(Phase::Auction), Event::Indicative(None)]
+    ));
+    for (order_id, side, amount, price) in orders {
+        let request: OrderRequest = serde_json::from_str(&format!(
+            r#"{{"type_op": "CREATE", "account_id": "{order_id}", "amount": "{amount}", "order_id": "{order_id}", "pair": "BTC/USDC", "limit_price": "{price}", "side": "{side}"}}"#,
+        ))
+        .expect("a valid order request");
+        let events = engine.process(request);
+        assert!(matches!(
+            events[..],
+            [Event::Added(_), Event::Indicative(_)]
+        ));
+    }
+    engine
+}
+
+fn equilibrium(
+    equilibrium: Option<Equilibrium>,
+) -> Option<(u64, u64, u64, Option<OrderSide>)> {
+    equilibrium.map(|equilibrium| {
+        (
+            equilibrium.price(),
+            equilibrium.volume(),
+            equilibrium.imbalance(),
+            equilibrium.imbalance_side(),
+        )
+    })
+}
+
+#[test]
+fn uncross() {
+    let mut engine = call(&[
+        (1, "BUY", "3", "10.5"),
+        (2, "BUY", "2", "10"),
+        (3, "BUY", "4", "9.5"),
+        (4, "SELL", "2", "9"),
+        (5, "SELL", "3", "9.5"),
+        (6, "SELL", "4", "10.5"),
+    ]);
+    // Both 9.5 and 10 execute 5, but 10 leaves no imbalance.
+    assert_eq!(equilibrium(engine.indicative()), Some((1000, 500, 0, None)));
+    assert_eq!(engine.orderbook().len(), (3, 3));
+
+    let events = engine.set_phase(Phase::Continuous);
+    assert!(matches!(
+        events.last(),
+        Some(Event::Phase(Phase::Continuous))
+    ));
+    let trades = events[..events.len() - 1]
+        .iter()
+        .map(|event| match event {
+            Event::Traded(trade) => {
+                (trade.taker(), trade.maker(), trade.amount(), trade.price())
+            }
+            _ => panic!("expected a trade, got {event:?}"),
+        })
+        .collect::<Vec<_>>();
+    assert_eq!(
+        trades,
+        [
+            (OrderId::new(1), OrderId::new(4), 200, 1000),
+            (OrderId::new(1), OrderId::new(5), 100, 1000),
+            (OrderId::new(2), OrderId::new(5), 200, 1000),
+        ]
+    );
+    assert_eq!(engine.phase(), Phase::Continuous);
+    assert_eq!(engine.orderbook().spread(), Some((1050, 950)));
+}
+
+#[test]
+fn tie_breakers() {
+    // 10 and 11 both execute 4 leaving 1 to buy: buying pressure picks 11.
+    let engine = call(&[
+        (1, "BUY", "5", "11"),
+        (2, "SELL", "2", "9"),
+        (3, "SELL", "2", "10"),
+    ]);
+    assert_eq!(
+        equilibrium(engine.indicative()),
+        Some((1100, 400, 100, Some(OrderSide::Bid)))
+    );
+
+    // Without pressure, the price closest to the reference wins.
+    let engine = call(&[(1, "BUY", "2", "11"), (2, "SELL", "2", "9")]);
+    assert_eq!(equilibrium(engine.indicative()).unwrap().0, 900);
+    assert_eq!(
+        equilibrium(Equilibrium::new(engine.orderbook(), Some(1050)))
+            .unwrap()
+            .0,
+        1100
+    );
+
+    let engine = call(&[(1, "BUY", "2", "9"), (2, "SELL", "2", "11")]);
+    assert_eq!(engine.indicative(), None);
+}
+
+#[test]
+fn priority() {
+    let mut engine = call(&[
+        (1, "BUY", "3", "10"),
+        (2, "BUY", "1", "10"),
+        (3, "SELL", "2", "10"),
+    ]);
+    // A closing auction uncrosses as well.
+    assert!(matches!(
+        engine.set_phase(Phase::Closed)[..],
+        [Event::Traded(_), Event::Phase(Phase::Closed)]
+    ));
+
+    // The partially filled order keeps its place in the queue.
+    let order = engine.order(&OrderId::new(1)).expect("a resting order");
+    assert_eq!(order.remaining(), 100);
+    assert_eq!(engine.queue_position(&OrderId::new(1)), Some(0));
+    assert_eq!(engine.queue_position(&OrderId::new(2)), Some(1));
+}
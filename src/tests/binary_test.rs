@@ -0,0 +1,271 @@
+use rand::rngs::StdRng;
+use rand::{Rng, SeedableRng};
+
+use crate::binary::{
+    decode_request, decode_response, encode_request, encode_response, msg_type,
+    DecodeError, Request, Response,
+};
+use crate::engine::{
+    to_units, Engine, ExecutionReport, Order, OrderId, OrderRequest,
+    RejectReason, ReportGenerator,
+};
+use crate::{Asset, OrderSide};
+
+fn fields(request: &Request) -> (u8, u64, u64, u64, u64, Option<OrderSide>) {
+    match request {
+        Request::Create(order) => (
+            msg_type::CREATE,
+            order.id().into(),
+            order.account_id(),
+            order.limit_price(),
+            order.amount(),
+            Some(order.side()),
+        ),
+        Request::Cancel(order_id) => {
+            (msg_type::CANCEL, (*order_id).into(), 0, 0, 0, None)
+        }
+        Request::Modify {
+            order_id,
+            limit_price,
+            amount,
+        } => (
+            msg_type::MODIFY,
+            (*order_id).into(),
+            0,
+            *limit_price,
+            *amount,
+            None,
+        ),
+    }
+}
+
+fn random_request(rng: &mut StdRng) -> Request {
+    match rng.gen_range(0..3) {
+        0 => Request::Create(Order::new(
+            OrderId::new(rng.gen()),
+            rng.gen(),
+            if rng.gen() {
+                OrderSide::Ask
+            } else {
+                OrderSide::Bid
+            },
+            rng.gen(),
+            rng.gen(),
+        )),
+        1 => Request::Cancel(OrderId::new(rng.gen())),
+        _ => Request::Modify {
+            order_id: OrderId::new(rng.gen()),
+            limit_price: rng.gen(),
+            amount: rng.gen(),
+        },
+    }
+}
+
+#[test]
+fn request_round_trip() {
+    let mut rng = StdRng::seed_from_u64(0x5eed);
+    let requests = (0..1_000)
+        .map(|_| random_request(&mut rng))
+        .collect::<Vec<_>>();
+
+    let mut buffer = Vec::new();
+    for request in &requests {
+        encode_request(request, &mut buffer);
+    }
+
+    let mut content = &buffer[..];
+    for request in &requests {
+        let (decoded, length) = decode_request(content)
+            .expect("a valid message")
+            .expect("a whole message");
+        assert_eq!(fields(&decoded), fields(request));
+        content = &content[length..];
+    }
+    assert!(content.is_empty());
+}
+
+#[test]
+fn order_request() {
+    let mut rng = StdRng::seed_from_u64(0x5eed);
+    for _ in 0..1_000 {
+        let request = random_request(&mut rng);
+        let expected = fields(&request);
+        let converted = match request.into_order_request("BTC/USDC") {
+            order_request @ OrderRequest::Create { .. } => Request::Create(
+                Order::try_from(order_request).expect("a valid create"),
+            ),
+            OrderRequest::Delete { order_id } => {
+                Request::Cancel(OrderId::new(order_id.parse().unwrap()))
+            }
+            OrderRequest::Modify {
+                order_id,
+                amount,
+                limit_price,
+            } => Request::Modify {
+                order_id: OrderId::new(order_id.parse().unwrap()),
+                limit_price: to_units(limit_price).unwrap(),
+                amount: to_units(amount).unwrap(),
+            },
+            other => panic!("unexpected request {other:?}"),
+        };
+        assert_eq!(fields(&converted), expected);
+    }
+}
+
+#[test]
+fn response_round_trip() {
+    let responses = [
+        Response::Ack {
+            msg_type: msg_type::CREATE,
+            order_id: OrderId::new(1),
+        },
+        Response::Reject {
+            order_id: OrderId::new(2),
+            reason: RejectReason::DuplicateOrder,
+        },
+        Response::Fill {
+            order_id: OrderId::new(3),
+            amount: 4,
+            price: 5,
+            leaves: 6,
+        },
+    ];
+
+    let mut buffer = Vec::new();
+    for response in &responses {
+        encode_response(response, &mut buffer);
+    }
+    assert_eq!(buffer.len(), 16 + 16 + 40);
+
+    let mut content = &buffer[..];
+    for response in &responses {
+        let (decoded, length) = decode_response(content)
+            .expect("a valid message")
+            .expect("a whole message");
+        assert_eq!(&decoded, response);
+        content = &content[length..];
+    }
+}
+
+#[test]
+fn engine_responses() {
+    let mut engine = Engine::new("BTC/USDC");
+    let mut generator = ReportGenerator::new();
+    let mut buffer = Vec::new();
+    for request in [
+        Request::Create(Order::new(OrderId::new(1), 1, OrderSide::Ask, 10, 5)),
+        Request::Create(Order::new(OrderId::new(2), 2, OrderSide::Bid, 11, 2)),
+        Request::Cancel(OrderId::new(2)),
+    ] {
+        encode_request(&request, &mut buffer);
+    }
+
+    let mut responses = Vec::new();
+    let mut content = &buffer[..];
+    while let Some((request, length)) = decode_request(content).unwrap() {
+        let reports: Vec<ExecutionReport> = match request {
+            Request::Create(order) => {
+                let events = engine.create(order);
+                generator.created(&order, &events)
+            }
+            Request::Cancel(order_id) => {
+                let events = engine.delete(order_id);
+                generator.reports(&events)
+            }
+            Request::Modify { .. } => unreachable!(),
+        };
+        responses.extend(reports.iter().map(Response::from));
+        content = &content[length..];
+    }
+
+    assert_eq!(
+        responses,
+        [
+            Response::Ack {
+                msg_type: msg_type::CREATE,
+                order_id: OrderId::new(1),
+            },
+            Response::Ack {
+                msg_type: msg_type::CREATE,
+                order_id: OrderId::new(2),
+            },
+            Response::Fill {
+                order_id: OrderId::new(2),
+                amount: 2,
+                price: 10,
+                leaves: 0,
+            },
+            Response::Fill {
+                order_id: OrderId::new(1),
+                amount: 2,
+                price: 10,
+                leaves: 3,
+            },
+            // The filled order cannot be cancelled anymore.
+            Response::Reject {
+                order_id: OrderId::new(2),
+                reason: RejectReason::UnknownOrder,
+            },
+        ]
+    );
+}
+
+#[test]
+fn decode_errors() {
+    let mut buffer = Vec::new();
+    encode_request(&Request::Cancel(OrderId::new(1)), &mut buffer);
+
+    for length in 0..buffer.len() {
+        assert_eq!(decode_request(&buffer[..length]), Ok(None));
+    }
+
+    let mut unknown = buffer.clone();
+    unknown[2] = b'?';
+    assert_eq!(
+        decode_request(&unknown),
+        Err(DecodeError::UnknownMessageType(b'?'))
+    );
+
+    let mut short = buffer.clone();
+    short[0] = 2;
+    assert!(matches!(
+        decode_request(&short),
+        Err(DecodeError::InvalidLength { .. })
+    ));
+
+    buffer.clear();
+    encode_request(
+        &Request::Create(Order::new(OrderId::new(1), 1, OrderSide::Ask, 1, 1)),
+        &mut buffer,
+    );
+    buffer[4] = 3;
+    assert_eq!(decode_request(&buffer), Err(DecodeError::InvalidSide(3)));
+}
+
+#[test]
+fn fuzz_decoders() {
+    let mut rng = StdRng::seed_from_u64(0xf022);
+    let mut buffer = [0u8; 128];
+
+    for _ in 0..100_000 {
+        let length = rng.gen_range(0..buffer.len());
+        rng.fill(&mut buffer[..length]);
+        // Bias towards plausible headers so decoding goes past framing.
+        if length >= 4 && rng.gen() {
+            let msg_length = [16u16, 32, 40][rng.gen_range(0..3)];
+            buffer[..2].copy_from_slice(&msg_length.to_le_bytes());
+            buffer[2] = b"NXMAJF"[rng.gen_range(0..6)];
+        }
+
+        for decoded in [
+            decode_request(&buffer[..length])
+                .map(|decoded| decoded.map(|d| d.1)),
+            decode_response(&buffer[..length])
+                .map(|decoded| decoded.map(|d| d.1)),
+        ] {
+            if let Ok(Some(consumed)) = decoded {
+                assert!(consumed <= length);
+            }
+        }
+    }
+}
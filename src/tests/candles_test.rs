@@ -0,0 +1,113 @@
+use crate::candles::{CandleAggregator, Interval, MAX_FLAT_CANDLES};
+use crate::engine::{Engine, Event, ManualClock, Order, OrderRequest};
+
+fn trade(
+    engine: &mut Engine,
+    order_id: u64,
+    amount: &str,
+    price: &str,
+) -> Vec<Event<Order>> {
+    let requests: Vec<OrderRequest> = serde_json::from_str(&format!(
+        r#"[
+            {{"type_op": "CREATE", "account_id": "1", "amount": "{amount}", "order_id": "{}", "pair": "BTC/USDC", "limit_price": "{price}", "side": "SELL"}},
+            {{"type_op": "CREATE", "account_id": "2", "amount": "{amount}", "order_id": "{}", "pair": "BTC/USDC", "limit_price": "{price}", "side": "BUY"}}
+        ]"#,
+        order_id,
+        order_id + 1,
+    ))
+    .expect("a set of valid order requests");
+    requests
+        .into_iter()
+        .flat_map(|request| engine.process(request))
+        .collect()
+}
+
+#[test]
+fn intervals() {
+    assert_eq!("5m".parse::<Interval>().unwrap(), Interval::FiveMinutes);
+    assert!("2m".parse::<Interval>().is_err());
+    assert_eq!(Interval::Hour.to_string(), "1h");
+    assert_eq!(Interval::Minute.start(125_000), 120_000);
+    assert_eq!(serde_json::to_string(&Interval::Day).unwrap(), r#""1d""#);
+}
+
+#[test]
+fn aggregate() {
+    let clock = ManualClock::new(59_000);
+    let mut aggregator =
+        CandleAggregator::new(Interval::Minute).with_clock(clock.clone());
+    let mut engine = Engine::new("BTC/USDC").with_clock(clock.clone());
+
+    // Nothing is produced before the first trade.
+    aggregator.tick();
+    assert!(aggregator.candles().is_empty());
+
+    let mut events = trade(&mut engine, 1, "1", "10");
+    aggregator.update(&events);
+    clock.advance(1_000);
+    for (order_id, amount, price) in
+        [(3, "2", "12"), (5, "1", "9"), (7, "0.5", "11")]
+    {
+        let trades = trade(&mut engine, order_id, amount, price);
+        aggregator.update(&trades);
+        events.extend(trades);
+    }
+    // Two minutes without trades.
+    clock.advance(180_000);
+    let trades = trade(&mut engine, 9, "1", "13");
+    aggregator.update(&trades);
+    events.extend(trades);
+
+    // Trades are bucketed by their own timestamp, however late they are fed.
+    let mut replay = CandleAggregator::new(Interval::Minute);
+    replay.update(&events);
+    assert_eq!(replay.candles(), aggregator.candles());
+
+    let candles = aggregator.drain_closed();
+    let summary = candles
+        .iter()
+        .map(|candle| {
+            (
+                candle.start(),
+                candle.open(),
+                candle.high(),
+                candle.low(),
+                candle.close(),
+                candle.volume(),
+                candle.trades(),
+            )
+        })
+        .collect::<Vec<_>>();
+    assert_eq!(
+        summary,
+        [
+            (0, 1000, 1000, 1000, 1000, 100, 1),
+            (60_000, 1200, 1200, 900, 1100, 350, 3),
+            (120_000, 1100, 1100, 1100, 1100, 0, 0),
+            (180_000, 1100, 1100, 1100, 1100, 0, 0),
+        ]
+    );
+    assert_eq!(aggregator.candles().len(), 1);
+    assert_eq!(aggregator.candles()[0].start(), 240_000);
+    assert_eq!(aggregator.candles()[0].close(), 1300);
+}
+
+#[test]
+fn clock_jump() {
+    let clock = ManualClock::new(0);
+    let mut aggregator =
+        CandleAggregator::new(Interval::Second).with_clock(clock.clone());
+    let mut engine = Engine::new("BTC/USDC").with_clock(clock.clone());
+
+    aggregator.update(&trade(&mut engine, 1, "1", "10"));
+    // A clock set decades forward only opens the last flat candles.
+    clock.advance(1_700_000_000_500);
+    aggregator.tick();
+
+    let candles = aggregator.candles();
+    assert_eq!(candles.len() as u64, MAX_FLAT_CANDLES + 1);
+    assert_eq!(candles[0].start(), 0);
+    assert_eq!(candles[1].start(), 1_700_000_000_000 - 9_999_000);
+    assert_eq!(candles[candles.len() - 1].start(), 1_700_000_000_000);
+    assert!(candles[1..].iter().all(|candle| candle.close() == 1000));
+}
@@ -0,0 +1,121 @@
+use crate::engine::{
+    CircuitBreaker, Engine, Event, ManualClock, Order, OrderRequest, Phase,
+    VolatilityBand,
+};
+use crate::ExchangeExt;
+
+const DAY: u64 = 24 * 60 * 60 * 1000;
+
+fn create(
+    engine: &mut Engine,
+    order_id: u64,
+    side: &str,
+    amount: &str,
+    price: &str,
+) -> Vec<Event<Order>> {
+    let request: OrderRequest = serde_json::from_str(&format!(
+        r#"{{"type_op": "CREATE", "account_id": "{order_id}", "amount": "{amount}", "order_id": "{order_id}", "pair": "BTC/USDC", "limit_price": "{price}", "side": "{side}"}}"#,
+    ))
+    .expect("a valid order request");
+    engine.process(request)
+}
+
+fn prices(events: &[Event<Order>]) -> Vec<u64> {
+    events
+        .iter()
+        .filter_map(|event| match event {
+            Event::Traded(trade) => Some(trade.price()),
+            _ => None,
+        })
+        .collect()
+}
+
+#[test]
+fn pair_bands() {
+    let breaker =
+        CircuitBreaker::new(VolatilityBand::new(1_000, 60_000, 5_000))
+            .with_pair_band("ETH/USDC", VolatilityBand::new(50, 1_000, 1_000));
+    assert_eq!(breaker.band("BTC/USDC").unwrap().threshold(), 1_000);
+    assert_eq!(breaker.band("ETH/USDC").unwrap().threshold(), 50);
+    // Nothing to compare against before the first trade.
+    assert!(breaker.allows("BTC/USDC", 1, 0));
+}
+
+#[test]
+fn volatility_auction() {
+    let clock = ManualClock::new(0);
+    // 10% away from the prices of the last minute, for 5 seconds.
+    let mut engine = Engine::new("BTC/USDC")
+        .with_clock(clock.clone())
+        .with_circuit_breaker(CircuitBreaker::new(VolatilityBand::new(
+            1_000, 60_000, 5_000,
+        )));
+
+    create(&mut engine, 1, "SELL", "1", "100");
+    assert_eq!(prices(&create(&mut engine, 2, "BUY", "1", "100")), [10000]);
+    create(&mut engine, 3, "SELL", "1", "105");
+    create(&mut engine, 4, "SELL", "1", "115");
+
+    // 105 is within 10% of 100, 115 is not.
+    let events = create(&mut engine, 5, "BUY", "2", "120");
+    assert_eq!(prices(&events), [10500]);
+    assert!(matches!(
+        events[1..],
+        [
+            Event::Added(_),
+            Event::Phase(Phase::Auction),
+            Event::Indicative(Some(_)),
+        ]
+    ));
+    assert_eq!(engine.phase(), Phase::Auction);
+    assert_eq!(engine.orderbook().spread(), Some((11500, 12000)));
+
+    // The auction keeps collecting orders until it runs its course.
+    clock.advance(4_999);
+    assert!(engine.tick().is_empty());
+    clock.advance(1);
+    let events = engine.tick();
+    assert_eq!(prices(&events), [11500]);
+    assert!(matches!(
+        events.last(),
+        Some(Event::Phase(Phase::Continuous))
+    ));
+    assert!(engine.orderbook().is_empty());
+
+    // The auction price is the new reference.
+    assert_eq!(
+        engine
+            .circuit_breaker()
+            .unwrap()
+            .reference("BTC/USDC", 5_000),
+        Some(11500)
+    );
+    create(&mut engine, 6, "SELL", "1", "125");
+    assert_eq!(prices(&create(&mut engine, 7, "BUY", "1", "125")), [12500]);
+}
+
+#[test]
+fn pair_references() {
+    let clock = ManualClock::new(1_000);
+    // An auction without end, which must not overflow the clock.
+    let mut engine = Engine::new("BTC/USDC")
+        .with_clock(clock.clone())
+        .with_circuit_breaker(CircuitBreaker::new(VolatilityBand::new(
+            1_000,
+            60_000,
+            u64::MAX,
+        )));
+
+    create(&mut engine, 1, "SELL", "1", "100");
+    create(&mut engine, 2, "BUY", "1", "100");
+    let breaker = engine.circuit_breaker().unwrap();
+    assert_eq!(breaker.reference("BTC/USDC", 1_000), Some(10000));
+    // Other pairs keep their own history.
+    assert_eq!(breaker.reference("ETH/USDC", 1_000), None);
+
+    create(&mut engine, 3, "SELL", "1", "200");
+    create(&mut engine, 4, "BUY", "1", "200");
+    assert_eq!(engine.phase(), Phase::Auction);
+    clock.advance(DAY);
+    assert!(engine.tick().is_empty());
+}
@@ -0,0 +1,314 @@
+use std::io::Write;
+use std::net::TcpStream;
+use std::str::FromStr;
+use std::thread;
+
+use rust_decimal::Decimal;
+
+use crate::engine::{Engine, OrderId, OrderRequest, ReportGenerator};
+use crate::fix::{
+    decode_request, encode_execution_report, msg_type, tag, Acceptor, FixError,
+    Message, MessageReader, MAX_BODY_LENGTH,
+};
+use crate::OrderSide;
+
+fn new_order_single(
+    cl_ord_id: &str,
+    account: &str,
+    side: &str,
+    qty: &str,
+    price: &str,
+) -> Message {
+    Message::new(msg_type::NEW_ORDER_SINGLE)
+        .with(tag::CL_ORD_ID, cl_ord_id)
+        .with(tag::ACCOUNT, account)
+        .with(tag::SYMBOL, "BTC/USDC")
+        .with(tag::SIDE, side)
+        .with(tag::ORDER_QTY, qty)
+        .with(tag::ORD_TYPE, 2)
+        .with(tag::PRICE, price)
+}
+
+#[test]
+fn encode_decode() {
+    let message = new_order_single("1", "7", "1", "1.5", "63500.25");
+    let mut encoded = message.encode();
+    // Leftovers of a following message must not be consumed.
+    encoded.extend_from_slice(b"8=FIX.4.4\x019=5");
+
+    let (decoded, length) = Message::decode(&encoded)
+        .expect("a valid message")
+        .expect("a whole message");
+    assert_eq!(decoded, message);
+    assert_eq!(&encoded[length..], b"8=FIX.4.4\x019=5");
+    assert!(encoded.starts_with(b"8=FIX.4.4\x019="));
+    assert!(matches!(Message::decode(&encoded[..length - 1]), Ok(None)));
+
+    let mut corrupted = message.encode();
+    corrupted[20] ^= 1;
+    assert!(matches!(
+        Message::decode(&corrupted),
+        Err(FixError::Checksum { .. })
+    ));
+    assert!(matches!(
+        Message::decode(b"8=FIX.4.4\x019=3\x0135=D\x0110=000\x01"),
+        Err(FixError::BodyLength)
+    ));
+    assert!(matches!(
+        Message::decode(b"8=FIX.4.4\x019=18446744073709551615\x0135=D\x01"),
+        Err(FixError::BodyLength)
+    ));
+
+    // A bogus length is refused before the body is read.
+    let header = format!("8=FIX.4.4\x019={}\x01", MAX_BODY_LENGTH + 1);
+    let mut reader = MessageReader::new(header.as_bytes());
+    assert!(matches!(reader.read_message(), Err(FixError::BodyLength)));
+    let mut reader = MessageReader::new(&[b'8'; 1 << 17][..]);
+    assert!(matches!(reader.read_message(), Err(FixError::BodyLength)));
+}
+
+#[test]
+fn decode_requests() {
+    let message = new_order_single("1", "7", "2", "1.5", "63500.25");
+    match decode_request(&message).expect("a valid order") {
+        OrderRequest::Create {
+            account_id,
+            amount,
+            order_id,
+            pair,
+            limit_price,
+            side,
+        } => {
+            assert_eq!(account_id, "7");
+            assert_eq!(amount.to_string(), "1.5");
+            assert_eq!(order_id, "1");
+            assert_eq!(pair, "BTC/USDC");
+            assert_eq!(limit_price.to_string(), "63500.25");
+            assert_eq!(side, OrderSide::Ask);
+        }
+        _ => panic!("expected a CREATE request"),
+    }
+
+    let cancel = Message::new(msg_type::ORDER_CANCEL_REQUEST)
+        .with(tag::CL_ORD_ID, "2")
+        .with(tag::ORIG_CL_ORD_ID, "1");
+    assert!(matches!(
+        decode_request(&cancel),
+        Ok(OrderRequest::Delete { order_id }) if order_id == "1"
+    ));
+
+    let replace = Message::new(msg_type::ORDER_CANCEL_REPLACE_REQUEST)
+        .with(tag::CL_ORD_ID, "3")
+        .with(tag::ORIG_CL_ORD_ID, "1")
+        .with(tag::ORDER_QTY, "2")
+        .with(tag::PRICE, "10");
+    assert!(matches!(
+        decode_request(&replace),
+        Ok(OrderRequest::Modify { order_id, .. }) if order_id == "1"
+    ));
+
+    let market = Message::new(msg_type::NEW_ORDER_SINGLE)
+        .with(tag::CL_ORD_ID, "1")
+        .with(tag::ACCOUNT, "7")
+        .with(tag::SYMBOL, "BTC/USDC")
+        .with(tag::SIDE, "1")
+        .with(tag::ORDER_QTY, "1")
+        .with(tag::ORD_TYPE, 1);
+    assert!(matches!(
+        decode_request(&market),
+        Err(FixError::InvalidValue(tag::ORD_TYPE))
+    ));
+    assert!(matches!(
+        decode_request(&new_order_single("x", "7", "1", "1", "1")),
+        Err(FixError::InvalidValue(tag::CL_ORD_ID))
+    ));
+    assert!(matches!(
+        decode_request(&new_order_single("1", "7", "1", "0", "1")),
+        Err(FixError::InvalidValue(tag::ORDER_QTY))
+    ));
+    assert!(matches!(
+        decode_request(&new_order_single("1", "7", "1", "1", "0.00")),
+        Err(FixError::InvalidValue(tag::PRICE))
+    ));
+    // Values must fit the engine units.
+    assert!(matches!(
+        decode_request(&new_order_single(
+            "1",
+            "7",
+            "1",
+            "100000000000000000000",
+            "1"
+        )),
+        Err(FixError::InvalidValue(tag::ORDER_QTY))
+    ));
+    assert!(matches!(
+        decode_request(&new_order_single("1", "7", "1", "1", "0.001")),
+        Err(FixError::InvalidValue(tag::PRICE))
+    ));
+}
+
+#[test]
+fn average_price() {
+    let mut engine = Engine::new("BTC/USDC");
+    let mut generator = ReportGenerator::new();
+    let mut reports = Vec::new();
+    for message in [
+        new_order_single("1", "1", "2", "1", "10.01"),
+        new_order_single("2", "1", "2", "2", "10.02"),
+        new_order_single("3", "2", "1", "3", "10.02"),
+    ] {
+        let request = decode_request(&message).expect("a valid order");
+        let events = engine.process(request.clone());
+        reports.extend(generator.generate(&request, &events));
+    }
+
+    let avg_px = reports
+        .iter()
+        .filter(|report| report.order_id() == OrderId::new(3))
+        .map(|report| {
+            let message = encode_execution_report(report, "BTC/USDC", 1);
+            Decimal::from_str(message.get(tag::AVG_PX).unwrap()).unwrap()
+        })
+        .collect::<Vec<_>>();
+    // Nothing executed when new, then 1 at 10.01 and 2 at 10.02.
+    assert_eq!(
+        avg_px,
+        [
+            Decimal::ZERO,
+            Decimal::new(1001, 2),
+            (Decimal::new(1001, 2) + Decimal::new(1002, 2) * Decimal::TWO)
+                / Decimal::from(3),
+        ]
+    );
+}
+
+struct Client {
+    stream: TcpStream,
+    reader: MessageReader<TcpStream>,
+    seq_num: u64,
+}
+
+impl Client {
+    fn connect(addr: std::net::SocketAddr, comp_id: &str) -> Self {
+        let stream = TcpStream::connect(addr).expect("a running acceptor");
+        let mut client = Self {
+            reader: MessageReader::new(stream.try_clone().unwrap()),
+            stream,
+            seq_num: 1,
+        };
+        client.send(
+            Message::new(msg_type::LOGON)
+                .with(tag::SENDER_COMP_ID, comp_id)
+                .with(tag::TARGET_COMP_ID, "ENGINE")
+                .with(tag::HEART_BT_INT, 30),
+        );
+        let logon = client.recv();
+        assert_eq!(logon.msg_type(), msg_type::LOGON);
+        assert_eq!(logon.get(tag::TARGET_COMP_ID), Some(comp_id));
+        client
+    }
+
+    fn send(&mut self, message: Message) {
+        let message = message.with(tag::MSG_SEQ_NUM, self.seq_num);
+        self.seq_num += 1;
+        self.stream.write_all(&message.encode()).unwrap();
+    }
+
+    fn recv(&mut self) -> Message {
+        self.reader
+            .read_message()
+            .expect("a valid message")
+            .expect("an open session")
+    }
+}
+
+#[test]
+fn acceptor() {
+    let acceptor = Acceptor::bind("127.0.0.1:0", Engine::new("BTC/USDC"))
+        .expect("a free loopback port");
+    let addr = acceptor.local_addr().unwrap();
+    thread::spawn(move || acceptor.run());
+
+    let mut maker = Client::connect(addr, "MAKER");
+    let mut taker = Client::connect(addr, "TAKER");
+
+    maker.send(new_order_single("1", "1", "2", "2", "100"));
+    let new = maker.recv();
+    assert_eq!(new.msg_type(), msg_type::EXECUTION_REPORT);
+    assert_eq!(new.get(tag::EXEC_TYPE), Some("0"));
+    assert_eq!(new.get(tag::LEAVES_QTY), Some("2"));
+
+    taker.send(new_order_single("2", "2", "1", "0.5", "101"));
+    let new = taker.recv();
+    assert_eq!(new.get(tag::EXEC_TYPE), Some("0"));
+    let fill = taker.recv();
+    assert_eq!(fill.get(tag::EXEC_TYPE), Some("F"));
+    assert_eq!(fill.get(tag::ORD_STATUS), Some("2"));
+    assert_eq!(fill.get(tag::LAST_QTY), Some("0.5"));
+    assert_eq!(fill.get(tag::LAST_PX), Some("100"));
+
+    let partial = maker.recv();
+    assert_eq!(partial.get(tag::CL_ORD_ID), Some("1"));
+    assert_eq!(partial.get(tag::EXEC_TYPE), Some("F"));
+    assert_eq!(partial.get(tag::ORD_STATUS), Some("1"));
+    assert_eq!(partial.get(tag::LEAVES_QTY), Some("1.5"));
+    assert_eq!(partial.get(tag::CUM_QTY), Some("0.5"));
+
+    // Orders of another session can be neither replaced nor cancelled.
+    taker.send(
+        Message::new(msg_type::ORDER_CANCEL_REQUEST)
+            .with(tag::CL_ORD_ID, "3")
+            .with(tag::ORIG_CL_ORD_ID, "1"),
+    );
+    let rejected = taker.recv();
+    assert_eq!(rejected.msg_type(), msg_type::ORDER_CANCEL_REJECT);
+    assert_eq!(rejected.get(tag::CXL_REJ_REASON), Some("1"));
+
+    maker.send(
+        Message::new(msg_type::ORDER_CANCEL_REPLACE_REQUEST)
+            .with(tag::CL_ORD_ID, "3")
+            .with(tag::ORIG_CL_ORD_ID, "1")
+            .with(tag::ORDER_QTY, "1")
+            .with(tag::PRICE, "100"),
+    );
+    let replaced = maker.recv();
+    assert_eq!(replaced.get(tag::EXEC_TYPE), Some("5"));
+    assert_eq!(replaced.get(tag::LEAVES_QTY), Some("0.5"));
+
+    maker.send(
+        Message::new(msg_type::ORDER_CANCEL_REQUEST)
+            .with(tag::CL_ORD_ID, "4")
+            .with(tag::ORIG_CL_ORD_ID, "1"),
+    );
+    let canceled = maker.recv();
+    assert_eq!(canceled.get(tag::EXEC_TYPE), Some("4"));
+    assert_eq!(canceled.get(tag::ORD_STATUS), Some("4"));
+
+    taker.send(
+        Message::new(msg_type::ORDER_CANCEL_REQUEST)
+            .with(tag::CL_ORD_ID, "5")
+            .with(tag::ORIG_CL_ORD_ID, "1"),
+    );
+    let rejected = taker.recv();
+    assert_eq!(rejected.msg_type(), msg_type::ORDER_CANCEL_REJECT);
+    assert_eq!(rejected.get(tag::CXL_REJ_REASON), Some("1"));
+
+    taker.send(new_order_single("x", "2", "1", "1", "1"));
+    let reject = taker.recv();
+    assert_eq!(reject.msg_type(), msg_type::REJECT);
+    assert_eq!(reject.get(tag::REF_SEQ_NUM), Some("5"));
+
+    taker.send(new_order_single(
+        "6",
+        "2",
+        "1",
+        "100000000000000000000",
+        "1",
+    ));
+    let reject = taker.recv();
+    assert_eq!(reject.msg_type(), msg_type::REJECT);
+    assert_eq!(reject.get(tag::REF_SEQ_NUM), Some("6"));
+
+    taker.send(Message::new(msg_type::LOGOUT));
+    assert_eq!(taker.recv().msg_type(), msg_type::LOGOUT);
+}
@@ -0,0 +1,144 @@
+use crate::engine::{
+    CircuitBreaker, Engine, Event, OrderId, OrderRequest, Phase, ProRata,
+    VolatilityBand,
+};
+use crate::OrderSide;
+
+fn create(
+    order_id: u64,
+    side: &str,
+    amount: &str,
+    price: &str,
+) -> OrderRequest {
+    serde_json::from_str(&format!(
+        r#"{{"type_op": "CREATE", "account_id": "{order_id}", "amount": "{amount}", "order_id": "{order_id}", "pair": "BTC/USDC", "limit_price": "{price}", "side": "{side}"}}"#,
+    ))
+    .expect("a valid order request")
+}
+
+fn book(engine: &mut Engine) {
+    for (order_id, amount, price) in [
+        (1, "1", "10"),
+        (2, "2", "10"),
+        (3, "3", "11"),
+        (4, "1", "13"),
+    ] {
+        engine.process(create(order_id, "SELL", amount, price));
+    }
+}
+
+#[test]
+fn simulate() {
+    let mut engine = Engine::new("BTC/USDC");
+    book(&mut engine);
+
+    let impact = engine.orderbook().simulate(OrderSide::Bid, 1150, 400);
+    let fills = impact
+        .fills()
+        .iter()
+        .map(|fill| (fill.maker(), fill.amount(), fill.price()))
+        .collect::<Vec<_>>();
+    assert_eq!(
+        fills,
+        [
+            (OrderId::new(1), 100, 1000),
+            (OrderId::new(2), 200, 1000),
+            (OrderId::new(3), 100, 1100),
+        ]
+    );
+    assert_eq!(impact.levels(), 2);
+    assert_eq!(impact.leftover(), 0);
+    // (300 * 10 + 100 * 11) / 400
+    assert_eq!(impact.average_price(), Some(1025));
+
+    let impact = engine.orderbook().simulate(OrderSide::Bid, 1200, 1000);
+    assert_eq!((impact.filled(), impact.leftover()), (600, 400));
+    let impact = engine.orderbook().simulate(OrderSide::Bid, u64::MAX, 1000);
+    assert_eq!((impact.levels(), impact.leftover()), (3, 300));
+    let impact = engine.orderbook().simulate(OrderSide::Bid, 900, 1000);
+    assert_eq!((impact.levels(), impact.average_price()), (0, None));
+
+    // The book is left untouched, and the real matcher agrees.
+    let trades = engine
+        .process(create(5, "BUY", "4", "11.5"))
+        .iter()
+        .filter_map(|event| match event {
+            Event::Traded(trade) => {
+                Some((trade.maker(), trade.amount(), trade.price()))
+            }
+            _ => None,
+        })
+        .collect::<Vec<_>>();
+    assert_eq!(trades, fills);
+}
+
+#[test]
+fn strategy() {
+    let mut engine = Engine::new("BTC/USDC").with_matching(ProRata::new());
+    book(&mut engine);
+
+    let impact = engine.orderbook().simulate(OrderSide::Bid, 1000, 150);
+    let fills = impact
+        .fills()
+        .iter()
+        .map(|fill| (fill.maker(), fill.amount()))
+        .collect::<Vec<_>>();
+    assert_eq!(fills, [(OrderId::new(1), 50), (OrderId::new(2), 100)]);
+
+    let trades = engine
+        .process(create(5, "BUY", "1.5", "10"))
+        .iter()
+        .filter_map(|event| match event {
+            Event::Traded(trade) => Some((trade.maker(), trade.amount())),
+            _ => None,
+        })
+        .collect::<Vec<_>>();
+    assert_eq!(trades, fills);
+}
+
+#[test]
+fn engine() {
+    // Trades may print up to 10% away from the last price.
+    let mut engine = Engine::new("BTC/USDC").with_circuit_breaker(
+        CircuitBreaker::new(VolatilityBand::new(1_000, 60_000, 5_000)),
+    );
+    book(&mut engine);
+    engine.process(create(5, "BUY", "0.5", "10"));
+
+    let impact = engine.simulate(OrderSide::Bid, 2000, 1000);
+    let fills = impact
+        .fills()
+        .iter()
+        .map(|fill| (fill.maker(), fill.amount(), fill.price()))
+        .collect::<Vec<_>>();
+    assert_eq!(
+        fills,
+        [
+            (OrderId::new(1), 50, 1000),
+            (OrderId::new(2), 200, 1000),
+            (OrderId::new(3), 300, 1100),
+        ]
+    );
+    assert_eq!((impact.levels(), impact.leftover()), (2, 450));
+    // The book alone would sweep the breaching level too.
+    let impact = engine.orderbook().simulate(OrderSide::Bid, 2000, 1000);
+    assert_eq!((impact.levels(), impact.leftover()), (3, 350));
+
+    // The real matcher agrees, then stops for a volatility auction.
+    let trades = engine
+        .process(create(6, "BUY", "10", "20"))
+        .iter()
+        .filter_map(|event| match event {
+            Event::Traded(trade) => {
+                Some((trade.maker(), trade.amount(), trade.price()))
+            }
+            _ => None,
+        })
+        .collect::<Vec<_>>();
+    assert_eq!(trades, fills);
+    assert_eq!(engine.phase(), Phase::Auction);
+
+    // Nothing trades during the auction.
+    let impact = engine.simulate(OrderSide::Bid, 2000, 100);
+    assert_eq!((impact.filled(), impact.leftover()), (0, 100));
+}
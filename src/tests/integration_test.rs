@@ -1,12 +1,20 @@
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::Arc;
+
 use compact_str::CompactString;
 use once_cell::sync::Lazy;
+use rust_decimal::Decimal;
 
-use crate::engine::{Event, Order, Orderbook, Trade};
-use crate::{Asset, Exchange};
+use crate::engine::{
+    Engine, Event, ExecType, FeeSchedule, FeeTier, Ledger, ManualClock, Order,
+    OrderId, OrderRequest, OrderStatus, Orderbook, RejectReason,
+    ReportGenerator, RiskLimits, Trade, TradeTape, VOLUME_WINDOW,
+};
+use crate::{Asset, Exchange, ExchangeExt, OrderSide};
 
 const PAIR: CompactString = CompactString::new_inline("BTC/USDC");
 const MOCK_SIZE: usize = 6;
-const ORDERS: Lazy<[Order; MOCK_SIZE]> = Lazy::new(|| {
+static ORDERS: Lazy<[Order; MOCK_SIZE]> = Lazy::new(|| {
     let input = include_str!("./mock_orders.json");
     serde_json::from_str(input)
         .expect("a set of valid orders with MOCK_SIZE length")
@@ -33,3 +41,555 @@ fn orderbook() {
     assert_eq!(orderbook.matching(ORDERS[4]).len(), 1);
     assert_eq!(orderbook.matching(ORDERS[5]).len(), 2);
 }
+
+#[test]
+fn remove_within_level() {
+    let mut orderbook = Orderbook::<Order, Event<Order>, Trade>::new(&PAIR);
+    for order_id in 1..=4 {
+        orderbook.insert(Order::new(
+            OrderId::new(order_id),
+            order_id,
+            OrderSide::Ask,
+            1000,
+            100,
+        ));
+    }
+
+    // Middle, then newest, then oldest: the rest keep their time priority.
+    assert!(orderbook.remove(&OrderId::new(2)).is_some());
+    assert!(orderbook.remove(&OrderId::new(4)).is_some());
+    assert_eq!(orderbook.queue_position(&OrderId::new(3)), Some(1));
+    assert!(orderbook.remove(&OrderId::new(1)).is_some());
+    assert_eq!(orderbook.queue_position(&OrderId::new(3)), Some(0));
+    assert!(orderbook.remove(&OrderId::new(2)).is_none());
+    assert_eq!(orderbook.depth(OrderSide::Ask, 5), [(1000, 100)]);
+
+    let order = orderbook.pop(&OrderSide::Ask).expect("a resting order");
+    assert_eq!(order.id(), OrderId::new(3));
+    assert!(orderbook.is_empty());
+    assert!(orderbook.depth(OrderSide::Ask, 5).is_empty());
+}
+
+fn requests(input: &str) -> Vec<OrderRequest> {
+    serde_json::from_str(input).expect("a set of valid order requests")
+}
+
+#[test]
+fn delete_all() {
+    let mut engine = Engine::new(&PAIR);
+    let orders = requests(
+        r#"[
+            {"type_op": "CREATE", "account_id": "1", "amount": "1", "order_id": "1", "pair": "BTC/USDC", "limit_price": "10", "side": "BUY"},
+            {"type_op": "CREATE", "account_id": "1", "amount": "1", "order_id": "2", "pair": "BTC/USDC", "limit_price": "11", "side": "BUY"},
+            {"type_op": "CREATE", "account_id": "1", "amount": "1", "order_id": "3", "pair": "BTC/USDC", "limit_price": "20", "side": "SELL"},
+            {"type_op": "CREATE", "account_id": "2", "amount": "1", "order_id": "4", "pair": "BTC/USDC", "limit_price": "10", "side": "BUY"}
+        ]"#,
+    );
+    for order in orders {
+        engine.process(order);
+    }
+    assert_eq!(engine.orderbook().len(), (1, 3));
+
+    let mut deletes = requests(
+        r#"[
+            {"type_op": "DELETE_ALL", "account_id": "1", "pair": "ETH/USDC"},
+            {"type_op": "DELETE_ALL", "account_id": "1", "side": "BUY"},
+            {"type_op": "DELETE_ALL", "account_id": "1"}
+        ]"#,
+    )
+    .into_iter();
+
+    // A request for another pair leaves the book and its sequence alone.
+    assert!(engine.process(deletes.next().unwrap()).is_empty());
+    assert_eq!(engine.orderbook().len(), (1, 3));
+    assert_eq!(engine.sequence(), 4);
+
+    let removed = engine.process(deletes.next().unwrap());
+    assert_eq!(removed.len(), 2);
+    assert!(removed
+        .iter()
+        .all(|event| matches!(event, Event::Removed(_))));
+    assert_eq!(engine.orderbook().len(), (1, 1));
+    assert_eq!(engine.orderbook().spread(), Some((2000, 1000)));
+
+    assert_eq!(engine.process(deletes.next().unwrap()).len(), 1);
+    assert_eq!(engine.orderbook().len(), (0, 1));
+    assert_eq!(
+        engine.orderbook().peek(&OrderSide::Bid).map(Asset::id),
+        Some(OrderId::new(4))
+    );
+}
+
+#[test]
+fn order_status() {
+    let mut engine = Engine::new(&PAIR);
+    let orders = requests(
+        r#"[
+            {"type_op": "CREATE", "account_id": "1", "amount": "3", "order_id": "1", "pair": "BTC/USDC", "limit_price": "10", "side": "SELL"},
+            {"type_op": "CREATE", "account_id": "2", "amount": "1", "order_id": "2", "pair": "BTC/USDC", "limit_price": "10", "side": "SELL"},
+            {"type_op": "CREATE", "account_id": "1", "amount": "1", "order_id": "3", "pair": "BTC/USDC", "limit_price": "11", "side": "SELL"},
+            {"type_op": "CREATE", "account_id": "3", "amount": "1", "order_id": "4", "pair": "BTC/USDC", "limit_price": "10", "side": "BUY"}
+        ]"#,
+    );
+    for order in orders {
+        engine.process(order);
+    }
+
+    let order = engine.order(&OrderId::new(1)).expect("a resting order");
+    assert_eq!(order.status(), OrderStatus::Partial);
+    assert_eq!(order.side(), OrderSide::Ask);
+    assert_eq!(order.limit_price(), 1000);
+    assert_eq!(order.amount(), 300);
+    assert_eq!(order.filled(), 100);
+    assert_eq!(order.remaining(), 200);
+
+    assert_eq!(engine.queue_position(&OrderId::new(1)), Some(0));
+    assert_eq!(engine.queue_position(&OrderId::new(2)), Some(1));
+    assert_eq!(engine.queue_position(&OrderId::new(3)), Some(0));
+    assert!(engine.order(&OrderId::new(4)).is_none());
+    assert!(engine.queue_position(&OrderId::new(4)).is_none());
+
+    let open_orders = engine.open_orders(1).map(Asset::id).collect::<Vec<_>>();
+    assert_eq!(open_orders, [OrderId::new(1), OrderId::new(3)]);
+    assert_eq!(engine.open_orders(3).count(), 0);
+}
+
+#[test]
+fn finished_order_status() {
+    let mut engine = Engine::new(&PAIR).with_history(3);
+    let orders = requests(
+        r#"[
+            {"type_op": "CREATE", "account_id": "1", "amount": "1", "order_id": "1", "pair": "BTC/USDC", "limit_price": "10", "side": "SELL"},
+            {"type_op": "CREATE", "account_id": "2", "amount": "1", "order_id": "2", "pair": "BTC/USDC", "limit_price": "10", "side": "BUY"},
+            {"type_op": "CREATE", "account_id": "1", "amount": "2", "order_id": "3", "pair": "BTC/USDC", "limit_price": "11", "side": "SELL"},
+            {"type_op": "CREATE", "account_id": "2", "amount": "1", "order_id": "4", "pair": "BTC/USDC", "limit_price": "11", "side": "BUY"},
+            {"type_op": "DELETE", "order_id": "3"},
+            {"type_op": "CREATE", "account_id": "1", "amount": "0", "order_id": "5", "pair": "BTC/USDC", "limit_price": "11", "side": "SELL"},
+            {"type_op": "CREATE", "account_id": "1", "amount": "1", "order_id": "6", "pair": "BTC/USDC", "limit_price": "12", "side": "SELL"}
+        ]"#,
+    );
+    for order in orders {
+        engine.process(order);
+    }
+
+    let status = |order_id| engine.status(&OrderId::new(order_id));
+    assert_eq!(status(4), Some(OrderStatus::Completed));
+    assert_eq!(status(3), Some(OrderStatus::Closed));
+    assert_eq!(status(5), Some(OrderStatus::Rejected));
+    assert_eq!(status(6), Some(OrderStatus::Open));
+    // Orders 1 and 2 were filled first, then forgotten.
+    assert_eq!(status(1), None);
+    assert_eq!(status(2), None);
+    assert_eq!(status(9), None);
+}
+
+#[test]
+fn execution_reports() {
+    let mut engine = Engine::new(&PAIR);
+    let mut generator = ReportGenerator::new();
+    let mut reports = Vec::new();
+    let orders = requests(
+        r#"[
+            {"type_op": "CREATE", "account_id": "1", "amount": "3", "order_id": "1", "pair": "BTC/USDC", "limit_price": "10", "side": "SELL"},
+            {"type_op": "CREATE", "account_id": "2", "amount": "1", "order_id": "2", "pair": "BTC/USDC", "limit_price": "11", "side": "SELL"},
+            {"type_op": "CREATE", "account_id": "3", "amount": "5", "order_id": "3", "pair": "BTC/USDC", "limit_price": "11", "side": "BUY"},
+            {"type_op": "CREATE", "account_id": "3", "amount": "1", "order_id": "3", "pair": "BTC/USDC", "limit_price": "11", "side": "BUY"},
+            {"type_op": "DELETE", "order_id": "3"},
+            {"type_op": "DELETE", "order_id": "9"}
+        ]"#,
+    );
+    for order in orders {
+        let events = engine.process(order.clone());
+        reports.extend(generator.generate(&order, &events));
+    }
+
+    let summary = reports
+        .iter()
+        .map(|report| {
+            (
+                report.order_id(),
+                report.exec_type(),
+                report.order_status(),
+                report.leaves_qty(),
+                report.cum_qty(),
+                report.last_qty(),
+                report.last_px(),
+            )
+        })
+        .collect::<Vec<_>>();
+    #[rustfmt::skip]
+    assert_eq!(
+        summary,
+        [
+            (OrderId::new(1), ExecType::New, OrderStatus::Open, 300, 0, 0, 0),
+            (OrderId::new(2), ExecType::New, OrderStatus::Open, 100, 0, 0, 0),
+            (OrderId::new(3), ExecType::New, OrderStatus::Open, 500, 0, 0, 0),
+            (OrderId::new(3), ExecType::PartialFill, OrderStatus::Partial, 200, 300, 300, 1000),
+            (OrderId::new(1), ExecType::Fill, OrderStatus::Completed, 0, 300, 300, 1000),
+            (OrderId::new(3), ExecType::PartialFill, OrderStatus::Partial, 100, 400, 100, 1100),
+            (OrderId::new(2), ExecType::Fill, OrderStatus::Completed, 0, 100, 100, 1100),
+            (OrderId::new(3), ExecType::Rejected, OrderStatus::Rejected, 0, 0, 0, 0),
+            (OrderId::new(3), ExecType::Canceled, OrderStatus::Closed, 0, 400, 0, 0),
+            (OrderId::new(9), ExecType::Rejected, OrderStatus::Rejected, 0, 0, 0, 0),
+        ]
+    );
+    assert_eq!(reports[5].avg_px(), Decimal::from(1025));
+    assert_eq!(reports[7].reason(), Some(RejectReason::DuplicateOrder));
+    assert_eq!(reports[9].reason(), Some(RejectReason::UnknownOrder));
+    assert_eq!(reports[9].side(), None);
+
+    // The notional of the largest orders does not fit a decimal.
+    let orders = requests(
+        r#"[
+            {"type_op": "CREATE", "account_id": "1", "amount": "184467440737095516.15", "order_id": "10", "pair": "BTC/USDC", "limit_price": "184467440737095516.15", "side": "SELL"},
+            {"type_op": "CREATE", "account_id": "2", "amount": "184467440737095516.15", "order_id": "11", "pair": "BTC/USDC", "limit_price": "184467440737095516.15", "side": "BUY"}
+        ]"#,
+    );
+    let reports = orders
+        .into_iter()
+        .flat_map(|order| {
+            let events = engine.process(order.clone());
+            generator.generate(&order, &events)
+        })
+        .collect::<Vec<_>>();
+    assert_eq!(reports.len(), 4);
+    assert!(reports[2..]
+        .iter()
+        .all(|report| report.avg_px() == Decimal::from(u64::MAX)));
+}
+
+#[test]
+fn modify() {
+    let mut engine = Engine::new(&PAIR);
+    let orders = requests(
+        r#"[
+            {"type_op": "CREATE", "account_id": "1", "amount": "2", "order_id": "1", "pair": "BTC/USDC", "limit_price": "10", "side": "SELL"},
+            {"type_op": "CREATE", "account_id": "2", "amount": "2", "order_id": "2", "pair": "BTC/USDC", "limit_price": "10", "side": "SELL"},
+            {"type_op": "CREATE", "account_id": "3", "amount": "1", "order_id": "3", "pair": "BTC/USDC", "limit_price": "9", "side": "BUY"}
+        ]"#,
+    );
+    for order in orders {
+        engine.process(order);
+    }
+
+    let mut modifies = requests(
+        r#"[
+            {"type_op": "MODIFY", "order_id": "1", "amount": "1", "limit_price": "10"},
+            {"type_op": "MODIFY", "order_id": "1", "amount": "3", "limit_price": "10"},
+            {"type_op": "MODIFY", "order_id": "3", "amount": "1", "limit_price": "10"},
+            {"type_op": "MODIFY", "order_id": "3", "amount": "1", "limit_price": "10"}
+        ]"#,
+    )
+    .into_iter();
+
+    // Decreasing the amount keeps the time priority.
+    let events = engine.process(modifies.next().unwrap());
+    assert!(matches!(events[..], [Event::Modified(_)]));
+    assert_eq!(engine.queue_position(&OrderId::new(1)), Some(0));
+    assert_eq!(engine.order(&OrderId::new(1)).unwrap().remaining(), 100);
+
+    // Increasing it sends the order to the back of the level.
+    engine.process(modifies.next().unwrap());
+    assert_eq!(engine.queue_position(&OrderId::new(1)), Some(1));
+    assert_eq!(engine.order(&OrderId::new(1)).unwrap().remaining(), 300);
+
+    // A modified price is matched again.
+    let events = engine.process(modifies.next().unwrap());
+    assert!(matches!(events[..], [Event::Modified(_), Event::Traded(_)]));
+    assert_eq!(engine.order(&OrderId::new(2)).unwrap().remaining(), 100);
+
+    let events = engine.process(modifies.next().unwrap());
+    assert!(matches!(
+        events[..],
+        [Event::Rejected(_, RejectReason::UnknownOrder)]
+    ));
+}
+
+#[test]
+fn risk_limits() {
+    let mut engine = Engine::new(&PAIR);
+    engine.risk_mut().set_limits(
+        RiskLimits::new()
+            .with_max_amount(1000)
+            .with_max_notional(50_000)
+            .with_price_band(1000)
+            .with_max_open_orders(2)
+            .with_max_position(500),
+    );
+    engine
+        .risk_mut()
+        .set_account_limits(2, RiskLimits::new().with_max_open_orders(3));
+
+    let rejection = |events: Vec<Event<Order>>| match events[..] {
+        [Event::Rejected(_, reason)] => Some(reason),
+        _ => None,
+    };
+    let mut orders = requests(
+        r#"[
+            {"type_op": "CREATE", "account_id": "1", "amount": "11", "order_id": "1", "pair": "BTC/USDC", "limit_price": "10", "side": "BUY"},
+            {"type_op": "CREATE", "account_id": "1", "amount": "6", "order_id": "2", "pair": "BTC/USDC", "limit_price": "100", "side": "BUY"},
+            {"type_op": "CREATE", "account_id": "1", "amount": "2", "order_id": "3", "pair": "BTC/USDC", "limit_price": "100", "side": "BUY"},
+            {"type_op": "CREATE", "account_id": "2", "amount": "1", "order_id": "4", "pair": "BTC/USDC", "limit_price": "120", "side": "SELL"},
+            {"type_op": "CREATE", "account_id": "2", "amount": "1", "order_id": "5", "pair": "BTC/USDC", "limit_price": "109", "side": "SELL"},
+            {"type_op": "CREATE", "account_id": "1", "amount": "2", "order_id": "6", "pair": "BTC/USDC", "limit_price": "90", "side": "BUY"},
+            {"type_op": "CREATE", "account_id": "1", "amount": "1", "order_id": "7", "pair": "BTC/USDC", "limit_price": "96", "side": "BUY"},
+            {"type_op": "CREATE", "account_id": "1", "amount": "1", "order_id": "8", "pair": "BTC/USDC", "limit_price": "97", "side": "BUY"},
+            {"type_op": "CREATE", "account_id": "2", "amount": "1", "order_id": "9", "pair": "BTC/USDC", "limit_price": "108", "side": "SELL"},
+            {"type_op": "CREATE", "account_id": "3", "amount": "2", "order_id": "10", "pair": "BTC/USDC", "limit_price": "109", "side": "BUY"},
+            {"type_op": "CREATE", "account_id": "3", "amount": "4", "order_id": "11", "pair": "BTC/USDC", "limit_price": "109", "side": "BUY"}
+        ]"#,
+    )
+    .into_iter();
+    let mut next =
+        |engine: &mut Engine| rejection(engine.process(orders.next().unwrap()));
+
+    assert_eq!(next(&mut engine), Some(RejectReason::AmountLimit));
+    assert_eq!(next(&mut engine), Some(RejectReason::NotionalLimit));
+    // Without any trade, the band is centered on the mid price once both
+    // sides are quoted.
+    assert_eq!(next(&mut engine), None);
+    assert_eq!(next(&mut engine), None);
+    assert_eq!(next(&mut engine), None);
+    assert_eq!(next(&mut engine), Some(RejectReason::PriceBand));
+    assert_eq!(next(&mut engine), None);
+    assert_eq!(next(&mut engine), Some(RejectReason::OpenOrdersLimit));
+    // Account 2 may rest a third order.
+    assert_eq!(next(&mut engine), None);
+    // Trades are reflected in positions, which count towards the limit.
+    assert_eq!(next(&mut engine), None);
+    assert_eq!(engine.risk().position(3), 200);
+    assert_eq!(engine.risk().position(2), -200);
+    assert_eq!(engine.risk().last_price(), Some(10900));
+    assert_eq!(next(&mut engine), Some(RejectReason::PositionLimit));
+
+    // Modifications are checked too.
+    let modify = requests(
+        r#"[{"type_op": "MODIFY", "order_id": "3", "amount": "5", "limit_price": "100"}]"#,
+    );
+    assert_eq!(
+        rejection(engine.process(modify[0].clone())),
+        Some(RejectReason::PositionLimit)
+    );
+}
+
+#[test]
+fn ledger() {
+    let mut ledger = Ledger::new();
+    ledger.deposit(1, "BTC", 300);
+    ledger.deposit(2, "USDC", 2_500);
+    let mut engine = Engine::new(&PAIR).with_ledger(ledger);
+
+    let mut orders = requests(
+        r#"[
+            {"type_op": "CREATE", "account_id": "1", "amount": "2", "order_id": "1", "pair": "BTC/USDC", "limit_price": "10", "side": "SELL"},
+            {"type_op": "CREATE", "account_id": "1", "amount": "1.5", "order_id": "2", "pair": "BTC/USDC", "limit_price": "11", "side": "SELL"},
+            {"type_op": "CREATE", "account_id": "2", "amount": "1.5", "order_id": "3", "pair": "BTC/USDC", "limit_price": "12", "side": "BUY"},
+            {"type_op": "CREATE", "account_id": "2", "amount": "1", "order_id": "4", "pair": "BTC/USDC", "limit_price": "11", "side": "BUY"},
+            {"type_op": "MODIFY", "order_id": "4", "amount": "1", "limit_price": "3"},
+            {"type_op": "DELETE", "order_id": "1"}
+        ]"#,
+    )
+    .into_iter();
+    let mut next = |engine: &mut Engine| engine.process(orders.next().unwrap());
+    let balance = |engine: &Engine, account_id, asset| {
+        let balance = engine.ledger().unwrap().balance(account_id, asset);
+        (balance.total(), balance.available())
+    };
+
+    next(&mut engine);
+    assert_eq!(balance(&engine, 1, "BTC"), (300, 100));
+    assert!(matches!(
+        next(&mut engine)[..],
+        [Event::Rejected(_, RejectReason::InsufficientBalance)]
+    ));
+
+    // Buying at 12 reserves 18, but only 15 is spent at 10.
+    let events = next(&mut engine);
+    assert!(matches!(events[..], [Event::Traded(_)]));
+    assert_eq!(balance(&engine, 1, "BTC"), (150, 100));
+    assert_eq!(balance(&engine, 1, "USDC"), (1_500, 1_500));
+    assert_eq!(balance(&engine, 2, "BTC"), (150, 150));
+    assert_eq!(balance(&engine, 2, "USDC"), (1_000, 1_000));
+
+    assert!(matches!(
+        next(&mut engine)[..],
+        [Event::Rejected(_, RejectReason::InsufficientBalance)]
+    ));
+    assert!(matches!(
+        next(&mut engine)[..],
+        [Event::Rejected(_, RejectReason::UnknownOrder)]
+    ));
+
+    // Cancelling releases what is left.
+    next(&mut engine);
+    assert_eq!(balance(&engine, 1, "BTC"), (150, 150));
+    assert_eq!(engine.ledger().unwrap().reserved(&OrderId::new(1)), 0);
+    assert!(engine
+        .ledger_mut()
+        .unwrap()
+        .withdraw(1, "BTC", 151)
+        .is_err());
+}
+
+#[test]
+#[should_panic(expected = "a ledger attached before any order rests")]
+fn late_ledger() {
+    let mut engine = Engine::new(&PAIR);
+    engine.process(requests(
+        r#"[{"type_op": "CREATE", "account_id": "1", "amount": "1", "order_id": "1", "pair": "BTC/USDC", "limit_price": "10", "side": "SELL"}]"#,
+    ).remove(0));
+    let _ = engine.with_ledger(Ledger::new());
+}
+
+#[test]
+fn fees() {
+    let schedule = FeeSchedule::new(10, 20)
+        .with_tier(FeeTier::new(1_000_000, -5, 10))
+        .with_pair_tier("ETH/USDC", FeeTier::new(0, 0, 0));
+    assert_eq!(
+        schedule.tier("ETH/USDC", 1_000_000),
+        Some(FeeTier::new(0, 0, 0))
+    );
+
+    let clock = ManualClock::new(0);
+    let mut ledger = Ledger::new();
+    ledger.deposit(1, "BTC", 300);
+    ledger.deposit(2, "USDC", 3_000_000);
+    let mut engine = Engine::new(&PAIR)
+        .with_ledger(ledger)
+        .with_fees(schedule)
+        .with_clock(clock.clone());
+
+    let trade = |engine: &mut Engine, order_id: u64| {
+        let orders = requests(&format!(
+            r#"[
+                {{"type_op": "CREATE", "account_id": "1", "amount": "1", "order_id": "{}", "pair": "BTC/USDC", "limit_price": "10000", "side": "SELL"}},
+                {{"type_op": "CREATE", "account_id": "2", "amount": "1", "order_id": "{}", "pair": "BTC/USDC", "limit_price": "10000", "side": "BUY"}}
+            ]"#,
+            order_id,
+            order_id + 1
+        ));
+        engine.process(orders[0].clone());
+        match &engine.process(orders[1].clone())[..] {
+            [Event::Traded(trade)] => (trade.taker_fee(), trade.maker_fee()),
+            _ => panic!("expected a trade"),
+        }
+    };
+
+    // Fees are rounded up in favour of the exchange.
+    assert_eq!(trade(&mut engine, 1), (1, 1_000));
+    // Both accounts reached the second tier, where makers earn a rebate.
+    assert_eq!(trade(&mut engine, 3), (1, -500));
+    assert_eq!(engine.fees().unwrap().volume(1, engine.now()), 2_000_000);
+
+    let ledger = engine.ledger().unwrap();
+    assert_eq!(ledger.balance(1, "USDC").total(), 1_999_500);
+    assert_eq!(ledger.balance(2, "BTC").total(), 198);
+    assert_eq!(ledger.balance(2, "USDC").total(), 1_000_000);
+    assert_eq!(ledger.fees("USDC"), 500);
+    assert_eq!(ledger.fees("BTC"), 2);
+
+    // Volume older than the window no longer counts.
+    clock.advance(VOLUME_WINDOW);
+    assert_eq!(trade(&mut engine, 5), (1, 1_000));
+
+    // Volume beyond `u64::MAX` saturates instead of overflowing.
+    let mut engine = Engine::new(&PAIR).with_fees(FeeSchedule::new(0, 0));
+    for order_id in [1, 3] {
+        let orders = requests(&format!(
+            r#"[
+                {{"type_op": "CREATE", "account_id": "1", "amount": "184467440737095516.15", "order_id": "{}", "pair": "BTC/USDC", "limit_price": "1", "side": "SELL"}},
+                {{"type_op": "CREATE", "account_id": "2", "amount": "184467440737095516.15", "order_id": "{}", "pair": "BTC/USDC", "limit_price": "1", "side": "BUY"}}
+            ]"#,
+            order_id,
+            order_id + 1
+        ));
+        for order in orders {
+            engine.process(order);
+        }
+    }
+    assert_eq!(engine.fees().unwrap().volume(1, engine.now()), u64::MAX);
+}
+
+#[test]
+fn trade_tape() {
+    let clock = ManualClock::new(1_000);
+    let mut engine = Engine::new(&PAIR).with_clock(clock.clone());
+    let mut tape = TradeTape::new(2);
+    let orders = requests(
+        r#"[
+            {"type_op": "CREATE", "account_id": "1", "amount": "1", "order_id": "1", "pair": "BTC/USDC", "limit_price": "10", "side": "SELL"},
+            {"type_op": "CREATE", "account_id": "2", "amount": "1", "order_id": "2", "pair": "BTC/USDC", "limit_price": "11", "side": "SELL"},
+            {"type_op": "CREATE", "account_id": "3", "amount": "2", "order_id": "3", "pair": "BTC/USDC", "limit_price": "11", "side": "BUY"},
+            {"type_op": "CREATE", "account_id": "4", "amount": "1", "order_id": "4", "pair": "BTC/USDC", "limit_price": "12", "side": "BUY"},
+            {"type_op": "CREATE", "account_id": "1", "amount": "1", "order_id": "5", "pair": "BTC/USDC", "limit_price": "12", "side": "SELL"}
+        ]"#,
+    );
+    for order in orders {
+        clock.advance(1);
+        tape.record(&engine.process(order));
+    }
+
+    let trades = tape
+        .trades(&PAIR)
+        .map(|trade| {
+            (
+                trade.id(),
+                trade.sequence(),
+                trade.timestamp(),
+                trade.aggressor(),
+                trade.taker_account(),
+                trade.maker_account(),
+                trade.price(),
+            )
+        })
+        .collect::<Vec<_>>();
+    // The first trade was dropped from the tape.
+    assert_eq!(
+        trades,
+        [
+            (2, 3, 1_003, OrderSide::Bid, 3, 2, 1100),
+            (3, 5, 1_005, OrderSide::Ask, 1, 4, 1200),
+        ]
+    );
+    assert!(tape.trades(&PAIR).all(|trade| trade.pair() == PAIR));
+    assert_eq!(
+        tape.recent(&PAIR, 1).map(Trade::id).collect::<Vec<_>>(),
+        [3]
+    );
+    assert_eq!(tape.since(&PAIR, 2).map(Trade::id).collect::<Vec<_>>(), [3]);
+    assert_eq!(tape.trades("ETH/USDC").count(), 0);
+    assert_eq!(engine.sequence(), 5);
+}
+
+#[test]
+fn shared_trade_ids() {
+    let trade_ids = Arc::new(AtomicU64::new(0));
+    let mut engines = ["BTC/USDC", "ETH/USDC"]
+        .map(|pair| Engine::new(pair).with_trade_ids(Arc::clone(&trade_ids)));
+
+    let mut ids = Vec::new();
+    for index in 0..4 {
+        let engine = &mut engines[index % 2];
+        let pair = engine.orderbook().pair().to_owned();
+        let orders = requests(&format!(
+            r#"[
+                {{"type_op": "CREATE", "account_id": "1", "amount": "1", "order_id": "{}", "pair": "{pair}", "limit_price": "10", "side": "SELL"}},
+                {{"type_op": "CREATE", "account_id": "2", "amount": "1", "order_id": "{}", "pair": "{pair}", "limit_price": "10", "side": "BUY"}}
+            ]"#,
+            2 * index + 1,
+            2 * index + 2,
+        ));
+        for order in orders {
+            ids.extend(engine.process(order).iter().filter_map(|event| {
+                match event {
+                    Event::Traded(trade) => Some(trade.id()),
+                    _ => None,
+                }
+            }));
+        }
+    }
+    // Each pair still sees its ids increase, and no two trades share one.
+    assert_eq!(ids, [1, 2, 3, 4]);
+    assert_eq!(trade_ids.load(Ordering::Relaxed), 4);
+}
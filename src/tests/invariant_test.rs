@@ -0,0 +1,438 @@
+use compact_str::{format_compact, CompactString};
+use proptest::prelude::*;
+use rust_decimal::Decimal;
+
+use crate::engine::{
+    Engine, Event, InvariantError, Order, OrderId, OrderRequest,
+    OrderRequestError, Orderbook, Phase, RejectReason, RiskLimits, Trade,
+};
+use crate::{Asset, Exchange, ExchangeExt, OrderSide};
+
+const PAIR: CompactString = CompactString::new_inline("BTC/USDC");
+
+#[derive(Clone, Debug)]
+enum Op {
+    Create {
+        account_id: u64,
+        side: OrderSide,
+        limit_price: u64,
+        amount: u64,
+    },
+    /// Delete the nth order created so far.
+    Delete(usize),
+    /// Modify the nth order created so far.
+    Modify {
+        order: usize,
+        limit_price: u64,
+        amount: u64,
+    },
+}
+
+/// Orders around a narrow range of whole prices, so they often cross.
+fn op() -> impl Strategy<Value = Op> {
+    let side = prop_oneof![Just(OrderSide::Ask), Just(OrderSide::Bid)];
+    prop_oneof![
+        6 => (1..4u64, side, 95..=105u64, 0..=10u64).prop_map(
+            |(account_id, side, limit_price, amount)| Op::Create {
+                account_id,
+                side,
+                limit_price,
+                amount,
+            }
+        ),
+        2 => any::<usize>().prop_map(Op::Delete),
+        2 => (any::<usize>(), 95..=105u64, 0..=15u64).prop_map(
+            |(order, limit_price, amount)| Op::Modify {
+                order,
+                limit_price,
+                amount,
+            }
+        ),
+    ]
+}
+
+#[derive(Clone, Copy)]
+struct Resting {
+    order_id: u64,
+    side: OrderSide,
+    limit_price: u64,
+    amount: u64,
+    filled: u64,
+    sequence: u64,
+}
+
+/// Naive price-time matcher keeping every resting order in a vector, to
+/// check the engine against.
+#[derive(Default)]
+struct Reference {
+    resting: Vec<Resting>,
+    sequence: u64,
+}
+
+/// Trades as `(maker, taker, amount, price)`.
+type Trades = Vec<(u64, u64, u64, u64)>;
+
+impl Reference {
+    fn create(
+        &mut self,
+        order_id: u64,
+        side: OrderSide,
+        limit_price: u64,
+        amount: u64,
+    ) -> Option<Trades> {
+        (amount > 0).then(|| {
+            self.matching(Resting {
+                order_id,
+                side,
+                limit_price,
+                amount,
+                filled: 0,
+                sequence: 0,
+            })
+        })
+    }
+
+    fn delete(&mut self, order_id: u64) -> bool {
+        let index = self.position(order_id);
+        index.map(|index| self.resting.remove(index)).is_some()
+    }
+
+    fn modify(
+        &mut self,
+        order_id: u64,
+        limit_price: u64,
+        amount: u64,
+    ) -> Option<Trades> {
+        let index = self.position(order_id)?;
+        let order = &mut self.resting[index];
+        if amount <= order.filled {
+            return None;
+        }
+        if limit_price == order.limit_price && amount <= order.amount {
+            order.amount = amount;
+            return Some(Vec::new());
+        }
+
+        let mut order = self.resting.remove(index);
+        (order.limit_price, order.amount) = (limit_price, amount);
+        Some(self.matching(order))
+    }
+
+    fn matching(&mut self, mut taker: Resting) -> Trades {
+        let mut trades = Vec::new();
+        while taker.filled < taker.amount {
+            let crosses = |maker: &&Resting| match taker.side {
+                OrderSide::Ask => {
+                    maker.side == OrderSide::Bid
+                        && maker.limit_price >= taker.limit_price
+                }
+                OrderSide::Bid => {
+                    maker.side == OrderSide::Ask
+                        && maker.limit_price <= taker.limit_price
+                }
+            };
+            let best = self
+                .resting
+                .iter()
+                .enumerate()
+                .filter(|(_, maker)| crosses(maker))
+                .min_by_key(|(_, maker)| {
+                    let price = match maker.side {
+                        OrderSide::Ask => maker.limit_price,
+                        OrderSide::Bid => u64::MAX - maker.limit_price,
+                    };
+                    (price, maker.sequence)
+                })
+                .map(|(index, _)| index);
+            let Some(index) = best else { break };
+
+            let maker = &mut self.resting[index];
+            let amount =
+                (taker.amount - taker.filled).min(maker.amount - maker.filled);
+            taker.filled += amount;
+            maker.filled += amount;
+            trades.push((
+                maker.order_id,
+                taker.order_id,
+                amount,
+                maker.limit_price,
+            ));
+            if maker.filled == maker.amount {
+                self.resting.remove(index);
+            }
+        }
+
+        if taker.filled < taker.amount {
+            self.sequence += 1;
+            taker.sequence = self.sequence;
+            self.resting.push(taker);
+        }
+        trades
+    }
+
+    fn position(&self, order_id: u64) -> Option<usize> {
+        self.resting
+            .iter()
+            .position(|order| order.order_id == order_id)
+    }
+
+    /// Return the levels of `side` from the best one, as the engine does.
+    fn depth(&self, side: OrderSide) -> Vec<(u64, u64)> {
+        let mut levels = std::collections::BTreeMap::<u64, u64>::new();
+        for order in self.resting.iter().filter(|order| order.side == side) {
+            *levels.entry(order.limit_price).or_default() +=
+                order.amount - order.filled;
+        }
+        match side {
+            OrderSide::Ask => levels.into_iter().collect(),
+            OrderSide::Bid => levels.into_iter().rev().collect(),
+        }
+    }
+
+    /// Return how many orders are ahead of `order` within its level.
+    fn queue_position(&self, order: &Resting) -> usize {
+        self.resting
+            .iter()
+            .filter(|other| {
+                other.side == order.side
+                    && other.limit_price == order.limit_price
+                    && other.sequence < order.sequence
+            })
+            .count()
+    }
+}
+
+fn trades(events: &[Event<Order>]) -> Trades {
+    events
+        .iter()
+        .filter_map(|event| match event {
+            Event::Traded(trade) => Some((
+                trade.maker().into(),
+                trade.taker().into(),
+                trade.amount(),
+                trade.price(),
+            )),
+            _ => None,
+        })
+        .collect()
+}
+
+fn check(engine: &Engine, reference: &Reference) -> Result<(), TestCaseError> {
+    let orderbook = engine.orderbook();
+    // Continuous trading never leaves the book crossed.
+    prop_assert_eq!(orderbook.validate(), Ok(()));
+
+    for side in [OrderSide::Ask, OrderSide::Bid] {
+        prop_assert_eq!(
+            orderbook.depth(side, usize::MAX),
+            reference.depth(side)
+        );
+    }
+    for order in &reference.resting {
+        let order_id = OrderId::new(order.order_id);
+        let remaining = engine.order(&order_id).map(Asset::remaining);
+        prop_assert_eq!(remaining, Some(order.amount - order.filled));
+        prop_assert_eq!(
+            engine.queue_position(&order_id),
+            Some(reference.queue_position(order))
+        );
+    }
+    Ok(())
+}
+
+proptest! {
+    #[test]
+    fn invariants(ops in prop::collection::vec(op(), 1..200)) {
+        let mut engine = Engine::new(&PAIR);
+        let mut reference = Reference::default();
+        let mut created = Vec::new();
+
+        for op in ops {
+            let (request, expected) = match op {
+                Op::Create { account_id, side, limit_price, amount } => {
+                    let order_id = created.len() as u64 + 1;
+                    created.push(order_id);
+                    let request = OrderRequest::Create {
+                        account_id: format_compact!("{account_id}"),
+                        amount: Decimal::from(amount),
+                        order_id: format_compact!("{order_id}"),
+                        pair: PAIR,
+                        limit_price: Decimal::from(limit_price),
+                        side,
+                    };
+                    let expected = reference.create(
+                        order_id,
+                        side,
+                        limit_price * 100,
+                        amount * 100,
+                    );
+                    (request, expected)
+                }
+                Op::Delete(_) | Op::Modify { .. } if created.is_empty() => {
+                    continue
+                }
+                Op::Delete(order) => {
+                    let order_id = created[order % created.len()];
+                    let request = OrderRequest::Delete {
+                        order_id: format_compact!("{order_id}"),
+                    };
+                    let expected =
+                        reference.delete(order_id).then(Vec::new);
+                    (request, expected)
+                }
+                Op::Modify { order, limit_price, amount } => {
+                    let order_id = created[order % created.len()];
+                    let request = OrderRequest::Modify {
+                        order_id: format_compact!("{order_id}"),
+                        amount: Decimal::from(amount),
+                        limit_price: Decimal::from(limit_price),
+                    };
+                    let expected = reference.modify(
+                        order_id,
+                        limit_price * 100,
+                        amount * 100,
+                    );
+                    (request, expected)
+                }
+            };
+
+            let events = engine.process(request);
+            let rejected = events
+                .iter()
+                .any(|event| matches!(event, Event::Rejected(..)));
+            match expected {
+                Some(expected) => {
+                    prop_assert!(!rejected, "unexpected {:?}", events);
+                    prop_assert_eq!(trades(&events), expected);
+                }
+                None => prop_assert!(rejected, "expected a rejection"),
+            }
+            check(&engine, &reference)?;
+        }
+    }
+}
+
+#[test]
+fn zero_amount() {
+    let mut engine = Engine::new(&PAIR);
+    let request = OrderRequest::Create {
+        account_id: format_compact!("1"),
+        // Less than the smallest unit the engine keeps.
+        amount: Decimal::new(1, 3),
+        order_id: format_compact!("1"),
+        pair: PAIR,
+        limit_price: Decimal::from(10),
+        side: OrderSide::Ask,
+    };
+    assert!(matches!(
+        engine.process(request)[..],
+        [Event::Rejected(_, RejectReason::InvalidAmount)]
+    ));
+    assert!(engine.orderbook().is_empty());
+}
+
+#[test]
+fn extreme_values() {
+    let mut engine = Engine::new(&PAIR);
+    let request = OrderRequest::Create {
+        account_id: format_compact!("1"),
+        amount: Decimal::MAX,
+        order_id: format_compact!("1"),
+        pair: PAIR,
+        limit_price: Decimal::from(10),
+        side: OrderSide::Ask,
+    };
+    assert!(matches!(
+        engine.try_process(request),
+        Err(OrderRequestError::InvalidValue(_))
+    ));
+
+    // Both sides of the auction hold more than an amount can.
+    engine.set_phase(Phase::Auction);
+    for (order_id, side) in [
+        (1, OrderSide::Ask),
+        (2, OrderSide::Ask),
+        (3, OrderSide::Bid),
+        (4, OrderSide::Bid),
+    ] {
+        let order = Order::new(OrderId::new(order_id), 1, side, 100, u64::MAX);
+        engine.create(order);
+    }
+    let indicative = engine.indicative().expect("a crossed book");
+    assert_eq!(indicative.volume(), u64::MAX);
+    let events = engine.set_phase(Phase::Continuous);
+    let traded = events
+        .iter()
+        .filter(|event| matches!(event, Event::Traded(_)))
+        .count();
+    assert_eq!(traded, 2);
+    assert!(engine.orderbook().is_empty());
+
+    // The mid price of the widest spread.
+    engine
+        .risk_mut()
+        .set_limits(RiskLimits::new().with_price_band(100));
+    for (order_id, side, price) in [
+        (5, OrderSide::Ask, u64::MAX),
+        (6, OrderSide::Bid, u64::MAX - 2),
+    ] {
+        engine.create(Order::new(OrderId::new(order_id), 1, side, price, 1));
+    }
+    assert_eq!(engine.orderbook().validate(), Ok(()));
+}
+
+#[test]
+fn uncross_overflow() {
+    let mut engine = Engine::new(&PAIR);
+    engine.set_phase(Phase::Auction);
+    // The asks at 100 and the bids at 101.95 or more each add up to more
+    // than an amount can, which must not tie prices the sums tell apart.
+    for (order_id, side, price, amount) in [
+        (1, OrderSide::Ask, 10_000, 7_423_345_081_252_662_271),
+        (2, OrderSide::Bid, 10_255, u64::MAX),
+        (3, OrderSide::Ask, 10_000, 13_093_571_283_691_872_438),
+        (4, OrderSide::Bid, 10_195, 73),
+    ] {
+        engine.create(Order::new(
+            OrderId::new(order_id),
+            1,
+            side,
+            price,
+            amount,
+        ));
+    }
+    let indicative = engine.indicative().expect("a crossed book");
+    assert_eq!(indicative.price(), 10_000);
+
+    engine.set_phase(Phase::Continuous);
+    assert_eq!(engine.orderbook().validate(), Ok(()));
+    assert_eq!(engine.orderbook().spread().map(|(_, bid)| bid), None);
+}
+
+#[test]
+fn broken_books() {
+    let mut engine = Engine::new(&PAIR);
+    engine.set_phase(Phase::Auction);
+    for (order_id, side, price) in
+        [(1, OrderSide::Ask, 100), (2, OrderSide::Bid, 101)]
+    {
+        engine.create(Order::new(OrderId::new(order_id), 1, side, price, 1));
+    }
+    assert_eq!(
+        engine.orderbook().validate(),
+        Err(InvariantError::Crossed { ask: 100, bid: 101 })
+    );
+    engine.set_phase(Phase::Continuous);
+    assert_eq!(engine.orderbook().validate(), Ok(()));
+
+    let mut orderbook = Orderbook::<Order, Event<Order>, Trade>::new(&PAIR);
+    let order: Order = serde_json::from_str(
+        r#"{"id": 3, "account_id": 1, "side": "ASK", "status": "PARTIAL", "amount": 1, "filled": 2, "limit_price": 100}"#,
+    )
+    .expect("a valid order");
+    orderbook.insert(order);
+    assert_eq!(
+        orderbook.validate(),
+        Err(InvariantError::Overfilled(OrderId::new(3)))
+    );
+}
@@ -0,0 +1,157 @@
+use crate::engine::{Engine, OrderRequest};
+use crate::market_data::Depth;
+
+fn process(engine: &mut Engine, input: &str) {
+    let requests: Vec<OrderRequest> =
+        serde_json::from_str(input).expect("a set of valid order requests");
+    for request in requests {
+        engine.process(request);
+    }
+}
+
+#[test]
+fn depth_diff() {
+    let mut engine = Engine::new("BTC/USDC");
+    process(
+        &mut engine,
+        r#"[
+            {"type_op": "CREATE", "account_id": "1", "amount": "1", "order_id": "1", "pair": "BTC/USDC", "limit_price": "10", "side": "BUY"},
+            {"type_op": "CREATE", "account_id": "1", "amount": "2", "order_id": "2", "pair": "BTC/USDC", "limit_price": "10", "side": "BUY"},
+            {"type_op": "CREATE", "account_id": "1", "amount": "1", "order_id": "3", "pair": "BTC/USDC", "limit_price": "9", "side": "BUY"},
+            {"type_op": "CREATE", "account_id": "2", "amount": "1", "order_id": "4", "pair": "BTC/USDC", "limit_price": "12", "side": "SELL"},
+            {"type_op": "CREATE", "account_id": "2", "amount": "1", "order_id": "5", "pair": "BTC/USDC", "limit_price": "11", "side": "SELL"}
+        ]"#,
+    );
+    let before = Depth::new(engine.orderbook());
+    assert_eq!(before.asks(), [(1100, 100), (1200, 100)]);
+    assert_eq!(before.bids(), [(1000, 300), (900, 100)]);
+    assert_eq!(
+        Depth::default().diff(&before),
+        (before.asks(), before.bids())
+    );
+
+    process(
+        &mut engine,
+        r#"[
+            {"type_op": "CREATE", "account_id": "3", "amount": "1.5", "order_id": "6", "pair": "BTC/USDC", "limit_price": "10", "side": "SELL"},
+            {"type_op": "DELETE", "order_id": "3"},
+            {"type_op": "CREATE", "account_id": "3", "amount": "1", "order_id": "7", "pair": "BTC/USDC", "limit_price": "13", "side": "SELL"}
+        ]"#,
+    );
+    let after = Depth::new(engine.orderbook());
+    assert_eq!(
+        before.diff(&after),
+        (vec![(1300, 100)], vec![(1000, 150), (900, 0)])
+    );
+    assert_eq!(after.diff(&after), (vec![], vec![]));
+}
+
+#[cfg(feature = "websocket")]
+#[test]
+fn websocket() {
+    use std::net::TcpStream;
+    use std::thread;
+
+    use serde_json::{json, Value};
+    use tungstenite::stream::MaybeTlsStream;
+    use tungstenite::{Message, WebSocket};
+
+    use crate::market_data::MarketDataServer;
+
+    fn recv(socket: &mut WebSocket<MaybeTlsStream<TcpStream>>) -> Value {
+        loop {
+            match socket.read().expect("an open connection") {
+                Message::Text(text) => {
+                    return serde_json::from_str(&text).expect("valid JSON")
+                }
+                _ => continue,
+            }
+        }
+    }
+
+    let (server, publisher) =
+        MarketDataServer::bind("127.0.0.1:0").expect("a free loopback port");
+    let addr = server.local_addr().unwrap();
+    thread::spawn(move || server.run());
+
+    let mut engine = Engine::new("BTC/USDC");
+    let feed = |engine: &mut Engine, input: &str| {
+        let requests: Vec<OrderRequest> = serde_json::from_str(input).unwrap();
+        for request in requests {
+            let events = engine.process(request);
+            publisher.publish(engine, &events);
+        }
+    };
+    // Orders resting before the publisher is attached show in snapshots.
+    process(
+        &mut engine,
+        r#"[{"type_op": "CREATE", "account_id": "1", "amount": "2", "order_id": "1", "pair": "BTC/USDC", "limit_price": "10", "side": "SELL"}]"#,
+    );
+    publisher.attach(&engine);
+
+    let (mut socket, _) =
+        tungstenite::connect(format!("ws://{addr}")).expect("a running server");
+    socket
+        .send(Message::Text(
+            json!({"type": "SUBSCRIBE", "pair": "BTC/USDC"}).to_string(),
+        ))
+        .unwrap();
+    assert_eq!(
+        recv(&mut socket),
+        json!({"type": "SNAPSHOT", "pair": "BTC/USDC", "asks": [[1000, 200]], "bids": []})
+    );
+
+    feed(
+        &mut engine,
+        r#"[{"type_op": "CREATE", "account_id": "2", "amount": "0.5", "order_id": "2", "pair": "BTC/USDC", "limit_price": "11", "side": "BUY"}]"#,
+    );
+    let trade = recv(&mut socket);
+    assert_eq!(trade["type"], "TRADE");
+    assert_eq!(trade["pair"], "BTC/USDC");
+    assert_eq!(trade["amount"], 50);
+    assert_eq!(trade["price"], 1000);
+    assert!(trade.get("taker_account").is_none());
+    assert!(trade.get("maker_fee").is_none());
+    assert_eq!(
+        recv(&mut socket),
+        json!({"type": "UPDATE", "pair": "BTC/USDC", "asks": [[1000, 150]], "bids": []})
+    );
+
+    feed(
+        &mut engine,
+        r#"[
+            {"type_op": "CREATE", "account_id": "2", "amount": "1", "order_id": "3", "pair": "BTC/USDC", "limit_price": "9", "side": "BUY"},
+            {"type_op": "DELETE", "order_id": "1"}
+        ]"#,
+    );
+    assert_eq!(
+        recv(&mut socket),
+        json!({"type": "UPDATE", "pair": "BTC/USDC", "asks": [], "bids": [[900, 100]]})
+    );
+    assert_eq!(
+        recv(&mut socket),
+        json!({"type": "UPDATE", "pair": "BTC/USDC", "asks": [[1000, 0]], "bids": []})
+    );
+
+    socket
+        .send(Message::Text(
+            json!({"type": "UNSUBSCRIBE", "pair": "BTC/USDC"}).to_string(),
+        ))
+        .unwrap();
+    socket
+        .send(Message::Text(
+            json!({"type": "SUBSCRIBE", "pair": "ETH/USDC"}).to_string(),
+        ))
+        .unwrap();
+    assert_eq!(
+        recv(&mut socket),
+        json!({"type": "SNAPSHOT", "pair": "ETH/USDC", "asks": [], "bids": []})
+    );
+    // Updates of an unsubscribed pair are no longer delivered.
+    feed(&mut engine, r#"[{"type_op": "DELETE", "order_id": "3"}]"#);
+    socket.close(None).unwrap();
+    match socket.read() {
+        Ok(Message::Close(_)) | Err(_) => (),
+        Ok(message) => panic!("unexpected message {message:?}"),
+    }
+}
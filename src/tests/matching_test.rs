@@ -0,0 +1,93 @@
+use crate::engine::{
+    Engine, Event, Fifo, Hybrid, MatchingStrategy, OrderId, OrderRequest,
+    ProRata, Residual,
+};
+use crate::ExchangeExt;
+
+/// Three orders resting at the same level, from the oldest to the newest:
+/// 10 for account 1, 30 for account 2 and 60 for account 3.
+const LEVEL: [(u64, u64); 3] = [(1, 10), (2, 30), (3, 60)];
+
+fn allocate(strategy: impl MatchingStrategy, amount: u64) -> [u64; 3] {
+    let mut fills = [0; 3];
+    strategy.allocate(amount, &LEVEL, &mut fills);
+    fills
+}
+
+#[test]
+fn fifo() {
+    assert_eq!(allocate(Fifo, 50), [10, 30, 10]);
+    assert_eq!(allocate(Fifo, 200), [10, 30, 60]);
+}
+
+#[test]
+fn pro_rata() {
+    // 25 is split 2.5, 7.5 and 15, rounded down to 2, 7 and 15.
+    assert_eq!(allocate(ProRata::new(), 25), [3, 7, 15]);
+    assert_eq!(
+        allocate(ProRata::new().with_residual(Residual::Largest), 25),
+        [2, 7, 16]
+    );
+    // In lots of 5, shares round down to 0, 5 and 15, leaving 5.
+    assert_eq!(allocate(ProRata::new().with_lot(5), 25), [5, 5, 15]);
+    assert_eq!(allocate(ProRata::new(), 50), [5, 15, 30]);
+    assert_eq!(allocate(ProRata::new(), 200), [10, 30, 60]);
+}
+
+#[test]
+fn hybrid() {
+    // The top order takes 10, the LMM 20% of the 40 left, and the remaining
+    // 32 is split pro-rata over 0, 30 and 52: 11.7 and 20.3 are rounded down
+    // and the residual goes to the oldest order with room.
+    let hybrid = Hybrid::new().with_top_order().with_lmm(3, 2_000);
+    assert_eq!(allocate(hybrid, 50), [10, 12, 28]);
+
+    // Without a top order, the LMM takes 50% of 50 then 25 is split over 10,
+    // 30 and 35.
+    let hybrid = Hybrid::new().with_lmm(3, 5_000);
+    assert_eq!(allocate(hybrid, 50), [4, 10, 36]);
+
+    // The LMM takes 80% of 50, then 10 is split over 10, 30 and 20: the
+    // residual goes to the order with the most room left, not the largest.
+    let hybrid = Hybrid::new()
+        .with_lmm(3, 8_000)
+        .with_pro_rata(ProRata::new().with_residual(Residual::Largest));
+    assert_eq!(allocate(hybrid, 50), [1, 6, 43]);
+}
+
+#[test]
+fn engine() {
+    let mut engine =
+        Engine::new("BTC/USDC").with_matching(ProRata::new().with_lot(100));
+    for (order_id, amount) in [(1, "1"), (2, "3"), (3, "6")] {
+        let request: OrderRequest = serde_json::from_str(&format!(
+            r#"{{"type_op": "CREATE", "account_id": "{order_id}", "amount": "{amount}", "order_id": "{order_id}", "pair": "BTC/USDC", "limit_price": "10", "side": "SELL"}}"#,
+        ))
+        .expect("a valid order request");
+        engine.process(request);
+    }
+
+    let request: OrderRequest = serde_json::from_str(
+        r#"{"type_op": "CREATE", "account_id": "4", "amount": "2.5", "order_id": "4", "pair": "BTC/USDC", "limit_price": "10", "side": "BUY"}"#,
+    )
+    .expect("a valid order request");
+    let trades = engine
+        .process(request)
+        .iter()
+        .map(|event| match event {
+            Event::Traded(trade) => (trade.maker(), trade.amount()),
+            _ => panic!("expected a trade, got {event:?}"),
+        })
+        .collect::<Vec<_>>();
+    // Shares of 25, 75 and 150 round down to 0, 0 and 100.
+    assert_eq!(
+        trades,
+        [
+            (OrderId::new(1), 100),
+            (OrderId::new(2), 50),
+            (OrderId::new(3), 100),
+        ]
+    );
+    assert!(engine.order(&OrderId::new(1)).is_none());
+    assert_eq!(engine.orderbook().len(), (2, 0));
+}
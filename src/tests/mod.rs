@@ -1 +1,14 @@
+mod allocation_test;
+mod auction_test;
+mod binary_test;
+mod candles_test;
+mod circuit_breaker_test;
+mod fix_test;
+mod impact_test;
 mod integration_test;
+mod invariant_test;
+mod market_data_test;
+mod matching_test;
+mod phase_test;
+mod server_test;
+mod simulation_test;
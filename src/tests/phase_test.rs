@@ -0,0 +1,144 @@
+use crate::engine::{
+    Engine, Event, ManualClock, Order, OrderRequest, Phase, RejectReason,
+    Schedule,
+};
+use crate::ExchangeExt;
+
+const HOUR: u64 = 60 * 60 * 1000;
+const DAY: u64 = 24 * HOUR;
+
+fn request(json: &str) -> OrderRequest {
+    serde_json::from_str(json).expect("a valid order request")
+}
+
+fn create(order_id: u64, side: &str, price: &str) -> OrderRequest {
+    request(&format!(
+        r#"{{"type_op": "CREATE", "account_id": "1", "amount": "1", "order_id": "{order_id}", "pair": "BTC/USDC", "limit_price": "{price}", "side": "{side}"}}"#,
+    ))
+}
+
+fn delete(order_id: u64) -> OrderRequest {
+    request(&format!(
+        r#"{{"type_op": "DELETE", "order_id": "{order_id}"}}"#
+    ))
+}
+
+fn rejected(events: &[Event<Order>]) -> bool {
+    matches!(events, [Event::Rejected(_, RejectReason::TradingPhase)])
+}
+
+#[test]
+fn accepted_requests() {
+    let mut engine = Engine::new("BTC/USDC");
+    assert_eq!(engine.phase(), Phase::Continuous);
+    engine.process(create(1, "BUY", "10"));
+    engine.process(create(2, "BUY", "10"));
+
+    engine.set_phase(Phase::Halted);
+    assert!(rejected(&engine.process(create(3, "BUY", "10"))));
+    assert!(rejected(&engine.process(delete(1))));
+    let events = engine
+        .process(request(r#"{"type_op": "DELETE_ALL", "account_id": "1"}"#));
+    assert!(matches!(
+        events[..],
+        [
+            Event::Rejected(_, RejectReason::TradingPhase),
+            Event::Rejected(_, RejectReason::TradingPhase)
+        ]
+    ));
+
+    engine.set_phase(Phase::CancelOnly);
+    assert!(rejected(&engine.process(create(3, "BUY", "10"))));
+    assert!(matches!(engine.process(delete(1))[..], [Event::Removed(_)]));
+
+    engine.set_phase(Phase::Closed);
+    assert!(rejected(&engine.process(create(3, "BUY", "10"))));
+    let events = engine
+        .process(request(r#"{"type_op": "DELETE_ALL", "account_id": "1"}"#));
+    assert!(matches!(events[..], [Event::Removed(_)]));
+
+    // Pre-open collects crossing orders without matching them.
+    engine.set_phase(Phase::PreOpen);
+    assert!(matches!(
+        engine.process(create(3, "BUY", "11"))[..],
+        [Event::Added(_)]
+    ));
+    engine.process(create(4, "SELL", "10"));
+    assert_eq!(engine.orderbook().len(), (1, 1));
+    assert!(matches!(
+        engine.set_phase(Phase::Continuous)[..],
+        [Event::Traded(_), Event::Phase(Phase::Continuous)]
+    ));
+    assert!(engine.orderbook().is_empty());
+}
+
+#[test]
+fn admin_request() {
+    let mut engine = Engine::new("BTC/USDC");
+    let events = engine.process(request(
+        r#"{"type_op": "SET_PHASE", "phase": "CANCEL_ONLY", "pair": "ETH/USDC"}"#,
+    ));
+    assert!(events.is_empty());
+    assert_eq!(engine.phase(), Phase::Continuous);
+
+    let events = engine.process(request(
+        r#"{"type_op": "SET_PHASE", "phase": "CANCEL_ONLY"}"#,
+    ));
+    assert!(matches!(events[..], [Event::Phase(Phase::CancelOnly)]));
+    assert_eq!(engine.phase(), Phase::CancelOnly);
+
+    // Entering the current phase again is a no-op.
+    assert!(engine.set_phase(Phase::CancelOnly).is_empty());
+}
+
+#[test]
+fn schedule() {
+    let schedule = Schedule::new()
+        .with_phase(8 * HOUR, Phase::PreOpen)
+        .with_phase(9 * HOUR, Phase::Continuous)
+        .with_phase(17 * HOUR, Phase::Auction)
+        .with_phase(17 * HOUR + 5 * 60 * 1000, Phase::Closed);
+    assert_eq!(
+        schedule.current(DAY + HOUR),
+        Some((17 * HOUR + 300_000, Phase::Closed))
+    );
+    assert_eq!(
+        schedule.next(DAY + 9 * HOUR),
+        Some((DAY + 17 * HOUR, Phase::Auction))
+    );
+
+    let clock = ManualClock::new(DAY + 7 * HOUR);
+    let mut engine = Engine::new("BTC/USDC")
+        .with_clock(clock.clone())
+        .with_schedule(schedule);
+
+    // Overnight the book is closed.
+    assert!(matches!(engine.tick()[..], [Event::Phase(Phase::Closed)]));
+    assert!(engine.tick().is_empty());
+
+    clock.set(DAY + 8 * HOUR);
+    engine.process(create(1, "BUY", "10"));
+    assert_eq!(engine.phase(), Phase::PreOpen);
+    engine.process(create(2, "SELL", "10"));
+
+    // An admin halt holds until the next scheduled switch.
+    engine.set_phase(Phase::Halted);
+    clock.advance(30 * 60 * 1000);
+    assert!(engine.tick().is_empty());
+
+    // Every switch missed while idle is replayed in order.
+    clock.set(DAY + 17 * HOUR + 10 * 60 * 1000);
+    let events = engine.tick();
+    assert!(matches!(
+        events[..],
+        [
+            // The halt interrupted the pre-open call.
+            Event::Traded(_),
+            Event::Phase(Phase::Continuous),
+            Event::Phase(Phase::Auction),
+            Event::Indicative(None),
+            Event::Phase(Phase::Closed),
+        ]
+    ));
+    assert!(engine.orderbook().is_empty());
+}
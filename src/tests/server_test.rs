@@ -0,0 +1,225 @@
+use std::net::{SocketAddr, TcpStream};
+use std::thread;
+use std::time::Duration;
+
+use crate::engine::{
+    Engine, ExecType, ManualClock, OrderId, OrderStatus, Phase, Schedule,
+};
+use crate::server::{read_frame, write_frame, Control, Server, ServerMessage};
+
+struct Client(TcpStream);
+
+impl Client {
+    fn connect(addr: SocketAddr) -> Self {
+        Self(TcpStream::connect(addr).expect("a running server"))
+    }
+
+    fn send(&mut self, message: &str) {
+        write_frame(&mut self.0, message.as_bytes()).unwrap();
+    }
+
+    fn recv(&mut self) -> ServerMessage {
+        let frame = read_frame(&mut self.0)
+            .expect("a valid frame")
+            .expect("an open session");
+        serde_json::from_slice(&frame).expect("a valid message")
+    }
+
+    fn report(&mut self) -> (OrderId, ExecType, OrderStatus) {
+        match self.recv() {
+            ServerMessage::Report(report) => {
+                (report.order_id(), report.exec_type(), report.order_status())
+            }
+            other => panic!("expected a report, got {other:?}"),
+        }
+    }
+}
+
+#[test]
+fn serve() {
+    let server = Server::bind("127.0.0.1:0", Engine::new("BTC/USDC"))
+        .expect("a free loopback port");
+    let addr = server.local_addr().unwrap();
+    thread::spawn(move || server.run());
+
+    let mut subscriber = Client::connect(addr);
+    subscriber.send(r#"{"type_op": "SUBSCRIBE"}"#);
+    assert!(matches!(
+        subscriber.recv(),
+        ServerMessage::Control(Control::Subscribe)
+    ));
+
+    let mut maker = Client::connect(addr);
+    maker.send(
+        r#"{"type_op": "CREATE", "account_id": "1", "amount": "2", "order_id": "1", "pair": "BTC/USDC", "limit_price": "10", "side": "SELL"}"#,
+    );
+    assert_eq!(
+        maker.report(),
+        (OrderId::new(1), ExecType::New, OrderStatus::Open)
+    );
+
+    let mut taker = Client::connect(addr);
+    taker.send(
+        r#"{"type_op": "CREATE", "account_id": "2", "amount": "1", "order_id": "2", "pair": "BTC/USDC", "limit_price": "10", "side": "BUY"}"#,
+    );
+    assert_eq!(
+        taker.report(),
+        (OrderId::new(2), ExecType::New, OrderStatus::Open)
+    );
+    assert_eq!(
+        taker.report(),
+        (OrderId::new(2), ExecType::Fill, OrderStatus::Completed)
+    );
+    assert_eq!(
+        maker.report(),
+        (OrderId::new(1), ExecType::PartialFill, OrderStatus::Partial)
+    );
+    match subscriber.recv() {
+        ServerMessage::Trade(trade) => {
+            let trade = serde_json::to_value(trade).unwrap();
+            assert_eq!(trade["amount"], 100);
+            assert_eq!(trade["price"], 1000);
+            // The parties are only disclosed to their own sessions.
+            assert!(trade.get("taker_account").is_none());
+            assert!(trade.get("maker").is_none());
+        }
+        other => panic!("expected a trade, got {other:?}"),
+    }
+
+    // Sessions may only touch their own orders and accounts.
+    taker.send(r#"{"type_op": "DELETE", "order_id": "1"}"#);
+    assert_eq!(
+        taker.report(),
+        (OrderId::new(1), ExecType::Rejected, OrderStatus::Rejected)
+    );
+    taker.send(
+        r#"{"type_op": "MODIFY", "order_id": "1", "amount": "5", "limit_price": "10"}"#,
+    );
+    assert_eq!(
+        taker.report(),
+        (OrderId::new(1), ExecType::Rejected, OrderStatus::Rejected)
+    );
+    taker.send(r#"{"type_op": "DELETE_ALL", "account_id": "1"}"#);
+    assert!(matches!(taker.recv(), ServerMessage::Error { .. }));
+    taker.send(
+        r#"{"type_op": "CREATE", "account_id": "1", "amount": "1", "order_id": "3", "pair": "BTC/USDC", "limit_price": "9", "side": "BUY"}"#,
+    );
+    assert!(matches!(taker.recv(), ServerMessage::Error { .. }));
+
+    // Phases are switched by the operator only.
+    taker.send(r#"{"type_op": "SET_PHASE", "phase": "HALTED"}"#);
+    assert!(matches!(taker.recv(), ServerMessage::Error { .. }));
+
+    taker.send(r#"{"type_op": "DELETE", "order_id": "x"}"#);
+    assert!(matches!(taker.recv(), ServerMessage::Error { .. }));
+    taker.send("not json");
+    assert!(matches!(taker.recv(), ServerMessage::Error { .. }));
+
+    maker.send(r#"{"type_op": "DELETE_ALL", "account_id": "1"}"#);
+    assert_eq!(
+        maker.report(),
+        (OrderId::new(1), ExecType::Canceled, OrderStatus::Closed)
+    );
+}
+
+#[test]
+fn scheduled_uncross() {
+    const HOUR: u64 = 60 * 60 * 1000;
+    let clock = ManualClock::new(8 * HOUR);
+    let engine = Engine::new("BTC/USDC")
+        .with_clock(clock.clone())
+        .with_schedule(
+            Schedule::new()
+                .with_phase(8 * HOUR, Phase::PreOpen)
+                .with_phase(9 * HOUR, Phase::Continuous),
+        );
+    let server =
+        Server::bind("127.0.0.1:0", engine).expect("a free loopback port");
+    let addr = server.local_addr().unwrap();
+    thread::spawn(move || server.run());
+
+    let mut maker = Client::connect(addr);
+    maker.send(
+        r#"{"type_op": "CREATE", "account_id": "1", "amount": "1", "order_id": "1", "pair": "BTC/USDC", "limit_price": "10", "side": "SELL"}"#,
+    );
+    assert_eq!(
+        maker.report(),
+        (OrderId::new(1), ExecType::New, OrderStatus::Open)
+    );
+    let mut taker = Client::connect(addr);
+    taker.send(
+        r#"{"type_op": "CREATE", "account_id": "2", "amount": "1", "order_id": "2", "pair": "BTC/USDC", "limit_price": "10", "side": "BUY"}"#,
+    );
+    assert_eq!(
+        taker.report(),
+        (OrderId::new(2), ExecType::New, OrderStatus::Open)
+    );
+
+    // The call uncrosses ahead of the next request, even a rejected one, and
+    // both sides hear of it.
+    clock.set(9 * HOUR);
+    taker.send(
+        r#"{"type_op": "CREATE", "account_id": "2", "amount": "0", "order_id": "3", "pair": "BTC/USDC", "limit_price": "10", "side": "BUY"}"#,
+    );
+    assert_eq!(
+        taker.report(),
+        (OrderId::new(2), ExecType::Fill, OrderStatus::Completed)
+    );
+    assert_eq!(
+        maker.report(),
+        (OrderId::new(1), ExecType::Fill, OrderStatus::Completed)
+    );
+    assert_eq!(
+        taker.report(),
+        (OrderId::new(3), ExecType::Rejected, OrderStatus::Rejected)
+    );
+}
+
+#[test]
+fn reconnect() {
+    let server = Server::bind("127.0.0.1:0", Engine::new("BTC/USDC"))
+        .expect("a free loopback port");
+    let addr = server.local_addr().unwrap();
+    thread::spawn(move || server.run());
+
+    let mut first = Client::connect(addr);
+    first.send(
+        r#"{"type_op": "CREATE", "account_id": "1", "amount": "1", "order_id": "1", "pair": "BTC/USDC", "limit_price": "10", "side": "SELL"}"#,
+    );
+    assert_eq!(
+        first.report(),
+        (OrderId::new(1), ExecType::New, OrderStatus::Open)
+    );
+    drop(first);
+
+    // The account is free again once the server notices the drop.
+    let mut second = Client::connect(addr);
+    loop {
+        second.send(
+            r#"{"type_op": "CREATE", "account_id": "1", "amount": "1", "order_id": "2", "pair": "BTC/USDC", "limit_price": "11", "side": "SELL"}"#,
+        );
+        match second.recv() {
+            ServerMessage::Report(report) => {
+                assert_eq!(report.order_id(), OrderId::new(2));
+                assert_eq!(report.exec_type(), ExecType::New);
+                break;
+            }
+            ServerMessage::Error { .. } => {
+                thread::sleep(Duration::from_millis(10))
+            }
+            other => panic!("expected a report, got {other:?}"),
+        }
+    }
+
+    // The orders left behind have no owner, but go with the account.
+    second.send(r#"{"type_op": "DELETE", "order_id": "1"}"#);
+    assert_eq!(
+        second.report(),
+        (OrderId::new(1), ExecType::Rejected, OrderStatus::Rejected)
+    );
+    second.send(r#"{"type_op": "DELETE_ALL", "account_id": "1"}"#);
+    assert_eq!(
+        second.report(),
+        (OrderId::new(2), ExecType::Canceled, OrderStatus::Cancelled)
+    );
+}
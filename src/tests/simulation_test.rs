@@ -0,0 +1,169 @@
+use rand::rngs::StdRng;
+
+use crate::engine::{Event, Order, OrderId};
+use crate::simulation::{
+    Action, Agent, MarketMaker, MomentumTaker, NoiseTrader, Report, Simulation,
+    View,
+};
+use crate::OrderSide;
+
+/// Agent buying the best ask on every arrival.
+#[derive(Default)]
+struct Taker {
+    now: u64,
+}
+
+impl Agent for Taker {
+    fn name(&self) -> &str {
+        "taker"
+    }
+
+    fn rate(&self) -> f64 {
+        10.0
+    }
+
+    fn act(&mut self, view: &View<'_>, _rng: &mut StdRng) -> Vec<Action> {
+        assert!(view.now() >= self.now);
+        self.now = view.now();
+        view.best_ask()
+            .map(|price| Action::Limit {
+                side: OrderSide::Bid,
+                price,
+                amount: 1,
+            })
+            .into_iter()
+            .collect()
+    }
+
+    fn observe(&mut self, events: &[Event<Order>], view: &View<'_>) {
+        for event in events {
+            if let Event::Traded(trade) = event {
+                assert_eq!(trade.taker_account(), 2);
+                assert_eq!(view.last_price(), Some(trade.price()));
+            }
+        }
+        assert!(view.open_orders().next().is_none());
+    }
+}
+
+fn market(seed: u64) -> Report {
+    let mut simulation = Simulation::new("BTC/USDC", seed);
+    simulation.add(MarketMaker::new("maker", 2.0, 150_000));
+    for i in 0..5 {
+        let name = format!("noise-{i}");
+        simulation.add(NoiseTrader::new(&name, 1.0, 150_000));
+    }
+    simulation.add(MomentumTaker::new("momentum", 0.5, 50));
+    simulation.run(300_000);
+    simulation.report()
+}
+
+#[test]
+fn deterministic() {
+    let report = market(7);
+    assert!(report.trades() > 0);
+    assert_eq!(report, market(7));
+    assert_ne!(report, market(8));
+}
+
+#[test]
+fn accounting() {
+    let report = market(42);
+
+    // Every trade has a buyer and a seller among the agents.
+    let agents = report.agents();
+    assert_eq!(agents.iter().map(|agent| agent.position()).sum::<i128>(), 0);
+    assert_eq!(
+        agents.iter().map(|agent| agent.volume()).sum::<u64>(),
+        2 * report.volume()
+    );
+    assert_eq!(
+        agents.iter().map(|agent| agent.trades()).sum::<u64>(),
+        2 * report.trades()
+    );
+    assert!(agents[0].orders() > 0 && agents[0].cancels() > 0);
+
+    // Once the market opens, the market maker keeps both sides quoted.
+    let spreads = report.spreads();
+    assert_eq!(spreads.len(), 301);
+    assert_eq!(spreads[0].spread(), None);
+    assert!(spreads[10..].iter().all(|sample| sample.spread().is_some()));
+}
+
+#[test]
+fn arrivals() {
+    let mut simulation = Simulation::new("BTC/USDC", 1).with_interval(100);
+    simulation.add(NoiseTrader::new("noise", 10.0, 10_000));
+    simulation.run(1_000_000);
+
+    let report = simulation.report();
+    assert_eq!(report.spreads().len(), 10_001);
+    assert_eq!(report.spreads()[1].time(), 100);
+    // 10 arrivals per second over 1000 seconds, each placing an order.
+    let orders = report.agents()[0].orders();
+    assert!((9_700..=10_300).contains(&orders), "{orders}");
+}
+
+#[test]
+fn observe() {
+    let mut simulation = Simulation::new("BTC/USDC", 3);
+    assert_eq!(simulation.add(MarketMaker::new("maker", 5.0, 10_000)), 1);
+    assert_eq!(simulation.add(Taker::default()), 2);
+    simulation.run(100_000);
+    assert!(simulation.engine().orderbook().validate().is_ok());
+
+    let report = simulation.report();
+    let taker = &report.agents()[1];
+    assert_eq!(taker.name(), "taker");
+    assert!(taker.trades() > 0);
+    assert_eq!(taker.volume(), taker.position() as u64);
+}
+
+/// Agent sending requests the engine rejects.
+struct Clumsy;
+
+impl Agent for Clumsy {
+    fn name(&self) -> &str {
+        "clumsy"
+    }
+
+    fn rate(&self) -> f64 {
+        10.0
+    }
+
+    fn act(&mut self, _view: &View<'_>, _rng: &mut StdRng) -> Vec<Action> {
+        vec![
+            Action::Limit {
+                side: OrderSide::Bid,
+                price: 100,
+                amount: 0,
+            },
+            Action::Cancel(OrderId::new(u64::MAX)),
+        ]
+    }
+}
+
+#[test]
+fn rejects() {
+    let mut simulation = Simulation::new("BTC/USDC", 5);
+    simulation.add(Clumsy);
+    simulation.run(1_000);
+
+    let report = simulation.report();
+    let clumsy = &report.agents()[0];
+    assert_eq!((clumsy.orders(), clumsy.cancels()), (0, 0));
+    assert!(clumsy.rejects() > 0 && clumsy.rejects().is_multiple_of(2));
+}
+
+#[test]
+fn endless_run() {
+    let mut simulation = Simulation::new("BTC/USDC", 5).with_interval(u64::MAX);
+    simulation.add(NoiseTrader::new("idle", 0.0, 10_000));
+    simulation.run(u64::MAX);
+    simulation.run(u64::MAX);
+
+    let report = simulation.report();
+    // At the start, and one interval later at the end of time.
+    assert_eq!(report.spreads().len(), 2);
+    assert_eq!(report.agents()[0].orders(), 0);
+}